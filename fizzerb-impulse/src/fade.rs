@@ -0,0 +1,72 @@
+use std::f32::consts::PI;
+
+/// Fades out the end of a sample to silence, such that cutting it off doesn't click.
+pub struct FadeOut {
+    pub sample_rate: f32,
+    /// The length of the fade in seconds. Zero leaves the sample untouched.
+    pub duration: f32,
+}
+
+impl FadeOut {
+    pub fn run(&self, samples: &mut [f32]) {
+        let length = ((self.duration * self.sample_rate) as usize).min(samples.len());
+        let start = samples.len() - length;
+
+        // A raised cosine window, reaching silence at the last sample.
+        for (i, sample) in samples[start..].iter_mut().enumerate() {
+            let progress = (i + 1) as f32 / length as f32;
+            *sample *= 0.5 + 0.5 * (PI * progress).cos();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn faded(sample_rate: f32, duration: f32, length: usize) -> Vec<f32> {
+        let mut samples = vec![1.0; length];
+        FadeOut {
+            sample_rate,
+            duration,
+        }
+        .run(&mut samples);
+        samples
+    }
+
+    #[test]
+    fn fade_reaches_silence_at_the_end() {
+        let samples = faded(100.0, 0.5, 200);
+        // Untouched before the fade starts.
+        assert!(samples[..150].iter().all(|&sample| sample == 1.0));
+        assert!(samples[150] < 1.0 && samples[150] > 0.99);
+        assert!(samples[199].abs() < 1e-6);
+    }
+
+    #[test]
+    fn fade_follows_a_raised_cosine() {
+        let samples = faded(100.0, 0.5, 200);
+        let fade = &samples[150..];
+        for (i, &sample) in fade.iter().enumerate() {
+            let progress = (i + 1) as f32 / fade.len() as f32;
+            let expected = (0.5 * PI * progress).cos().powi(2);
+            assert!((sample - expected).abs() < 1e-5, "{sample} at {i}");
+        }
+        // Monotonically falling, and halfway down halfway through.
+        assert!(fade.windows(2).all(|pair| pair[1] < pair[0]));
+        assert!((fade[24] - 0.5).abs() < 1e-5);
+    }
+
+    #[test]
+    fn zero_duration_leaves_the_samples_untouched() {
+        assert!(faded(48000.0, 0.0, 64).iter().all(|&sample| sample == 1.0));
+    }
+
+    #[test]
+    fn fades_longer_than_the_samples_cover_all_of_them() {
+        let samples = faded(100.0, 10.0, 50);
+        assert!(samples[0] < 1.0);
+        assert!(samples[49].abs() < 1e-6);
+        assert!(samples.windows(2).all(|pair| pair[1] < pair[0]));
+    }
+}
@@ -1,10 +1,15 @@
 //! Renderer for impulse responses.
 
 mod compressor;
+mod fade;
 
 pub use compressor::Compressor;
+pub use fade::FadeOut;
 use fizzerb_model::Response;
-use tracing::{debug, trace};
+use tracing::debug;
+
+/// Samples quieter than this are considered silent.
+const SILENCE: f32 = 0.00001;
 
 #[derive(Debug, Clone)]
 pub struct ImpulseRenderer {
@@ -15,11 +20,13 @@ pub struct ImpulseRenderer {
 }
 
 impl ImpulseRenderer {
-    pub fn new(sample_rate: f32) -> Self {
+    /// Creates a renderer for impulse responses `length` seconds long.
+    pub fn new(sample_rate: f32, length: f32) -> Self {
+        let samples = (length * sample_rate).ceil() as usize;
         Self {
             sample_rate,
             sample_period: 1.0 / sample_rate,
-            audio_buffer: vec![],
+            audio_buffer: vec![0.0; samples],
             responses_in_buffer: 0,
         }
     }
@@ -28,26 +35,21 @@ impl ImpulseRenderer {
         assert!(response.time > 0.0);
 
         let position = (response.time / self.sample_period) as usize;
-        let positive = response.bounces % 2 == 0;
-        self.audio_buffer[position] += response.loudness * if positive { 1.0 } else { -1.0 };
+        // Polarity flips with every reflection after the first, which keeps the first
+        // reflection as loud and in phase as the direct sound.
+        let positive = response.bounces == 0 || response.bounces % 2 == 1;
+        if let Some(sample) = self.audio_buffer.get_mut(position) {
+            *sample += response.loudness * if positive { 1.0 } else { -1.0 };
+        }
     }
 
-    /// Adds the given bounce responses into the audio buffer.
-    ///
-    /// The `responses` buffer is assumed to be sorted from earlie.
+    /// Adds the given bounce responses into the audio buffer, in any order. Responses arriving
+    /// after the end of the impulse response are left out.
     pub fn add_responses(&mut self, responses: &[Response]) {
         if responses.is_empty() {
             return;
         }
 
-        let last_time = responses.last().unwrap().time;
-        assert!(last_time > 0.0);
-        let required_buffer_size = (last_time / self.sample_period).ceil() as usize + 2;
-        if self.audio_buffer.len() < required_buffer_size {
-            trace!("resizing sample buffer to {required_buffer_size}");
-            self.audio_buffer.resize(required_buffer_size, 0.0);
-        }
-
         for &response in responses {
             self.add_response(response);
         }
@@ -55,23 +57,69 @@ impl ImpulseRenderer {
         self.responses_in_buffer += 1;
     }
 
-    /// Renders the audio buffer into a finished sample.
+    /// Renders the audio buffer into a finished sample, as long as the impulse response.
     pub fn render(&self, gain: f32, compressor: Compressor) -> Vec<f32> {
-        let mut output = self.audio_buffer.clone();
-        let truncated_length = (!output.is_empty())
-            .then(|| output.iter().rposition(|&x| x > 0.00001))
-            .flatten()
-            .unwrap_or(0);
-        output.resize(truncated_length, 0.0);
-        debug!("rendering sample with length {truncated_length}");
-
-        let mut input = output.clone();
-        for sample in &mut input {
-            *sample *= gain;
-        }
+        debug!("rendering sample with length {}", self.audio_buffer.len());
 
-        compressor.run(&input[..truncated_length], &mut output);
+        let input: Vec<_> = self
+            .audio_buffer
+            .iter()
+            .map(|&sample| sample * gain)
+            .collect();
+        let mut output = vec![0.0; input.len()];
+        compressor.run(&input, &mut output);
 
         output
     }
 }
+
+/// Returns the length of the sample without the silence at its end.
+pub fn trimmed_length(samples: &[f32]) -> usize {
+    samples
+        .iter()
+        .rposition(|&sample| sample.abs() > SILENCE)
+        .map_or(0, |last| last + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn polarity_flips_after_the_first_reflection() {
+        let mut renderer = ImpulseRenderer::new(1024.0, 0.1);
+        let responses: Vec<_> = (0..4)
+            .map(|bounces| Response {
+                time: (bounces + 1) as f32 / 64.0,
+                loudness: 1.0,
+                bounces,
+            })
+            .collect();
+        renderer.add_responses(&responses);
+
+        let impulses: Vec<_> = responses
+            .iter()
+            .map(|response| renderer.audio_buffer[(response.time * 1024.0) as usize])
+            .collect();
+        assert_eq!(impulses, [1.0, 1.0, -1.0, 1.0]);
+    }
+
+    #[test]
+    fn trimming_cuts_off_trailing_silence() {
+        let samples = [0.0, 0.5, 0.0, -0.25, 0.0, SILENCE, -SILENCE * 0.5, 0.0];
+        assert_eq!(trimmed_length(&samples), 4);
+    }
+
+    #[test]
+    fn trimming_keeps_samples_without_trailing_silence() {
+        let samples = [0.0, 0.5, -0.25];
+        assert_eq!(trimmed_length(&samples), samples.len());
+    }
+
+    #[test]
+    fn silent_samples_are_trimmed_away() {
+        assert_eq!(trimmed_length(&[0.0; 16]), 0);
+        assert_eq!(trimmed_length(&[SILENCE; 16]), 0);
+        assert_eq!(trimmed_length(&[]), 0);
+    }
+}
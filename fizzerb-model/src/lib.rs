@@ -11,39 +11,104 @@ use glam::{vec2, Vec2};
 pub struct Response {
     pub time: f32,
     pub loudness: f32,
+    /// The number of times the sound bounced off of walls. Zero for the direct path.
     pub bounces: usize,
 }
 
+/// The shape of a wall's curve between its start and end points.
+#[derive(Debug, Clone, Copy, Default)]
+pub enum WallShape {
+    /// A straight line segment.
+    #[default]
+    Line,
+    /// A circular arc, bulging out of the straight line between the wall's endpoints by the given
+    /// distance. Positive values bulge in the direction of the wall's normal.
+    Arc { sagitta: f32 },
+    /// A quadratic Bézier curve.
+    QuadraticBezier { control: Vec2 },
+    /// A cubic Bézier curve.
+    CubicBezier {
+        control_start: Vec2,
+        control_end: Vec2,
+    },
+}
+
+/// One of the two sides of a wall.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    /// The side [`Wall::normal`] points towards.
+    Front,
+    Back,
+}
+
 /// A wall made up of a material.
 #[derive(Debug, Clone, Copy)]
 pub struct Wall {
     pub start: Vec2,
     pub end: Vec2,
+    pub shape: WallShape,
     pub material: MaterialIndex,
+    /// The material of the wall's back side, if it differs from the front's.
+    pub back_material: Option<MaterialIndex>,
 }
 
 impl Wall {
-    /// Returns the normal vector of this wall.
+    /// Returns the material of the given side of the wall.
+    pub fn material_on(&self, side: Side) -> MaterialIndex {
+        match side {
+            Side::Front => self.material,
+            Side::Back => self.back_material.unwrap_or(self.material),
+        }
+    }
+
+    /// Returns the normal vector of the straight line between this wall's endpoints.
     pub fn normal(&self) -> Vec2 {
         let direction = (self.end - self.start).normalize();
         vec2(-direction.y, direction.x)
     }
 
-    /// Reflects a ray facing the given `direction` against this wall and returns the reflected ray.
+    /// Reflects a ray facing the given `direction` against the straight line between this wall's
+    /// endpoints and returns the reflected ray.
     pub fn reflect(&self, direction: Vec2) -> Vec2 {
         let normal = self.normal();
         math::reflect(direction, normal)
     }
+
+    /// Returns the circle this wall lies on, as a `(center, radius)` pair, if the wall is an arc.
+    pub fn arc_circle(&self) -> Option<(Vec2, f32)> {
+        if let WallShape::Arc { sagitta } = self.shape {
+            let half_chord = self.start.distance(self.end) * 0.5;
+            if sagitta == 0.0 || half_chord == 0.0 {
+                return None;
+            }
+            let radius = (half_chord * half_chord + sagitta * sagitta) / (2.0 * sagitta.abs());
+            let apex = (self.start + self.end) * 0.5 + self.normal() * sagitta;
+            let center = apex - self.normal() * sagitta.signum() * radius;
+            Some((center, radius))
+        } else {
+            None
+        }
+    }
 }
 
 /// Definition of a wall material.
 #[derive(Debug, Clone)]
 pub struct Material {
-    /// The "color" of the wall - how much sound it absorbs. Since sound doesn't have color,
-    /// this is only a float coefficient.
+    /// The "color" of the wall - the share of sound it reflects rather than absorbs, from 0 for a
+    /// wall absorbing all sound, to 1 for one absorbing none. Since sound doesn't have color, this
+    /// is only a float coefficient.
+    ///
+    /// Each reflection multiplies a ray's energy by this coefficient, and responses are scaled by
+    /// that energy as an amplitude: 0.5 lowers the level by about 6 dB per reflection.
     pub diffuse: f32,
     /// How much sound waves are scattered by the wall.
     pub roughness: f32,
+    /// The share of sound passing through the wall rather than being reflected off of it, from 0
+    /// for a wall sound doesn't get through at all, to 1 for an acoustically transparent one.
+    ///
+    /// Like [`diffuse`](Self::diffuse), this is an amplitude factor: a shadow ray passing through
+    /// the wall carries this share of the amplitude it would have had without the wall.
+    pub transmission: f32,
 }
 
 impl Default for Material {
@@ -51,6 +116,34 @@ impl Default for Material {
         Self {
             diffuse: 1.0,
             roughness: 0.0,
+            transmission: 0.0,
+        }
+    }
+}
+
+/// How a speaker's loudness depends on the direction sound leaves it in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Directivity {
+    /// Equally loud in all directions.
+    #[default]
+    Omnidirectional,
+    /// Loudest in front, silent behind.
+    Cardioid,
+    /// Narrower than a cardioid, with a small lobe behind.
+    Supercardioid,
+    /// Equally loud in front and behind, silent to the sides.
+    Bidirectional,
+}
+
+impl Directivity {
+    /// Returns the gain of sound leaving the speaker at an angle from the direction it's facing,
+    /// given as the cosine of that angle.
+    pub fn gain(self, cos_angle: f32) -> f32 {
+        match self {
+            Directivity::Omnidirectional => 1.0,
+            Directivity::Cardioid => 0.5 + 0.5 * cos_angle,
+            Directivity::Supercardioid => (0.37 + 0.63 * cos_angle).abs(),
+            Directivity::Bidirectional => cos_angle.abs(),
         }
     }
 }
@@ -60,6 +153,16 @@ impl Default for Material {
 pub struct Speaker {
     pub position: Vec2,
     pub power: f32,
+    pub directivity: Directivity,
+    /// The direction the speaker is facing. Must be normalized.
+    pub direction: Vec2,
+}
+
+impl Speaker {
+    /// Returns the gain of sound leaving the speaker in the given (normalized) direction.
+    pub fn gain(&self, direction: Vec2) -> f32 {
+        self.directivity.gain(self.direction.dot(direction))
+    }
 }
 
 /// A microphone that registers impulses from speakers.
@@ -128,6 +231,8 @@ impl Space {
 }
 
 pub mod walls {
+    use std::f32::consts::TAU;
+
     use glam::vec2;
 
     use super::*;
@@ -141,24 +246,54 @@ pub mod walls {
             Wall {
                 start: position,
                 end: position + vec2(size.x, 0.0),
+                shape: WallShape::Line,
                 material,
+                back_material: None,
             },
             Wall {
                 start: position + vec2(size.x, 0.0),
                 end: position + size,
+                shape: WallShape::Line,
                 material,
+                back_material: None,
             },
             Wall {
                 start: position + size,
                 end: position + vec2(0.0, size.y),
+                shape: WallShape::Line,
                 material,
+                back_material: None,
             },
             Wall {
                 start: position + vec2(0.0, size.y),
                 end: position,
+                shape: WallShape::Line,
                 material,
+                back_material: None,
             },
         ]
         .into_iter()
     }
+
+    /// Makes a regular polygon with the given number of `sides`, inscribed in a circle of the given
+    /// `radius`. The first vertex lies at `start_angle` radians from the X axis.
+    pub fn make_regular_polygon(
+        center: Vec2,
+        radius: f32,
+        sides: usize,
+        start_angle: f32,
+        material: MaterialIndex,
+    ) -> impl Iterator<Item = Wall> {
+        let vertex = move |i: usize| {
+            let angle = start_angle + i as f32 / sides as f32 * TAU;
+            center + Vec2::from_angle(angle) * radius
+        };
+        (0..sides).map(move |i| Wall {
+            start: vertex(i),
+            end: vertex(i + 1),
+            shape: WallShape::Line,
+            material,
+            back_material: None,
+        })
+    }
 }
@@ -0,0 +1,275 @@
+//! Checking whether the walls around a point close it off from empty space.
+
+use std::f32::consts::TAU;
+
+use fizzerb_model::{math, Space};
+use glam::Vec2;
+
+use crate::{
+    diffraction::find_edges,
+    ray::{LineSegment, Ray},
+    tracer::trace_to_walls,
+};
+
+/// The number of rays cast around the point when looking for gaps.
+const PROBE_RAYS: usize = 720;
+
+/// The number of times probe rays bounce off of walls, to find gaps which aren't in plain sight.
+const PROBE_BOUNCES: usize = 8;
+
+/// How far back along an escaping ray to look for the gap it escaped through, in metres. Rays
+/// bouncing right at a wall's free end start their escape on the line between the ends, or even
+/// slightly past it.
+const GAP_LOOKBEHIND: f32 = 0.01;
+
+/// An opening in the walls, through which rays escape into empty space.
+#[derive(Debug, Clone, Copy)]
+pub struct Gap {
+    /// One of the rays which escaped through the gap, starting at its last bounce.
+    pub escape: Ray,
+    /// The positions of the free wall ends closest to either side of the escaping ray, if there
+    /// are any. For a gap between two walls, these are the ends the gap lies between.
+    pub sides: [Option<Vec2>; 2],
+}
+
+/// A place where traced rays escaped the space.
+#[derive(Debug, Clone, Copy)]
+pub struct Leak {
+    /// The middle of the gap the rays escaped through, or the closest free wall end if the gap
+    /// isn't between two of them.
+    pub position: Vec2,
+    /// The number of rays which escaped through the gap.
+    pub rays: usize,
+}
+
+/// Casts rays around `origin` and returns the gaps through which they escape the space. Rays
+/// escaping through the same gap are only reported once. A point enclosed by walls has no gaps.
+///
+/// Walls letting sound through still count as closed, since rays that pass through them don't
+/// escape into empty space by themselves.
+pub fn find_gaps(space: &Space, origin: Vec2) -> Vec<Gap> {
+    let free_ends: Vec<_> = find_edges(space)
+        .into_iter()
+        .map(|edge| edge.position)
+        .collect();
+
+    let mut gaps: Vec<Gap> = vec![];
+    for i in 0..PROBE_RAYS {
+        let direction = Vec2::from_angle(i as f32 / PROBE_RAYS as f32 * TAU);
+        let escape = match escape_ray(space, origin, direction) {
+            Some(escape) => escape,
+            None => continue,
+        };
+        let sides = gap_sides(&free_ends, escape);
+        if !gaps.iter().any(|gap| gap.sides == sides) {
+            gaps.push(Gap { escape, sides });
+        }
+    }
+    gaps
+}
+
+/// Groups the escaping parts of traced rays by the gap they escaped through, and returns where the
+/// gaps are, most used first.
+pub fn find_leaks(space: &Space, escapes: &[Ray]) -> Vec<Leak> {
+    let free_ends: Vec<_> = find_edges(space)
+        .into_iter()
+        .map(|edge| edge.position)
+        .collect();
+
+    let mut leaks: Vec<([Option<Vec2>; 2], Leak)> = vec![];
+    for &escape in escapes {
+        let sides = gap_sides(&free_ends, escape);
+        match leaks
+            .iter_mut()
+            .find(|(leak_sides, _)| *leak_sides == sides)
+        {
+            Some((_, leak)) => leak.rays += 1,
+            None => {
+                let position = match sides {
+                    [Some(a), Some(b)] => (a + b) * 0.5,
+                    [Some(end), None] | [None, Some(end)] => end,
+                    [None, None] => escape.start,
+                };
+                leaks.push((sides, Leak { position, rays: 1 }));
+            }
+        }
+    }
+    leaks.sort_by_key(|(_, leak)| std::cmp::Reverse(leak.rays));
+    leaks.into_iter().map(|(_, leak)| leak).collect()
+}
+
+/// Returns the free wall ends on either side of the gap the escaping ray passes through.
+fn gap_sides(free_ends: &[Vec2], escape: Ray) -> [Option<Vec2>; 2] {
+    let lookout = Ray {
+        start: escape.start - escape.direction * GAP_LOOKBEHIND,
+        ..escape
+    };
+    gap_ends(free_ends, lookout).unwrap_or_else(|| flanking_ends(free_ends, lookout))
+}
+
+/// Follows a ray bouncing off of walls, and returns the part of it that escapes into empty space,
+/// if it does.
+fn escape_ray(space: &Space, origin: Vec2, direction: Vec2) -> Option<Ray> {
+    let mut ray = Ray {
+        start: origin,
+        direction,
+    };
+    for _ in 0..=PROBE_BOUNCES {
+        let hit = match trace_to_walls(ray, space) {
+            Some(hit) => hit,
+            None => return Some(ray),
+        };
+        ray = hit.ray.leave(math::reflect(ray.direction, hit.ray.normal));
+    }
+    None
+}
+
+/// Finds the pair of free wall ends with the shortest distance between them, which the escaping
+/// ray passes between.
+fn gap_ends(free_ends: &[Vec2], escape: Ray) -> Option<[Option<Vec2>; 2]> {
+    let mut closest: Option<(f32, [Option<Vec2>; 2])> = None;
+    for (i, &a) in free_ends.iter().enumerate() {
+        for &b in &free_ends[i + 1..] {
+            let width = a.distance(b);
+            let narrower = !closest.is_some_and(|(closest_width, _)| closest_width <= width);
+            if narrower && escape.cast(LineSegment { a, b }).is_some() {
+                closest = Some((width, [Some(a), Some(b)]));
+            }
+        }
+    }
+    closest.map(|(_, sides)| sides)
+}
+
+/// Finds the free wall ends which most closely flank the escaping ray on its left and right, as
+/// seen from where it starts. Used when the ray doesn't pass between any pair of free ends.
+fn flanking_ends(free_ends: &[Vec2], escape: Ray) -> [Option<Vec2>; 2] {
+    let mut sides = [None; 2];
+    let mut closest_angles = [f32::INFINITY; 2];
+    for &end in free_ends {
+        let offset = end - escape.start;
+        let along = escape.direction.dot(offset);
+        let across = escape.direction.perp_dot(offset);
+        if along <= 0.0 {
+            continue;
+        }
+        let side = if across >= 0.0 { 0 } else { 1 };
+        let angle = across.abs().atan2(along);
+        if angle < closest_angles[side] {
+            closest_angles[side] = angle;
+            sides[side] = Some(end);
+        }
+    }
+    sides
+}
+
+#[cfg(test)]
+mod tests {
+    use fizzerb_model::{walls::make_box, Material, MaterialIndex, Wall, WallShape};
+    use glam::vec2;
+
+    use super::*;
+
+    fn line(start: Vec2, end: Vec2, material: MaterialIndex) -> Wall {
+        Wall {
+            start,
+            end,
+            shape: WallShape::Line,
+            material,
+            back_material: None,
+        }
+    }
+
+    /// Checks that the gap lies between the two points, in either order.
+    fn assert_sides(gap: &Gap, a: Vec2, b: Vec2) {
+        let sides = gap.sides;
+        assert!(
+            sides == [Some(a), Some(b)] || sides == [Some(b), Some(a)],
+            "expected a gap between {a} and {b}, got {gap:?}"
+        );
+    }
+
+    #[test]
+    fn closed_rooms_have_no_gaps() {
+        let mut space = Space::new();
+        let material = space.add_material(Material::default());
+        space.add_walls(make_box(Vec2::ZERO, vec2(4.0, 3.0), material));
+        assert!(find_gaps(&space, vec2(1.0, 1.0)).is_empty());
+    }
+
+    #[test]
+    fn missing_walls_are_gaps_between_their_ends() {
+        let mut space = Space::new();
+        let material = space.add_material(Material::default());
+        space.add_walls(make_box(Vec2::ZERO, vec2(4.0, 3.0), material));
+        let missing = space.walls.remove(0);
+
+        let gaps = find_gaps(&space, vec2(1.0, 1.0));
+        assert_eq!(gaps.len(), 1, "{gaps:?}");
+        assert_sides(&gaps[0], missing.start, missing.end);
+    }
+
+    /// Two rooms side by side, joined by an inner doorway, with the second one opening to the
+    /// outside through a narrower doorway. Only the outer doorway lets sound out, even when
+    /// looking from the first room.
+    #[test]
+    fn doorways_to_the_outside_are_gaps() {
+        let mut space = Space::new();
+        let material = space.add_material(Material::default());
+        let [a, b, c] = [0.0, 4.0, 8.0];
+        let (bottom, top) = (0.0, 4.0);
+        for (start, end) in [
+            (vec2(a, bottom), vec2(c, bottom)),
+            (vec2(c, top), vec2(a, top)),
+            (vec2(a, top), vec2(a, bottom)),
+            // The inner doorway.
+            (vec2(b, bottom), vec2(b, 1.4)),
+            (vec2(b, 2.6), vec2(b, top)),
+            // The outer doorway.
+            (vec2(c, bottom), vec2(c, 1.5)),
+            (vec2(c, 2.5), vec2(c, top)),
+        ] {
+            space.add_wall(line(start, end, material));
+        }
+
+        for origin in [vec2(2.0, 2.0), vec2(6.0, 3.0)] {
+            let gaps = find_gaps(&space, origin);
+            assert_eq!(gaps.len(), 1, "from {origin}: {gaps:?}");
+            assert_sides(&gaps[0], vec2(c, 1.5), vec2(c, 2.5));
+        }
+    }
+
+    #[test]
+    fn gaps_lie_between_the_closest_ends_around_the_ray() {
+        let escape = Ray {
+            start: vec2(1.0, -1.0),
+            direction: Vec2::Y,
+        };
+        let free_ends = [vec2(0.0, 0.0), vec2(3.0, 0.0), vec2(1.5, 0.0)];
+        assert_eq!(
+            gap_ends(&free_ends, escape),
+            Some([Some(vec2(0.0, 0.0)), Some(vec2(1.5, 0.0))])
+        );
+        assert_eq!(gap_ends(&free_ends[1..], escape), None);
+    }
+
+    #[test]
+    fn flanking_ends_are_the_closest_on_either_side() {
+        let escape = Ray {
+            start: Vec2::ZERO,
+            direction: Vec2::Y,
+        };
+        let free_ends = [
+            vec2(-1.0, 2.0),
+            vec2(-1.0, 5.0),
+            vec2(1.0, 3.0),
+            vec2(2.0, 1.0),
+            // Behind the ray's start.
+            vec2(0.1, -1.0),
+        ];
+        assert_eq!(
+            flanking_ends(&free_ends, escape),
+            [Some(vec2(-1.0, 5.0)), Some(vec2(1.0, 3.0))]
+        );
+        assert_eq!(flanking_ends(&free_ends[..2], escape)[1], None);
+    }
+}
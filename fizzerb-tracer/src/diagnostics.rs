@@ -0,0 +1,86 @@
+//! Statistics about how traced rays fare in a space, for finding out why a render sounds the way
+//! it does.
+
+use crate::{ray::Ray, RayStats, Termination};
+
+/// Statistics gathered over many traced rays.
+#[derive(Debug, Clone, Default)]
+pub struct Diagnostics {
+    /// The number of rays traced.
+    pub rays: usize,
+    /// The escaping parts of the rays which escaped into empty space, starting at their last
+    /// bounce.
+    pub escapes: Vec<Ray>,
+    /// The total number of times the rays bounced off of or passed through walls.
+    pub bounces: usize,
+    /// The number of rays cut off at the maximal number of bounces.
+    pub cut_off: usize,
+    /// The total share of their initial energy the cut off rays still carried.
+    pub energy_at_cutoff: f32,
+    /// The number of rays stopped by Russian roulette after losing most of their energy.
+    pub absorbed: usize,
+    /// The number of rays stopped by the time limit.
+    pub timed_out: usize,
+}
+
+impl Diagnostics {
+    pub fn add(&mut self, stats: RayStats) {
+        self.rays += 1;
+        self.bounces += stats.bounces;
+        match stats.termination {
+            Termination::Escaped(ray) => self.escapes.push(ray),
+            Termination::MaxBounces => {
+                self.cut_off += 1;
+                self.energy_at_cutoff += stats.energy;
+            }
+            Termination::Absorbed => self.absorbed += 1,
+            Termination::TimeLimit => self.timed_out += 1,
+        }
+    }
+
+    /// Adds the statistics gathered by `other` to these.
+    pub fn merge(&mut self, other: Diagnostics) {
+        self.rays += other.rays;
+        self.escapes.extend(other.escapes);
+        self.bounces += other.bounces;
+        self.cut_off += other.cut_off;
+        self.energy_at_cutoff += other.energy_at_cutoff;
+        self.absorbed += other.absorbed;
+        self.timed_out += other.timed_out;
+    }
+
+    /// Returns the share of rays which escaped into empty space.
+    pub fn escaped_share(&self) -> f32 {
+        ratio(self.escapes.len() as f32, self.rays)
+    }
+
+    /// Returns the average number of times a ray bounced off of or passed through walls.
+    pub fn average_bounces(&self) -> f32 {
+        ratio(self.bounces as f32, self.rays)
+    }
+
+    /// Returns the average share of their initial energy rays still carried when they were cut
+    /// off. High values mean the maximal number of bounces cuts the reverb's tail short.
+    pub fn average_energy_at_cutoff(&self) -> f32 {
+        ratio(self.energy_at_cutoff, self.cut_off)
+    }
+}
+
+impl FromIterator<RayStats> for Diagnostics {
+    fn from_iter<T: IntoIterator<Item = RayStats>>(iter: T) -> Self {
+        let mut diagnostics = Self::default();
+        for stats in iter {
+            diagnostics.add(stats);
+        }
+        diagnostics
+    }
+}
+
+/// Divides `sum` by `count`, or returns zero if there's nothing to divide by.
+fn ratio(sum: f32, count: usize) -> f32 {
+    if count == 0 {
+        0.0
+    } else {
+        sum / count as f32
+    }
+}
@@ -0,0 +1,274 @@
+//! Diffraction of sound around the free ends of walls.
+//!
+//! Each free wall end is treated as the edge of a rigid half-plane, and the diffracted field is
+//! computed with the uniform theory of diffraction (UTD), using a real-valued approximation of
+//! its transition function.
+
+use std::f32::consts::{PI, TAU};
+
+use fizzerb_model::{Space, Wall, WallShape};
+use glam::{vec2, Vec2};
+
+use crate::ray::SURFACE_OFFSET;
+
+/// The frequency in Hz at which diffraction is evaluated. Lower frequencies bend further into
+/// the shadow behind an edge.
+pub const DIFFRACTION_FREQUENCY: f32 = 500.0;
+
+/// Wall ends closer than this many metres are considered joined.
+const JOIN_DISTANCE: f32 = 1e-4;
+
+/// The free end of a wall, which sound can bend around.
+#[derive(Debug, Clone, Copy)]
+pub struct Edge {
+    pub position: Vec2,
+    /// The direction in which the wall leaves the edge.
+    pub face: Vec2,
+}
+
+impl Edge {
+    /// Returns a point just past the edge, off of the wall, for casting visibility rays at.
+    pub fn visibility_point(&self) -> Vec2 {
+        self.position - self.face * SURFACE_OFFSET
+    }
+
+    /// Returns the angle between the edge's face and the direction towards `point`, counted
+    /// counterclockwise in the range `[0, 2π)`.
+    fn angle_to(&self, point: Vec2) -> f32 {
+        let direction = point - self.position;
+        self.face
+            .perp_dot(direction)
+            .atan2(self.face.dot(direction))
+            .rem_euclid(TAU)
+    }
+}
+
+/// Finds the ends of walls which aren't joined to another wall.
+pub fn find_edges(space: &Space) -> Vec<Edge> {
+    let ends: Vec<_> = space
+        .walls
+        .iter()
+        .flat_map(|wall| [wall.start, wall.end])
+        .collect();
+    space
+        .walls
+        .iter()
+        .flat_map(wall_ends)
+        .filter(|edge| {
+            ends.iter()
+                .filter(|&&end| end.distance(edge.position) <= JOIN_DISTANCE)
+                .count()
+                == 1
+        })
+        .collect()
+}
+
+/// Returns both ends of a wall as edges.
+fn wall_ends(wall: &Wall) -> [Edge; 2] {
+    let chord = wall.end - wall.start;
+    let (start_tangent, end_tangent) = match wall.shape {
+        WallShape::Line => (chord, -chord),
+        WallShape::Arc { sagitta } => match wall.arc_circle() {
+            Some((center, _)) => {
+                // The tangent at either end leans towards the arc's apex. The chord can't be used
+                // for this, as it's perpendicular to both tangents on half circles.
+                let apex = (wall.start + wall.end) * 0.5 + wall.normal() * sagitta;
+                let tangent = |point: Vec2| {
+                    let radial = point - center;
+                    let tangent = vec2(-radial.y, radial.x);
+                    if tangent.dot(apex - point) >= 0.0 {
+                        tangent
+                    } else {
+                        -tangent
+                    }
+                };
+                (tangent(wall.start), tangent(wall.end))
+            }
+            None => (chord, -chord),
+        },
+        WallShape::QuadraticBezier { control } => (control - wall.start, control - wall.end),
+        WallShape::CubicBezier {
+            control_start,
+            control_end,
+        } => (control_start - wall.start, control_end - wall.end),
+    };
+    [
+        Edge {
+            position: wall.start,
+            face: start_tangent
+                .try_normalize()
+                .unwrap_or(chord.normalize_or_zero()),
+        },
+        Edge {
+            position: wall.end,
+            face: end_tangent
+                .try_normalize()
+                .unwrap_or(-chord.normalize_or_zero()),
+        },
+    ]
+}
+
+/// Returns the loudness of sound diffracted by the edge, per unit of the source's power, or
+/// `None` if the receiver doesn't lie in the shadow the edge casts.
+///
+/// `source` is the point the sound reaches the edge from, after having already travelled
+/// `distance_before`.
+pub fn diffracted_weight(
+    edge: &Edge,
+    source: Vec2,
+    distance_before: f32,
+    receiver: Vec2,
+    speed_of_sound: f32,
+) -> Option<f32> {
+    let source_angle = edge.angle_to(source);
+    let receiver_angle = edge.angle_to(receiver);
+    if (receiver_angle - source_angle).abs() <= PI {
+        return None;
+    }
+
+    let distance_to_edge = distance_before + source.distance(edge.position);
+    let distance_from_edge = receiver.distance(edge.position);
+    let total_distance = distance_to_edge + distance_from_edge;
+    let wavenumber = TAU * DIFFRACTION_FREQUENCY / speed_of_sound;
+    let length = distance_to_edge * distance_from_edge / total_distance;
+
+    // The UTD coefficient of a rigid half-plane, multiplied by the spreading factor. Each term's
+    // transition function is approximated by `F(X) ≈ sqrt(πX / (1 + πX))`, which cancels out the
+    // singularity at the shadow and reflection boundaries, and leaves the diffracted sound at half
+    // the loudness of unobstructed sound right at the boundary.
+    let term = |angle: f32| {
+        let half_cos = (angle * 0.5).cos();
+        let x = wavenumber * length * 2.0 * half_cos * half_cos;
+        half_cos.signum() / (1.0 + PI * x).sqrt()
+    };
+    let coefficient = term(receiver_angle - source_angle) + term(receiver_angle + source_angle);
+    Some(0.5 * coefficient.abs() / total_distance)
+}
+
+#[cfg(test)]
+mod tests {
+    use fizzerb_model::{walls::make_box, Material, MaterialIndex};
+
+    use super::*;
+
+    const SPEED_OF_SOUND: f32 = 343.0;
+
+    fn line(start: Vec2, end: Vec2) -> Wall {
+        Wall {
+            start,
+            end,
+            shape: WallShape::Line,
+            material: MaterialIndex(0),
+            back_material: None,
+        }
+    }
+
+    fn assert_close(actual: Vec2, expected: Vec2) {
+        assert!(
+            actual.distance(expected) < 1e-5,
+            "expected {expected}, got {actual}"
+        );
+    }
+
+    /// An edge at the origin, with its wall running along the positive X axis.
+    const EDGE: Edge = Edge {
+        position: Vec2::ZERO,
+        face: Vec2::X,
+    };
+    /// The source lies straight above the edge, so the shadow spans the quarter below the wall,
+    /// at angles from `3π/2` to `2π`.
+    const SOURCE_ANGLE: f32 = PI / 2.0;
+    const DISTANCE: f32 = 5.0;
+
+    fn weight_at(receiver_angle: f32) -> Option<f32> {
+        diffracted_weight(
+            &EDGE,
+            Vec2::from_angle(SOURCE_ANGLE) * DISTANCE,
+            0.0,
+            Vec2::from_angle(receiver_angle) * DISTANCE,
+            SPEED_OF_SOUND,
+        )
+    }
+
+    #[test]
+    fn closed_rooms_have_no_edges() {
+        let mut space = Space::new();
+        let material = space.add_material(Material::default());
+        space.add_walls(make_box(Vec2::ZERO, vec2(4.0, 3.0), material));
+        assert!(find_edges(&space).is_empty());
+    }
+
+    #[test]
+    fn lone_walls_have_two_edges() {
+        let mut space = Space::new();
+        space.add_wall(line(Vec2::ZERO, vec2(2.0, 0.0)));
+        let edges = find_edges(&space);
+        assert_eq!(edges.len(), 2);
+        assert_close(edges[0].position, Vec2::ZERO);
+        assert_close(edges[1].position, vec2(2.0, 0.0));
+    }
+
+    #[test]
+    fn edges_face_along_the_wall() {
+        let [start, end] = wall_ends(&line(Vec2::ZERO, vec2(2.0, 0.0)));
+        assert_close(start.face, Vec2::X);
+        assert_close(end.face, -Vec2::X);
+
+        let arc = |sagitta| Wall {
+            shape: WallShape::Arc { sagitta },
+            ..line(Vec2::ZERO, vec2(2.0, 0.0))
+        };
+        // A flat arc with its center below the chord, bulging up along the wall's normal.
+        let [start, end] = wall_ends(&arc(0.5));
+        assert_close(start.face, vec2(0.6, 0.8));
+        assert_close(end.face, vec2(-0.6, 0.8));
+        // Half circles leave their ends perpendicular to the chord.
+        let [start, end] = wall_ends(&arc(1.0));
+        assert_close(start.face, Vec2::Y);
+        assert_close(end.face, Vec2::Y);
+        let [start, end] = wall_ends(&arc(-1.0));
+        assert_close(start.face, -Vec2::Y);
+        assert_close(end.face, -Vec2::Y);
+    }
+
+    #[test]
+    fn receivers_outside_the_shadow_get_no_diffracted_sound() {
+        for angle in [0.1, SOURCE_ANGLE, PI, 1.5 * PI - 0.01] {
+            assert_eq!(weight_at(angle), None, "at angle {angle}");
+        }
+    }
+
+    #[test]
+    fn diffracted_sound_is_half_as_loud_at_the_shadow_boundary() {
+        // Far away from the edge, the term for the reflection boundary becomes negligible.
+        let distance = 1000.0;
+        let weight = diffracted_weight(
+            &EDGE,
+            Vec2::from_angle(SOURCE_ANGLE) * distance,
+            0.0,
+            Vec2::from_angle(SOURCE_ANGLE + PI + 0.001) * distance,
+            SPEED_OF_SOUND,
+        )
+        .unwrap();
+        let expected = 0.5 / (2.0 * distance);
+        assert!(
+            (weight - expected).abs() < expected * 0.01,
+            "expected {expected}, got {weight}"
+        );
+    }
+
+    #[test]
+    fn diffracted_sound_falls_off_deeper_in_the_shadow() {
+        let weights: Vec<_> = (1..50)
+            .map(|i| {
+                let angle = 1.5 * PI + i as f32 / 50.0 * 0.5 * PI;
+                weight_at(angle).unwrap()
+            })
+            .collect();
+        assert!(
+            weights.windows(2).all(|pair| pair[1] < pair[0]),
+            "{weights:?}"
+        );
+        assert!(weights[weights.len() - 1] < 0.5 * weights[0]);
+    }
+}
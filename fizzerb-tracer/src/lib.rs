@@ -1,7 +1,14 @@
+mod closure;
+mod diagnostics;
+mod diffraction;
 mod ray;
 mod response;
+mod roots;
 mod tracer;
 
+pub use closure::{find_gaps, find_leaks, Gap, Leak};
+pub use diagnostics::Diagnostics;
+pub use diffraction::DIFFRACTION_FREQUENCY;
 pub use ray::*;
 pub use response::*;
 pub use tracer::*;
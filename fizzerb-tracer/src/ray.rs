@@ -1,7 +1,9 @@
 //! Raycasting math.
 
-use fizzerb_model::Wall;
-use glam::Vec2;
+use fizzerb_model::{Side, Wall, WallShape};
+use glam::{vec2, Vec2};
+
+use crate::roots;
 
 #[derive(Debug, Clone, Copy)]
 pub struct LineSegment {
@@ -28,31 +30,604 @@ pub struct Ray {
 pub struct RayHit {
     pub position: Vec2,
     pub ray_length: f32,
+    /// The normal of the surface at the hit position, facing the side the ray came from.
+    pub normal: Vec2,
+    /// The side of the surface the ray hit.
+    pub side: Side,
+}
+
+/// How far rays leaving a surface start off of it, in metres, such that they don't hit the same
+/// surface again due to rounding errors.
+pub const SURFACE_OFFSET: f32 = 1e-4;
+
+/// How far past a segment's endpoints rays still hit it, relative to its length. Keeps rays from
+/// slipping through the corners where two walls meet.
+const ENDPOINT_TOLERANCE: f32 = 1e-5;
+
+/// Segments whose direction is within this angle (in radians) of a ray's are considered parallel
+/// to it, and are never hit.
+const PARALLEL_TOLERANCE: f32 = 1e-6;
+
+impl RayHit {
+    /// Returns a ray leaving the hit position in the given direction, starting off of the surface
+    /// on the side the direction points to.
+    pub fn leave(&self, direction: Vec2) -> Ray {
+        let side = if direction.dot(self.normal) >= 0.0 {
+            1.0
+        } else {
+            -1.0
+        };
+        Ray {
+            start: self.position + self.normal * side * SURFACE_OFFSET,
+            direction,
+        }
+    }
 }
 
 impl Ray {
     /// Casts the ray against a line segment.
+    ///
+    /// Rays parallel to the segment (in either direction) never hit it, while rays passing
+    /// exactly through one of its endpoints do.
     pub fn cast(self, segment: LineSegment) -> Option<RayHit> {
-        let ray_start = self.start;
-        let ray_direction = self.direction;
-        let segment_start = segment.a;
         let segment_direction = segment.b - segment.a;
-        if ray_direction
-            .normalize()
-            .abs_diff_eq(segment_direction.normalize(), 1e-9)
-        {
+        let to_segment = segment.a - self.start;
+
+        // Solve start + direction*t = a + segment_direction*u for t and u.
+        let denominator = self.direction.perp_dot(segment_direction);
+        let scale = self.direction.length() * segment_direction.length();
+        if denominator.abs() <= PARALLEL_TOLERANCE * scale {
             return None;
         }
-        let t2 = (ray_direction.x * (segment_start.y - ray_start.y)
-            + ray_direction.y * (ray_start.x - segment_start.x))
-            / (segment_direction.x * ray_direction.y - segment_direction.y * ray_direction.x);
-        let t1 = (segment_start.x + segment_direction.x * t2 - ray_start.x) / ray_direction.x;
-        if t1 < 0.0 || !(0.0..=1.0).contains(&t2) {
+        let t = to_segment.perp_dot(segment_direction) / denominator;
+        let u = to_segment.perp_dot(self.direction) / denominator;
+        if t < 0.0 || !(-ENDPOINT_TOLERANCE..=1.0 + ENDPOINT_TOLERANCE).contains(&u) {
             return None;
         }
+        let (normal, side) = self.facing(perpendicular(segment_direction).normalize());
         Some(RayHit {
-            position: ray_start + ray_direction * t1,
-            ray_length: t1,
+            position: self.start + self.direction * t,
+            ray_length: t,
+            normal,
+            side,
         })
     }
+
+    /// Takes the normal pointing out of a surface's front side, and returns the normal facing
+    /// against the ray along with the side of the surface the ray hits.
+    fn facing(self, front_normal: Vec2) -> (Vec2, Side) {
+        if front_normal.dot(self.direction) > 0.0 {
+            (-front_normal, Side::Back)
+        } else {
+            (front_normal, Side::Front)
+        }
+    }
+
+    /// Casts the ray against a wall of any shape.
+    pub fn cast_wall(self, wall: &Wall) -> Option<RayHit> {
+        match wall.shape {
+            WallShape::Line => self.cast(LineSegment::from_wall(wall)),
+            WallShape::Arc { sagitta } => {
+                if let Some((center, radius)) = wall.arc_circle() {
+                    self.cast_arc(wall, sagitta, center, radius)
+                } else {
+                    self.cast(LineSegment::from_wall(wall))
+                }
+            }
+            WallShape::QuadraticBezier { control } => {
+                self.cast_bezier(quadratic_bezier_coefficients(wall.start, control, wall.end))
+            }
+            WallShape::CubicBezier {
+                control_start,
+                control_end,
+            } => self.cast_bezier(cubic_bezier_coefficients(
+                wall.start,
+                control_start,
+                control_end,
+                wall.end,
+            )),
+        }
+    }
+
+    /// Casts the ray against a filled circle. Rays starting inside the circle hit it immediately.
+    pub fn cast_circle(self, center: Vec2, radius: f32) -> Option<RayHit> {
+        let offset = self.start - center;
+        let a = self.direction.length_squared() as f64;
+        let b = 2.0 * offset.dot(self.direction) as f64;
+        let c = (offset.length_squared() - radius * radius) as f64;
+
+        let roots = roots::quadratic(a, b, c);
+        let (entry, exit) = match *roots.as_slice() {
+            [root] => (root, root),
+            [a, b] => (a.min(b), a.max(b)),
+            _ => return None,
+        };
+        if exit < 0.0 {
+            return None;
+        }
+        let t = entry.max(0.0) as f32;
+        let position = self.start + self.direction * t;
+        Some(RayHit {
+            position,
+            ray_length: t,
+            normal: (position - center).normalize_or_zero(),
+            // Rays starting inside the circle hit it from the inside.
+            side: if entry < 0.0 { Side::Back } else { Side::Front },
+        })
+    }
+
+    /// Casts the ray against the arc of the given circle which lies on the `sagitta` side of the
+    /// wall's endpoints.
+    fn cast_arc(self, wall: &Wall, sagitta: f32, center: Vec2, radius: f32) -> Option<RayHit> {
+        // Solve |start + direction*t - center|^2 = radius^2 for t.
+        let offset = self.start - center;
+        let a = self.direction.length_squared() as f64;
+        let b = 2.0 * offset.dot(self.direction) as f64;
+        let c = (offset.length_squared() - radius * radius) as f64;
+
+        let chord_midpoint = (wall.start + wall.end) * 0.5;
+        let arc_side = wall.normal() * sagitta.signum();
+        roots::quadratic(a, b, c)
+            .as_slice()
+            .iter()
+            .map(|&t| t as f32)
+            .filter(|&t| t >= 0.0)
+            .map(|t| (t, self.start + self.direction * t))
+            .filter(|&(_, position)| (position - chord_midpoint).dot(arc_side) >= 0.0)
+            .min_by(|(a, _), (b, _)| a.total_cmp(b))
+            .map(|(t, position)| {
+                // The circle's outward normal points out of the wall's front if the arc bulges
+                // out in the direction of the wall's normal.
+                let (normal, side) = self.facing((position - center) / radius * sagitta.signum());
+                RayHit {
+                    position,
+                    ray_length: t,
+                    normal,
+                    side,
+                }
+            })
+    }
+
+    /// Casts the ray against a Bézier curve given by its power basis coefficients.
+    fn cast_bezier(self, coefficients: [Vec2; 4]) -> Option<RayHit> {
+        // Project the curve onto the ray's perpendicular, such that the roots of the projection
+        // are the curve parameters at which it crosses the ray's line.
+        let ray_normal = perpendicular(self.direction);
+        let [d, c, b, a] = [
+            coefficients[0] - self.start,
+            coefficients[1],
+            coefficients[2],
+            coefficients[3],
+        ]
+        .map(|coefficient| ray_normal.dot(coefficient) as f64);
+
+        let direction_length_squared = self.direction.length_squared();
+        roots::cubic(a, b, c, d)
+            .as_slice()
+            .iter()
+            .map(|&t| t as f32)
+            .filter(|t| (0.0..=1.0).contains(t))
+            .map(|t| {
+                let position = evaluate_polynomial(coefficients, t);
+                let ray_length =
+                    (position - self.start).dot(self.direction) / direction_length_squared;
+                (t, position, ray_length)
+            })
+            .filter(|&(_, _, ray_length)| ray_length >= 0.0)
+            .min_by(|(_, _, a), (_, _, b)| a.total_cmp(b))
+            .map(|(t, position, ray_length)| {
+                let (normal, side) = self.facing(
+                    perpendicular(evaluate_derivative(coefficients, t)).normalize_or_zero(),
+                );
+                RayHit {
+                    position,
+                    ray_length,
+                    normal,
+                    side,
+                }
+            })
+    }
+}
+
+/// Returns the vector perpendicular to the given one, with the same winding as [`Wall::normal`].
+fn perpendicular(v: Vec2) -> Vec2 {
+    vec2(-v.y, v.x)
+}
+
+/// Converts the control points of a quadratic Bézier curve into power basis coefficients, ordered
+/// from the constant term upwards.
+fn quadratic_bezier_coefficients(p0: Vec2, p1: Vec2, p2: Vec2) -> [Vec2; 4] {
+    [p0, 2.0 * (p1 - p0), p0 - 2.0 * p1 + p2, Vec2::ZERO]
+}
+
+/// Converts the control points of a cubic Bézier curve into power basis coefficients, ordered
+/// from the constant term upwards.
+fn cubic_bezier_coefficients(p0: Vec2, p1: Vec2, p2: Vec2, p3: Vec2) -> [Vec2; 4] {
+    [
+        p0,
+        3.0 * (p1 - p0),
+        3.0 * (p0 - 2.0 * p1 + p2),
+        -p0 + 3.0 * p1 - 3.0 * p2 + p3,
+    ]
+}
+
+fn evaluate_polynomial(coefficients: [Vec2; 4], t: f32) -> Vec2 {
+    coefficients
+        .iter()
+        .rev()
+        .fold(Vec2::ZERO, |sum, &coefficient| sum * t + coefficient)
+}
+
+fn evaluate_derivative(coefficients: [Vec2; 4], t: f32) -> Vec2 {
+    coefficients[1] + 2.0 * coefficients[2] * t + 3.0 * coefficients[3] * t * t
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::{PI, TAU};
+
+    use fastrand::Rng;
+    use fizzerb_model::{math, MaterialIndex};
+
+    use super::*;
+
+    const CASES: usize = 10_000;
+
+    fn random_point(rng: &mut Rng) -> Vec2 {
+        vec2(rng.f32() * 20.0 - 10.0, rng.f32() * 20.0 - 10.0)
+    }
+
+    fn wall(start: Vec2, end: Vec2, shape: WallShape) -> Wall {
+        Wall {
+            start,
+            end,
+            shape,
+            material: MaterialIndex(0),
+            back_material: None,
+        }
+    }
+
+    /// Returns a ray starting at a random point and passing through `target`, unless it would
+    /// only graze the curve there, given its `tangent`.
+    fn ray_towards(rng: &mut Rng, target: Vec2, tangent: Vec2) -> Option<Ray> {
+        let start = random_point(rng);
+        let direction = (target - start).normalize();
+        let sine = direction.perp_dot(tangent.normalize());
+        (start.distance(target) > 0.01 && sine.abs() > 0.05).then_some(Ray { start, direction })
+    }
+
+    /// Checks that the hit lies on the ray, and that its normal is a unit vector facing against
+    /// the ray.
+    fn check_hit_on_ray(ray: Ray, hit: RayHit) {
+        assert!(hit.ray_length >= 0.0, "{ray:?} hit {hit:?}");
+        let along_ray = ray.start + ray.direction * hit.ray_length;
+        assert!(
+            along_ray.distance(hit.position) < 1e-3,
+            "{ray:?} hit {hit:?}"
+        );
+        assert!(
+            (hit.normal.length() - 1.0).abs() < 1e-3,
+            "{ray:?} hit {hit:?}"
+        );
+        assert!(hit.normal.dot(ray.direction) <= 0.0, "{ray:?} hit {hit:?}");
+    }
+
+    #[test]
+    fn rays_hit_arcs_on_the_arc() {
+        let mut rng = Rng::with_seed(0xa4c);
+        for _ in 0..CASES {
+            let start = random_point(&mut rng);
+            let end = random_point(&mut rng);
+            let half_chord = start.distance(end) / 2.0;
+            if half_chord < 0.1 {
+                continue;
+            }
+            // Anything from a shallow arc up to a semicircle, bulging either way.
+            let sagitta =
+                half_chord * (0.05 + rng.f32() * 0.95) * if rng.bool() { 1.0 } else { -1.0 };
+            let wall = wall(start, end, WallShape::Arc { sagitta });
+            let (center, radius) = wall.arc_circle().unwrap();
+
+            // Aim at a random point of the arc.
+            let sweep = (start - center).angle_between(end - center);
+            let start_angle = Vec2::X.angle_between(start - center);
+            let angle = start_angle + sweep * (0.01 + rng.f32() * 0.98);
+            let target = center + Vec2::from_angle(angle) * radius;
+            let midpoint = (start + end) * 0.5;
+            let arc_side = wall.normal() * sagitta.signum();
+            assert!((target - midpoint).dot(arc_side) > 0.0);
+            let tangent = Vec2::from_angle(angle + PI / 2.0);
+            let ray = match ray_towards(&mut rng, target, tangent) {
+                Some(ray) => ray,
+                None => continue,
+            };
+
+            let hit = ray
+                .cast_wall(&wall)
+                .unwrap_or_else(|| panic!("{ray:?} missed {wall:?}"));
+            check_hit_on_ray(ray, hit);
+            // The ray may hit another part of the arc on its way to the target, but never pass it.
+            assert!(hit.ray_length <= ray.start.distance(target) + 1e-3);
+            // The hit is on the circle, on the arc's side of the chord, and the normal is
+            // perpendicular to the circle there.
+            assert!((hit.position.distance(center) - radius).abs() < 1e-3 * radius.max(1.0));
+            assert!((hit.position - midpoint).dot(arc_side) > -1e-3);
+            assert!(hit.normal.perp_dot(hit.position - center).abs() < 1e-3 * radius);
+        }
+    }
+
+    /// Returns the distance from the point to the closest point on the Bézier curve given by its
+    /// power basis coefficients.
+    fn distance_to_curve(coefficients: [Vec2; 4], point: Vec2) -> f32 {
+        const SAMPLES: usize = 1000;
+        let distance = |t: f32| evaluate_polynomial(coefficients, t).distance(point);
+        let closest = (0..=SAMPLES)
+            .map(|i| i as f32 / SAMPLES as f32)
+            .min_by(|&a, &b| distance(a).total_cmp(&distance(b)))
+            .unwrap();
+        // Narrow it down between the neighboring samples.
+        let step = 1.0 / SAMPLES as f32;
+        let (mut low, mut high) = ((closest - step).max(0.0), (closest + step).min(1.0));
+        for _ in 0..32 {
+            let a = low + (high - low) / 3.0;
+            let b = high - (high - low) / 3.0;
+            if distance(a) < distance(b) {
+                high = b;
+            } else {
+                low = a;
+            }
+        }
+        distance((low + high) / 2.0)
+    }
+
+    #[test]
+    fn rays_hit_bezier_curves_on_the_curve() {
+        let mut rng = Rng::with_seed(0xbe2);
+        for i in 0..CASES {
+            let [start, control_start, control_end, end] = [(); 4].map(|_| random_point(&mut rng));
+            let (shape, coefficients) = if i % 2 == 0 {
+                let shape = WallShape::QuadraticBezier {
+                    control: control_start,
+                };
+                (
+                    shape,
+                    quadratic_bezier_coefficients(start, control_start, end),
+                )
+            } else {
+                let shape = WallShape::CubicBezier {
+                    control_start,
+                    control_end,
+                };
+                let coefficients =
+                    cubic_bezier_coefficients(start, control_start, control_end, end);
+                (shape, coefficients)
+            };
+            let wall = wall(start, end, shape);
+
+            let t = 0.01 + rng.f32() * 0.98;
+            let target = evaluate_polynomial(coefficients, t);
+            let tangent = evaluate_derivative(coefficients, t);
+            if tangent.length() < 0.1 {
+                continue;
+            }
+            let ray = match ray_towards(&mut rng, target, tangent) {
+                Some(ray) => ray,
+                None => continue,
+            };
+
+            let hit = ray
+                .cast_wall(&wall)
+                .unwrap_or_else(|| panic!("{ray:?} missed {wall:?}"));
+            check_hit_on_ray(ray, hit);
+            assert!(hit.ray_length <= ray.start.distance(target) + 1e-3);
+            assert!(distance_to_curve(coefficients, hit.position) < 1e-3);
+        }
+    }
+
+    #[test]
+    fn rays_miss_curves_beside_them() {
+        let wall = wall(
+            vec2(0.0, 0.0),
+            vec2(4.0, 0.0),
+            WallShape::CubicBezier {
+                control_start: vec2(1.0, 2.0),
+                control_end: vec2(3.0, 2.0),
+            },
+        );
+        // Passing beyond the end, above the apex, and pointing away.
+        for (start, direction) in [
+            (vec2(5.0, -1.0), Vec2::Y),
+            (vec2(-1.0, 2.0), Vec2::X),
+            (vec2(2.0, -1.0), Vec2::NEG_Y),
+        ] {
+            let ray = Ray { start, direction };
+            assert!(ray.cast_wall(&wall).is_none(), "{ray:?} hit {wall:?}");
+        }
+    }
+
+    fn random_direction(rng: &mut Rng) -> Vec2 {
+        Vec2::from_angle(rng.f32() * TAU)
+    }
+
+    fn random_segment(rng: &mut Rng) -> LineSegment {
+        loop {
+            let segment = LineSegment {
+                a: random_point(rng),
+                b: random_point(rng),
+            };
+            if segment.a.distance(segment.b) > 0.1 {
+                return segment;
+            }
+        }
+    }
+
+    /// Returns the distance of the point from the segment's line, positive on the side its
+    /// normal points to.
+    fn signed_distance(segment: LineSegment, point: Vec2) -> f32 {
+        let normal = perpendicular(segment.b - segment.a).normalize();
+        (point - segment.a).dot(normal)
+    }
+
+    /// Checks the properties every hit must have.
+    fn check_hit(ray: Ray, segment: LineSegment, hit: RayHit) {
+        let context = format!("{ray:?} hitting {segment:?} at {hit:?}");
+
+        // The hit lies on the ray...
+        assert!(hit.ray_length >= 0.0, "{context}");
+        let along_ray = ray.start + ray.direction * hit.ray_length;
+        assert!(along_ray.distance(hit.position) < 1e-4, "{context}");
+        // ...and on the segment, give or take the endpoint tolerance.
+        assert!(
+            signed_distance(segment, hit.position).abs() < 1e-3,
+            "{context}"
+        );
+        let length = segment.a.distance(segment.b);
+        let u = (hit.position - segment.a).dot(segment.b - segment.a) / (length * length);
+        let slack = ENDPOINT_TOLERANCE + 1e-4;
+        assert!((-slack..=1.0 + slack).contains(&u), "{context}");
+
+        // The normal is perpendicular to the segment and faces against the ray.
+        assert!((hit.normal.length() - 1.0).abs() < 1e-4, "{context}");
+        assert!(
+            hit.normal.dot(segment.b - segment.a).abs() < 1e-3 * length,
+            "{context}"
+        );
+        assert!(hit.normal.dot(ray.direction) <= 0.0, "{context}");
+        let front = signed_distance(segment, ray.start) >= 0.0;
+        assert_eq!(hit.side == Side::Front, front, "{context}");
+
+        // Leaving puts the ray's origin off of the segment, on the side it's headed towards.
+        let reflected = math::reflect(ray.direction, hit.normal);
+        for direction in [reflected, ray.direction] {
+            let leaving = hit.leave(direction);
+            let offset = (leaving.start - hit.position).dot(hit.normal);
+            let expected = SURFACE_OFFSET * direction.dot(hit.normal).signum();
+            assert!((offset - expected).abs() < 1e-6, "{context}");
+            let distance = signed_distance(segment, leaving.start);
+            let before = signed_distance(segment, ray.start);
+            let same_side = direction.dot(hit.normal) > 0.0;
+            assert_eq!(distance.signum() == before.signum(), same_side, "{context}");
+            assert!(
+                (distance.abs() - SURFACE_OFFSET).abs() < SURFACE_OFFSET * 0.5,
+                "{context}"
+            );
+        }
+    }
+
+    #[test]
+    fn random_rays_hit_segments_on_both() {
+        let mut rng = Rng::with_seed(0x5eed);
+        let mut hits = 0;
+        for _ in 0..CASES {
+            let ray = Ray {
+                start: random_point(&mut rng),
+                direction: random_direction(&mut rng),
+            };
+            let segment = random_segment(&mut rng);
+            if let Some(hit) = ray.cast(segment) {
+                check_hit(ray, segment, hit);
+                hits += 1;
+            }
+        }
+        // Make sure the properties were checked for a meaningful number of hits.
+        assert!(hits > CASES / 10, "only {hits} hits");
+    }
+
+    #[test]
+    fn rays_aimed_at_segments_hit_them() {
+        let mut rng = Rng::with_seed(0xa1);
+        for _ in 0..CASES {
+            let segment = random_segment(&mut rng);
+            let target = segment.a.lerp(segment.b, 0.01 + rng.f32() * 0.98);
+            let start = random_point(&mut rng);
+            let direction = (target - start).normalize();
+            let ray = Ray { start, direction };
+            // Grazing rays only barely miss or hit, which is checked separately.
+            let sine = direction.perp_dot((segment.b - segment.a).normalize());
+            if start.distance(target) < 0.01 || sine.abs() < 1e-2 {
+                continue;
+            }
+            let hit = ray
+                .cast(segment)
+                .unwrap_or_else(|| panic!("{ray:?} missed {segment:?}"));
+            assert!(hit.position.distance(target) < 1e-3, "{ray:?} hit {hit:?}");
+            check_hit(ray, segment, hit);
+        }
+    }
+
+    #[test]
+    fn vertical_rays_hit_segments() {
+        let mut rng = Rng::with_seed(0x7e47);
+        for _ in 0..CASES {
+            let segment = random_segment(&mut rng);
+            let target = segment.a.lerp(segment.b, rng.f32());
+            let direction = if rng.bool() { Vec2::Y } else { Vec2::NEG_Y };
+            let ray = Ray {
+                start: target - direction * (0.01 + rng.f32() * 10.0),
+                direction,
+            };
+            if (segment.b.x - segment.a.x).abs() < 1e-3 {
+                // Vertical segments are parallel to the rays.
+                continue;
+            }
+            let hit = ray
+                .cast(segment)
+                .unwrap_or_else(|| panic!("{ray:?} missed {segment:?}"));
+            check_hit(ray, segment, hit);
+        }
+    }
+
+    #[test]
+    fn parallel_and_anti_parallel_rays_miss() {
+        let mut rng = Rng::with_seed(0x9a7a);
+        for _ in 0..CASES {
+            let segment = random_segment(&mut rng);
+            let along = (segment.b - segment.a).normalize();
+            // Rays along the segment's line, as well as ones beside it.
+            let offset = perpendicular(along) * (rng.f32() - 0.5) * 2.0 * rng.f32();
+            let direction = if rng.bool() { along } else { -along };
+            let ray = Ray {
+                start: segment.a - direction * (1.0 + rng.f32() * 10.0) + offset,
+                direction,
+            };
+            assert!(ray.cast(segment).is_none(), "{ray:?} hit {segment:?}");
+        }
+    }
+
+    #[test]
+    fn rays_through_endpoints_hit() {
+        // Points on a coarse grid, so that rays can pass through endpoints exactly.
+        fn grid_point(rng: &mut Rng) -> Vec2 {
+            vec2(rng.i32(-40..=40) as f32, rng.i32(-40..=40) as f32) * 0.25
+        }
+
+        let mut rng = Rng::with_seed(0xe4d);
+        let mut cases = 0;
+        while cases < CASES {
+            let segment = LineSegment {
+                a: grid_point(&mut rng),
+                b: grid_point(&mut rng),
+            };
+            let endpoint = if rng.bool() { segment.a } else { segment.b };
+            let start = grid_point(&mut rng);
+            // Left unnormalized to keep it exact.
+            let direction = endpoint - start;
+            if segment.a == segment.b
+                || start == endpoint
+                || direction.perp_dot(segment.b - segment.a) == 0.0
+            {
+                continue;
+            }
+            let ray = Ray { start, direction };
+            let hit = ray
+                .cast(segment)
+                .unwrap_or_else(|| panic!("{ray:?} missed {segment:?}"));
+            assert!(
+                hit.position.distance(endpoint) < 1e-4,
+                "{ray:?} hit {hit:?}"
+            );
+            check_hit(ray, segment, hit);
+            cases += 1;
+        }
+    }
 }
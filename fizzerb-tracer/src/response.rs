@@ -7,7 +7,7 @@ use crate::{ray::Ray, RayHit};
 pub enum RayPurpose {
     /// Used for generating bounces against walls.
     Bounce,
-    /// Used for tracing back to the speaker.
+    /// Used for tracing back to the receiver.
     Trace,
 }
 
@@ -28,9 +28,47 @@ pub struct RecordedRay {
     pub hit: RayHit,
 }
 
+/// Why a traced ray stopped bouncing around the space.
+#[derive(Debug, Clone, Copy)]
+pub enum Termination {
+    /// The ray left the space through a gap in its walls. Holds the escaping part of the ray,
+    /// starting at its last bounce.
+    Escaped(Ray),
+    /// The ray reached the maximal number of bounces.
+    MaxBounces,
+    /// The ray's energy dropped below the threshold, and it was stopped by Russian roulette.
+    Absorbed,
+    /// The ray travelled further than sound does within the maximal time.
+    TimeLimit,
+}
+
+/// Statistics about a single traced ray, for diagnosing the space.
+#[derive(Debug, Clone, Copy)]
+pub struct RayStats {
+    pub termination: Termination,
+    /// The number of times the ray bounced off of or passed through walls.
+    pub bounces: usize,
+    /// The share of its initial energy the ray still carried when it stopped. Walls absorb the
+    /// part of the energy of rays reflecting off of them that their material's `diffuse`
+    /// coefficient doesn't reflect, and responses are scaled by the energy left as an amplitude.
+    pub energy: f32,
+}
+
 /// Recording of impulse responses.
 #[derive(Debug, Clone)]
 pub struct Recording {
     pub responses: Vec<Response>,
     pub rays: Vec<RecordedRay>,
+    pub stats: RayStats,
+}
+
+/// Recording of impulse responses at every microphone, from rays leaving a single speaker.
+#[derive(Debug, Clone)]
+pub struct Survey {
+    /// The responses registered by each microphone, indexed by its [`MicrophoneIndex`].
+    ///
+    /// [`MicrophoneIndex`]: fizzerb_model::MicrophoneIndex
+    pub responses: Vec<Vec<Response>>,
+    pub rays: Vec<RecordedRay>,
+    pub stats: RayStats,
 }
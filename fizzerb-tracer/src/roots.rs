@@ -0,0 +1,175 @@
+//! Real roots of low-degree polynomials, used for intersecting rays with curves.
+
+use std::f64::consts::PI;
+
+/// Coefficients smaller than this (relative to the others) are treated as zero, lowering the
+/// degree of the polynomial.
+const EPSILON: f64 = 1e-12;
+
+/// Up to three real roots of a polynomial.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Roots {
+    values: [f64; 3],
+    len: usize,
+}
+
+impl Roots {
+    fn push(&mut self, root: f64) {
+        self.values[self.len] = root;
+        self.len += 1;
+    }
+
+    pub fn as_slice(&self) -> &[f64] {
+        &self.values[..self.len]
+    }
+}
+
+/// Solves `a*x + b = 0`.
+pub fn linear(a: f64, b: f64) -> Roots {
+    let mut roots = Roots::default();
+    if a.abs() > EPSILON {
+        roots.push(-b / a);
+    }
+    roots
+}
+
+/// Solves `a*x^2 + b*x + c = 0`.
+pub fn quadratic(a: f64, b: f64, c: f64) -> Roots {
+    if a.abs() <= EPSILON * (b.abs() + c.abs()) {
+        return linear(b, c);
+    }
+
+    let mut roots = Roots::default();
+    let discriminant = b * b - 4.0 * a * c;
+    // Rounding can push the discriminant of a repeated root slightly below zero.
+    let tolerance = EPSILON * (b * b + (4.0 * a * c).abs());
+    if discriminant >= -tolerance {
+        // Numerically stable variant which avoids subtracting two similar numbers.
+        let q = -0.5 * (b + b.signum() * discriminant.max(0.0).sqrt());
+        if q == 0.0 {
+            roots.push(0.0);
+        } else {
+            roots.push(q / a);
+            roots.push(c / q);
+        }
+    }
+    roots
+}
+
+/// Solves `a*x^3 + b*x^2 + c*x + d = 0`.
+pub fn cubic(a: f64, b: f64, c: f64, d: f64) -> Roots {
+    if a.abs() <= EPSILON * (b.abs() + c.abs() + d.abs()) {
+        return quadratic(b, c, d);
+    }
+
+    // Normalize to x^3 + b*x^2 + c*x + d and substitute x = t - b/3 to obtain the depressed
+    // cubic t^3 + p*t + q.
+    let (b, c, d) = (b / a, c / a, d / a);
+    let offset = b / 3.0;
+    let p = c - b * b / 3.0;
+    let q = 2.0 * b * b * b / 27.0 - b * c / 3.0 + d;
+
+    let mut roots = Roots::default();
+    let discriminant = q * q / 4.0 + p * p * p / 27.0;
+    // Likewise, rounding can push it above zero, which would lose repeated roots.
+    let tolerance = EPSILON * (q * q / 4.0 + (p * p * p / 27.0).abs());
+    if discriminant > tolerance {
+        // One real root (Cardano's formula.)
+        let sqrt_discriminant = discriminant.sqrt();
+        let u = (-q / 2.0 + sqrt_discriminant).cbrt();
+        let v = (-q / 2.0 - sqrt_discriminant).cbrt();
+        roots.push(u + v - offset);
+    } else if p >= -EPSILON {
+        // Triple root.
+        roots.push(-q.cbrt() - offset);
+    } else {
+        // Three real roots (trigonometric method.)
+        let r = 2.0 * (-p / 3.0).sqrt();
+        let phi = (3.0 * q / (p * r)).clamp(-1.0, 1.0).acos() / 3.0;
+        for k in 0..3 {
+            roots.push(r * (phi - 2.0 * PI * k as f64 / 3.0).cos() - offset);
+        }
+    }
+    roots
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Checks that the roots are the expected ones, in any order.
+    fn assert_roots(roots: Roots, expected: &[f64]) {
+        let mut actual = roots.as_slice().to_vec();
+        actual.sort_by(f64::total_cmp);
+        assert_eq!(
+            actual.len(),
+            expected.len(),
+            "expected {expected:?}, got {actual:?}"
+        );
+        for (actual, expected) in actual.iter().zip(expected) {
+            assert!(
+                (actual - expected).abs() < 1e-6,
+                "expected {expected:?}, got {actual:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn linear_roots() {
+        assert_roots(linear(2.0, -3.0), &[1.5]);
+        assert_roots(linear(0.0, 1.0), &[]);
+    }
+
+    #[test]
+    fn quadratic_roots() {
+        // (x - 1)(x - 3)
+        assert_roots(quadratic(1.0, -4.0, 3.0), &[1.0, 3.0]);
+        // -2x(x + 5)
+        assert_roots(quadratic(-2.0, -10.0, 0.0), &[-5.0, 0.0]);
+        assert_roots(quadratic(1.0, 0.0, 1.0), &[]);
+    }
+
+    #[test]
+    fn quadratic_repeated_roots() {
+        // (x - 2)^2
+        assert_roots(quadratic(1.0, -4.0, 4.0), &[2.0, 2.0]);
+        // (x - 0.7)^2, whose discriminant comes out slightly negative when computed in floats.
+        assert_roots(quadratic(1.0, -1.4, 0.49), &[0.7, 0.7]);
+        assert_roots(quadratic(1.0, 0.0, 0.0), &[0.0]);
+    }
+
+    #[test]
+    fn quadratic_with_vanishing_leading_coefficient_is_linear() {
+        assert_roots(quadratic(0.0, 2.0, -1.0), &[0.5]);
+        assert_roots(quadratic(1e-20, 2.0, -1.0), &[0.5]);
+        assert_roots(quadratic(0.0, 0.0, 1.0), &[]);
+    }
+
+    #[test]
+    fn cubic_roots() {
+        // (x - 1)(x - 2)(x - 3)
+        assert_roots(cubic(1.0, -6.0, 11.0, -6.0), &[1.0, 2.0, 3.0]);
+        // 2(x + 1)(x^2 + 1)
+        assert_roots(cubic(2.0, 2.0, 2.0, 2.0), &[-1.0]);
+        // x(x - 4)(x + 4)
+        assert_roots(cubic(1.0, 0.0, -16.0, 0.0), &[-4.0, 0.0, 4.0]);
+    }
+
+    #[test]
+    fn cubic_repeated_roots() {
+        // (x - 2)^3
+        assert_roots(cubic(1.0, -6.0, 12.0, -8.0), &[2.0]);
+        // (x - 1)^2 (x - 3)
+        assert_roots(cubic(1.0, -5.0, 7.0, -3.0), &[1.0, 1.0, 3.0]);
+        // (x + 0.5)^2 (x - 0.25), with a discriminant that isn't exactly zero in floats either.
+        assert_roots(cubic(4.0, 3.0, 0.0, -0.25), &[-0.5, -0.5, 0.25]);
+    }
+
+    #[test]
+    fn cubic_with_vanishing_leading_coefficients_is_quadratic_or_linear() {
+        assert_roots(cubic(0.0, 1.0, -4.0, 3.0), &[1.0, 3.0]);
+        assert_roots(cubic(1e-20, 1.0, -4.0, 3.0), &[1.0, 3.0]);
+        assert_roots(cubic(0.0, 0.0, 2.0, -1.0), &[0.5]);
+        assert_roots(cubic(0.0, 0.0, 0.0, 1.0), &[]);
+    }
+}
@@ -1,12 +1,13 @@
-use std::time::Instant;
+use std::{f32::consts::PI, time::Instant};
 
-use fizzerb_model::{MicrophoneIndex, Response, Space, Speaker, SpeakerIndex, WallIndex};
+use fizzerb_model::{math, MicrophoneIndex, Response, Side, Space, SpeakerIndex, WallIndex};
 use glam::Vec2;
 use tracing::{debug_span, trace};
 
 use crate::{
-    ray::{LineSegment, Ray, RayHit},
-    RayPurpose, RecordedRay, Recording,
+    diffraction::{diffracted_weight, find_edges, Edge},
+    ray::{Ray, RayHit},
+    RayPurpose, RayStats, RecordedRay, Recording, Survey, Termination,
 };
 
 pub const SPEED_OF_SOUND_IN_AIR: f32 = 343.0;
@@ -16,26 +17,77 @@ pub struct TracerConfig {
     /// The speed of sound in m/s.
     pub speed_of_sound: f32,
 
-    /// The maximal number of times a traced ray can bounce off of walls.
+    /// The maximal number of times a traced ray can bounce off of or pass through walls.
     pub max_bounces: usize,
 
+    /// The share of its initial energy below which a ray is put through Russian roulette: it's
+    /// either stopped, or carries on with its energy raised back to the threshold, with chances
+    /// such that the result stays the same on average. Responses are scaled by a ray's energy as
+    /// an amplitude, so 0.001 is 60 dB below its initial level. Zero traces rays until they run
+    /// out of bounces.
+    pub energy_threshold: f32,
+
+    /// The time in seconds after which sound is no longer traced. Rays stop once they've travelled
+    /// further than sound does in that time, and paths arriving later are left out.
+    pub max_time: f32,
+
     /// Whether to record casted rays into the recording. Disabling this may improve performance.
     pub record_rays: bool,
+
+    /// How rays register responses at the receiver.
+    pub detection: Detection,
+
+    /// Whether to register the direct path from the source to the receiver, as a response with
+    /// zero bounces. Disabling this leaves only the reflections.
+    pub include_direct_sound: bool,
+
+    /// Whether sound bends around the free ends of walls, reaching receivers hidden behind them.
+    /// Only applies to shadow ray detection, and is evaluated at [`DIFFRACTION_FREQUENCY`].
+    pub diffraction: bool,
 }
 
+/// Model used for detecting when a traced ray reaches the receiver: the speaker when tracing from
+/// a microphone, or a microphone when tracing from a speaker.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Detection {
+    /// After every bounce, a shadow ray is cast towards the receiver, and every bounce that can
+    /// see it registers a response.
+    #[default]
+    ShadowRays,
+    /// The receiver is a circle with the given radius in metres, and registers a response
+    /// whenever a ray passes through it. Radii below [`MIN_RECEIVER_RADIUS`] are raised to it.
+    Volumetric { radius: f32 },
+}
+
+/// The smallest radius of volumetric receivers, in metres. The weight of every hit grows as the
+/// radius shrinks, and becomes infinite at zero.
+pub const MIN_RECEIVER_RADIUS: f32 = 0.01;
+
 /// Raytracer state.
 #[derive(Debug, Clone)]
 pub struct Tracer<'r> {
     pub space: &'r Space,
     pub config: &'r TracerConfig,
+    /// The wall ends sound diffracts around. Empty if diffraction is disabled.
+    edges: Vec<Edge>,
 }
 
 impl<'r> Tracer<'r> {
     pub fn new(space: &'r Space, config: &'r TracerConfig) -> Self {
-        Self { space, config }
+        let edges = if config.diffraction {
+            find_edges(space)
+        } else {
+            vec![]
+        };
+        Self {
+            space,
+            config,
+            edges,
+        }
     }
 
-    /// Traces a single ray for a microphone-speaker pair.
+    /// Traces a single ray for a microphone-speaker pair. The ray leaves the microphone, and
+    /// registers responses when it reaches the speaker.
     ///
     /// `start_ray` is assumed to be normalized.
     pub fn perform_trace(
@@ -45,7 +97,6 @@ impl<'r> Tracer<'r> {
         start_ray: Vec2,
     ) -> Recording {
         let _span = debug_span!("trace", from = microphone_index.0, to = speaker_index.0).entered();
-        let start = Instant::now();
 
         let microphone = &self.space.microphones[microphone_index.0];
         let speaker = &self.space.speakers[speaker_index.0];
@@ -53,71 +104,378 @@ impl<'r> Tracer<'r> {
         let inv_speed_of_sound = 1.0 / self.config.speed_of_sound;
 
         let mut responses = Vec::with_capacity(self.config.max_bounces);
+        let (rays, stats) = self.trace_paths(
+            microphone.position,
+            start_ray,
+            &[speaker.position],
+            |arrival| {
+                // The path goes towards the speaker, while sound leaves it the other way.
+                let gain = speaker.gain(-arrival.direction);
+                responses.push(Response {
+                    time: inv_speed_of_sound * arrival.distance,
+                    loudness: speaker.power * gain * arrival.weight,
+                    bounces: arrival.bounces,
+                });
+            },
+        );
+
+        Recording {
+            responses,
+            rays,
+            stats,
+        }
+    }
+
+    /// Traces a single ray leaving the speaker, and registers responses at every microphone it
+    /// reaches. This costs about as much as a single [`perform_trace`], no matter the number of
+    /// microphones.
+    ///
+    /// `start_ray` is assumed to be normalized.
+    ///
+    /// [`perform_trace`]: Tracer::perform_trace
+    pub fn perform_source_trace(&self, speaker_index: SpeakerIndex, start_ray: Vec2) -> Survey {
+        let _span = debug_span!("source trace", from = speaker_index.0).entered();
+
+        let speaker = &self.space.speakers[speaker_index.0];
+        let microphone_positions: Vec<_> = self
+            .space
+            .microphones
+            .iter()
+            .map(|microphone| microphone.position)
+            .collect();
+
+        let inv_speed_of_sound = 1.0 / self.config.speed_of_sound;
+
+        let mut responses = vec![vec![]; microphone_positions.len()];
+        let (rays, stats) = self.trace_paths(
+            speaker.position,
+            start_ray,
+            &microphone_positions,
+            |arrival| {
+                let gain = speaker.gain(arrival.departure);
+                responses[arrival.target].push(Response {
+                    time: inv_speed_of_sound * arrival.distance,
+                    loudness: speaker.power * gain * arrival.weight,
+                    bounces: arrival.bounces,
+                });
+            },
+        );
+
+        Survey {
+            responses,
+            rays,
+            stats,
+        }
+    }
+
+    /// Traces a ray from `origin` bouncing around the space, and calls `arrive` for every path
+    /// it finds to one of the `targets`, using the configured detection model. Returns the
+    /// recorded rays, along with statistics about the traced ray.
+    fn trace_paths(
+        &self,
+        origin: Vec2,
+        start_ray: Vec2,
+        targets: &[Vec2],
+        mut arrive: impl FnMut(Arrival),
+    ) -> (Vec<RecordedRay>, RayStats) {
+        let start = Instant::now();
+
         let mut recorded_rays = if self.config.record_rays {
-            Vec::with_capacity(self.config.max_bounces * 2)
+            Vec::with_capacity(self.config.max_bounces * (1 + targets.len()))
         } else {
             vec![]
         };
         let mut ray = Ray {
-            start: microphone.position,
+            start: origin,
             direction: start_ray,
         };
         let mut distance_bounced = 0.0_f32;
-        for _i in 0..(self.config.max_bounces + 1) {
-            if let Some(hit) = trace_to_walls(ray, self.space) {
-                if self.config.record_rays {
-                    recorded_rays.push(RecordedRay {
-                        purpose: RayPurpose::Bounce,
-                        ray,
-                        hit: hit.ray,
+        let mut next_hit = trace_to_walls(ray, self.space);
+        if self.config.include_direct_sound {
+            self.detect(
+                Segment::new(ray, next_hit, distance_bounced, 0, start_ray, 1.0),
+                targets,
+                &mut recorded_rays,
+                &mut arrive,
+            );
+        }
+        let max_distance = self.max_distance();
+        let mut bounces = 0;
+        let mut interactions = 0;
+        let mut energy = 1.0;
+        let mut termination = None;
+        for _ in 0..self.config.max_bounces {
+            let hit = if let Some(hit) = next_hit {
+                hit
+            } else {
+                trace!("empty space hit, finishing off");
+                break;
+            };
+            if distance_bounced + hit.ray.ray_length > max_distance {
+                trace!("time limit reached, finishing off");
+                termination = Some(Termination::TimeLimit);
+                break;
+            }
+            interactions += 1;
+            if self.config.record_rays {
+                recorded_rays.push(RecordedRay {
+                    purpose: RayPurpose::Bounce,
+                    ray,
+                    hit: hit.ray,
+                });
+            }
+
+            // Rather than splitting into a reflected and a transmitted ray, which would multiply
+            // the number of rays with every hit, the ray picks one of them at random, in
+            // proportion to how much sound each carries.
+            let transmitted = fastrand::f32() < wall_transmission(self.space, hit);
+            let direction = if transmitted {
+                ray.direction
+            } else {
+                bounces += 1;
+                energy *= wall_reflectance(self.space, hit);
+                math::reflect(ray.direction, hit.ray.normal)
+            };
+            if energy < self.config.energy_threshold {
+                let survival = energy / self.config.energy_threshold;
+                if fastrand::f32() >= survival {
+                    trace!("ray ran out of energy, finishing off");
+                    termination = Some(Termination::Absorbed);
+                    break;
+                }
+                energy /= survival;
+            }
+            ray = hit.ray.leave(direction);
+            distance_bounced += hit.ray.ray_length;
+            next_hit = trace_to_walls(ray, self.space);
+
+            // Shadow rays are only cast from where the path changes direction; from anywhere
+            // else, they'd count the path leading there a second time.
+            let detects = match self.config.detection {
+                Detection::ShadowRays => !transmitted,
+                Detection::Volumetric { .. } => bounces > 0 || self.config.include_direct_sound,
+            };
+            if detects {
+                self.detect(
+                    Segment::new(ray, next_hit, distance_bounced, bounces, start_ray, energy),
+                    targets,
+                    &mut recorded_rays,
+                    &mut arrive,
+                );
+            }
+        }
+
+        let end = Instant::now();
+        trace!("tracing took {:?}", end - start);
+
+        // A ray with nothing left to hit escapes, even if it ran out of bounces just before.
+        let termination = match termination {
+            Some(termination) => termination,
+            None if next_hit.is_none() => Termination::Escaped(ray),
+            None => Termination::MaxBounces,
+        };
+        let stats = RayStats {
+            termination,
+            bounces: interactions,
+            energy,
+        };
+        (recorded_rays, stats)
+    }
+
+    /// Returns how far sound travels in the configured maximal time.
+    fn max_distance(&self) -> f32 {
+        self.config.max_time * self.config.speed_of_sound
+    }
+
+    /// Finds the paths from the segment to the `targets`.
+    fn detect(
+        &self,
+        segment: Segment,
+        targets: &[Vec2],
+        recorded_rays: &mut Vec<RecordedRay>,
+        arrive: &mut impl FnMut(Arrival),
+    ) {
+        let max_distance = self.max_distance();
+        for (target, &position) in targets.iter().enumerate() {
+            let mut arrive = |arrival: Arrival| {
+                // A target overlapping the ray's origin has no meaningful path to it.
+                if arrival.distance > 0.0 && arrival.distance <= max_distance {
+                    arrive(Arrival {
+                        weight: arrival.weight * segment.energy,
+                        ..arrival
                     });
                 }
+            };
+            match self.config.detection {
+                Detection::ShadowRays => {
+                    let trace = trace_to_point(segment.ray.start, self.space, position);
+                    if let Some(trace) = trace {
+                        if self.config.record_rays {
+                            recorded_rays.push(trace.to_recorded_ray());
+                        }
 
-                let wall = &self.space.walls[hit.wall.0];
-                let reflected = wall.reflect(ray.direction);
-                ray = Ray {
-                    start: hit.ray.position + reflected * 0.001,
-                    direction: reflected,
-                };
-                distance_bounced += hit.ray.ray_length;
-
-                if let Some(trace) = trace_to_speaker(ray.start, self.space, speaker) {
-                    if self.config.record_rays {
-                        recorded_rays.push(RecordedRay {
-                            purpose: RayPurpose::Trace,
-                            ray: trace.ray,
-                            hit: RayHit {
-                                position: speaker.position,
-                                ray_length: trace.distance_to_speaker,
-                            },
+                        let distance = segment.distance_before + trace.distance;
+                        arrive(Arrival {
+                            target,
+                            distance,
+                            direction: trace.ray.direction,
+                            departure: segment.departure(trace.ray.direction),
+                            weight: trace.transmission / distance,
+                            bounces: segment.bounces,
+                        });
+                    }
+                    // Sound can also bend around walls which let only some of it through.
+                    let hidden = !trace.is_some_and(|trace| trace.transmission >= 1.0);
+                    if self.config.diffraction && hidden {
+                        self.diffract(segment, target, position, recorded_rays, &mut arrive);
+                    }
+                }
+                Detection::Volumetric { radius } => {
+                    let radius = radius.max(MIN_RECEIVER_RADIUS);
+                    if let Some(hit) = segment
+                        .ray
+                        .cast_circle(position, radius)
+                        .filter(|hit| hit.ray_length <= segment.length)
+                    {
+                        arrive(Arrival {
+                            target,
+                            distance: segment.distance_before + hit.ray_length,
+                            direction: segment.ray.direction,
+                            departure: segment.departure(segment.ray.direction),
+                            weight: volumetric_weight(radius),
+                            bounces: segment.bounces,
                         });
                     }
-
-                    let distance_travelled = distance_bounced + trace.distance_to_speaker;
-                    let time = inv_speed_of_sound * distance_travelled;
-                    let loudness = speaker.power / distance_travelled;
-                    responses.push(Response {
-                        time,
-                        loudness,
-                        bounces: _i,
-                    });
                 }
-            } else {
-                trace!("empty space hit, finishing off");
-                break;
             }
         }
+    }
 
-        let end = Instant::now();
-        trace!("tracing took {:?}", end - start);
+    /// Finds the paths from the segment's start to a target hidden from it, which bend around
+    /// the free ends of walls.
+    fn diffract(
+        &self,
+        segment: Segment,
+        target: usize,
+        position: Vec2,
+        recorded_rays: &mut Vec<RecordedRay>,
+        arrive: &mut impl FnMut(Arrival),
+    ) {
+        let start = segment.ray.start;
+        for edge in &self.edges {
+            let weight = match diffracted_weight(
+                edge,
+                start,
+                segment.distance_before,
+                position,
+                self.config.speed_of_sound,
+            ) {
+                Some(weight) => weight,
+                None => continue,
+            };
+            let corner = edge.visibility_point();
+            let (to_edge, from_edge) = match (
+                trace_to_point(start, self.space, corner),
+                trace_to_point(corner, self.space, position),
+            ) {
+                (Some(to_edge), Some(from_edge)) => (to_edge, from_edge),
+                _ => continue,
+            };
+            if self.config.record_rays {
+                recorded_rays.push(to_edge.to_recorded_ray());
+                recorded_rays.push(from_edge.to_recorded_ray());
+            }
 
-        Recording {
-            responses,
-            rays: recorded_rays,
+            arrive(Arrival {
+                target,
+                distance: segment.distance_before
+                    + start.distance(edge.position)
+                    + edge.position.distance(position),
+                direction: from_edge.ray.direction,
+                departure: segment.departure(to_edge.ray.direction),
+                weight: weight * to_edge.transmission * from_edge.transmission,
+                bounces: segment.bounces,
+            });
+        }
+    }
+}
+
+/// A straight part of a traced ray's path, between two bounces.
+#[derive(Debug, Clone, Copy)]
+struct Segment {
+    ray: Ray,
+    /// How far the ray travels before hitting a wall. Infinite if it escapes the space.
+    length: f32,
+    /// How far the ray has travelled before this segment.
+    distance_before: f32,
+    /// How many times the ray has bounced off of walls before this segment.
+    bounces: usize,
+    /// The direction the ray left its origin in.
+    departure: Vec2,
+    /// The share of its initial energy the ray carries along this segment.
+    energy: f32,
+}
+
+impl Segment {
+    fn new(
+        ray: Ray,
+        end: Option<WallHit>,
+        distance_before: f32,
+        bounces: usize,
+        departure: Vec2,
+        energy: f32,
+    ) -> Self {
+        Self {
+            ray,
+            length: end.map_or(f32::INFINITY, |hit| hit.ray.ray_length),
+            distance_before,
+            bounces,
+            departure,
+            energy,
+        }
+    }
+
+    /// Returns the direction a path through this segment leaves the origin in, given the
+    /// direction of the path's first leg away from the segment's start. The two only differ for
+    /// paths which haven't bounced yet.
+    fn departure(&self, first_leg: Vec2) -> Vec2 {
+        if self.bounces == 0 {
+            first_leg
+        } else {
+            self.departure
         }
     }
 }
 
+/// A path found from a traced ray's origin to one of its targets.
+#[derive(Debug, Clone, Copy)]
+struct Arrival {
+    /// The index of the target that was reached.
+    target: usize,
+    /// The total length of the path.
+    distance: f32,
+    /// The direction the path travels in as it reaches the target.
+    direction: Vec2,
+    /// The direction the path leaves the origin in.
+    departure: Vec2,
+    /// The loudness carried by the path, per unit of the source's power.
+    weight: f32,
+    bounces: usize,
+}
+
+/// Returns the loudness a single ray carries into a volumetric receiver of the given radius, per
+/// unit of the source's power.
+///
+/// Out of many rays, a path of length `d` to a receiver of radius `r` is taken by about `2r / 2πd`
+/// of them, the share of a circle of radius `d` the receiver covers. Weighting each hit by the
+/// shadow-ray estimator's `1 / d` divided by that share cancels out the distance, such that on
+/// average, every path contributes `1 / d` per ray regardless of the receiver's size (as long as
+/// it's small compared to `d`.) Unlike with shadow rays, every distinct path is counted, rather
+/// than however many paths the rays happen to find.
+fn volumetric_weight(radius: f32) -> f32 {
+    PI / radius
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct WallHit {
     pub ray: RayHit,
@@ -126,12 +484,12 @@ pub struct WallHit {
 
 /// Traces the ray against all walls within the space, and returns the wall that was hit
 /// (if any.)
-fn trace_to_walls(ray: Ray, space: &Space) -> Option<WallHit> {
+pub(crate) fn trace_to_walls(ray: Ray, space: &Space) -> Option<WallHit> {
     // Kind of naive approach but good code is outside my budget atm
     let mut closest_hit: Option<RayHit> = None;
     let mut closest_wall = 0;
     for (wall_index, wall) in space.walls.iter().enumerate() {
-        if let Some(hit) = ray.cast(LineSegment::from_wall(wall)) {
+        if let Some(hit) = ray.cast_wall(wall) {
             if closest_hit.is_none() {
                 closest_wall = wall_index;
                 closest_hit = Some(hit);
@@ -154,27 +512,398 @@ fn trace_to_walls(ray: Ray, space: &Space) -> Option<WallHit> {
 }
 
 #[derive(Debug, Clone, Copy)]
-struct SpeakerTrace {
+struct PointTrace {
     ray: Ray,
-    distance_to_speaker: f32,
+    distance: f32,
+    /// The share of sound which makes it through the walls in the way.
+    transmission: f32,
 }
 
-/// Traces from the given start point to the speaker, and returns a trace if the speaker can be
-/// reached. Otherwise returns None.
-fn trace_to_speaker(start: Vec2, space: &Space, speaker: &Speaker) -> Option<SpeakerTrace> {
-    let direction_unnormalized = speaker.position - start;
+impl PointTrace {
+    fn to_recorded_ray(self) -> RecordedRay {
+        RecordedRay {
+            purpose: RayPurpose::Trace,
+            ray: self.ray,
+            hit: RayHit {
+                position: self.ray.start + self.ray.direction * self.distance,
+                ray_length: self.distance,
+                normal: -self.ray.direction,
+                // Points don't have sides.
+                side: Side::Front,
+            },
+        }
+    }
+}
+
+/// Traces from the given start point to the given end point, and returns a trace if the end point
+/// can be reached, possibly through walls which let sound through. Otherwise returns None.
+fn trace_to_point(start: Vec2, space: &Space, end: Vec2) -> Option<PointTrace> {
+    let direction_unnormalized = end - start;
     let distance = direction_unnormalized.length();
     let direction = direction_unnormalized / distance;
 
-    let ray = Ray { start, direction };
-    let trace = SpeakerTrace {
-        ray,
-        distance_to_speaker: distance,
+    let mut transmission = 1.0;
+    let mut ray = Ray { start, direction };
+    loop {
+        match trace_to_walls(ray, space) {
+            Some(hit) if hit.ray.ray_length < ray.start.distance(end) => {
+                transmission *= wall_transmission(space, hit);
+                if transmission <= 0.0 {
+                    return None;
+                }
+                ray = hit.ray.leave(direction);
+            }
+            _ => break,
+        }
+    }
+
+    Some(PointTrace {
+        ray: Ray { start, direction },
+        distance,
+        transmission,
+    })
+}
+
+/// Returns the share of sound which reflects off of the wall rather than being absorbed, when hit
+/// on the given side.
+fn wall_reflectance(space: &Space, hit: WallHit) -> f32 {
+    let material = space.walls[hit.wall.0].material_on(hit.ray.side);
+    space
+        .materials
+        .get(material.0)
+        .map_or(1.0, |material| material.diffuse.clamp(0.0, 1.0))
+}
+
+/// Returns the share of sound which passes through the wall, when hit on the given side.
+fn wall_transmission(space: &Space, hit: WallHit) -> f32 {
+    let material = space.walls[hit.wall.0].material_on(hit.ray.side);
+    space
+        .materials
+        .get(material.0)
+        .map_or(0.0, |material| material.transmission)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::TAU;
+
+    use fizzerb_model::{
+        walls::make_box, Directivity, Material, Microphone, Speaker, Wall, WallShape,
     };
+    use glam::vec2;
+
+    use super::*;
+
+    const MICROPHONE: Vec2 = vec2(3.0, 3.0);
+    const SPEAKER: Vec2 = vec2(6.0, 5.0);
+    const ROOM_SIZE: Vec2 = vec2(10.0, 8.0);
+
+    /// A room with fully reflective walls, one microphone and one speaker.
+    fn box_room() -> Space {
+        let mut space = Space::new();
+        let material = space.add_material(Material::default());
+        space.add_walls(make_box(Vec2::ZERO, ROOM_SIZE, material));
+        space.add_microphone(Microphone {
+            position: MICROPHONE,
+        });
+        space.add_speaker(Speaker {
+            position: SPEAKER,
+            power: 1.0,
+            directivity: Directivity::Omnidirectional,
+            direction: Vec2::X,
+        });
+        space
+    }
+
+    const RAYS: usize = 100_000;
+
+    /// Returns the directions of rays evenly spread around their origin.
+    fn ray_directions() -> impl Iterator<Item = Vec2> {
+        (0..RAYS).map(|i| Vec2::from_angle((i as f32 + 0.5) / RAYS as f32 * TAU))
+    }
+
+    fn config(detection: Detection, max_bounces: usize) -> TracerConfig {
+        TracerConfig {
+            speed_of_sound: SPEED_OF_SOUND_IN_AIR,
+            max_bounces,
+            energy_threshold: 0.0,
+            max_time: 1.0,
+            record_rays: false,
+            detection,
+            include_direct_sound: true,
+            diffraction: false,
+        }
+    }
+
+    /// Adds the loudness of the responses per ray to `energy`, by their number of bounces.
+    fn add_energy(energy: &mut [f32], responses: &[Response]) {
+        for response in responses {
+            energy[response.bounces] += response.loudness / RAYS as f32;
+        }
+    }
+
+    /// Traces rays evenly spread around the first microphone, and returns the loudness they
+    /// register per ray, summed by the number of bounces.
+    fn trace_energy(space: &Space, config: &TracerConfig) -> Vec<f32> {
+        let tracer = Tracer::new(space, config);
+
+        let mut energy = vec![0.0; config.max_bounces + 1];
+        for direction in ray_directions() {
+            let recording = tracer.perform_trace(MicrophoneIndex(0), SpeakerIndex(0), direction);
+            add_energy(&mut energy, &recording.responses);
+        }
+        energy
+    }
+
+    /// Returns the energy per number of bounces in [`box_room`], up to `max_bounces`.
+    fn energy_by_order(detection: Detection, max_bounces: usize) -> Vec<f32> {
+        trace_energy(&box_room(), &config(detection, max_bounces))
+    }
+
+    fn assert_close(actual: f32, expected: f32, tolerance: f32) {
+        assert!(
+            (actual - expected).abs() <= expected * tolerance,
+            "expected {expected} within {}%, got {actual}",
+            tolerance * 100.0
+        );
+    }
 
-    if let Some(hit) = trace_to_walls(ray, space) {
-        (hit.ray.ray_length >= distance).then_some(trace)
-    } else {
-        Some(trace)
+    #[test]
+    fn volumetric_and_shadow_ray_direct_sound_agree() {
+        let shadow_rays = energy_by_order(Detection::ShadowRays, 0);
+        let volumetric = energy_by_order(Detection::Volumetric { radius: 0.25 }, 0);
+        let expected = 1.0 / MICROPHONE.distance(SPEAKER);
+        assert_close(shadow_rays[0], expected, 0.001);
+        assert_close(volumetric[0], expected, 0.05);
+    }
+
+    #[test]
+    fn direct_sound_can_be_left_out() {
+        let config = TracerConfig {
+            include_direct_sound: false,
+            ..config(Detection::ShadowRays, 1)
+        };
+        let energy = trace_energy(&box_room(), &config);
+        assert_eq!(energy[0], 0.0);
+        assert!(energy[1] > 0.0);
+    }
+
+    #[test]
+    fn direct_sound_is_weighted_by_directivity() {
+        let towards_microphone = (MICROPHONE - SPEAKER).normalize();
+        let config = config(Detection::ShadowRays, 0);
+        let mut space = box_room();
+        space.speakers[0].directivity = Directivity::Cardioid;
+
+        space.speakers[0].direction = towards_microphone;
+        let facing = trace_energy(&space, &config);
+        assert_close(facing[0], 1.0 / MICROPHONE.distance(SPEAKER), 0.001);
+
+        space.speakers[0].direction = -towards_microphone;
+        let facing_away = trace_energy(&space, &config);
+        assert!(facing_away[0].abs() < 1e-6);
+    }
+
+    /// Adds a wall of the given transmission across the line of sight, halfway between the
+    /// microphone and the speaker.
+    fn add_partition(space: &mut Space, transmission: f32) {
+        let material = space.add_material(Material {
+            transmission,
+            ..Material::default()
+        });
+        let middle = (MICROPHONE + SPEAKER) / 2.0;
+        let across = (SPEAKER - MICROPHONE).perp().normalize();
+        space.add_wall(Wall {
+            start: middle - across,
+            end: middle + across,
+            shape: WallShape::Line,
+            material,
+            back_material: None,
+        });
+    }
+
+    #[test]
+    fn direct_sound_is_occluded_by_walls() {
+        let mut space = box_room();
+        add_partition(&mut space, 0.0);
+        assert!(trace_to_point(MICROPHONE, &space, SPEAKER).is_none());
+        let energy = trace_energy(&space, &config(Detection::ShadowRays, 0));
+        assert_eq!(energy[0], 0.0);
+    }
+
+    #[test]
+    fn shadow_rays_are_attenuated_by_walls_in_the_way() {
+        let mut space = box_room();
+        add_partition(&mut space, 0.5);
+        let energy = trace_energy(&space, &config(Detection::ShadowRays, 0));
+        assert_close(energy[0], 0.5 / MICROPHONE.distance(SPEAKER), 0.001);
+    }
+
+    /// Rays pick between passing through a wall and reflecting off of it at random, which must
+    /// leave both the transmitted and the reflected sound at their share of the energy on
+    /// average.
+    #[test]
+    fn transmission_is_unbiased() {
+        fastrand::seed(0x5eed);
+        let transmission = 0.3;
+        let microphone = Vec2::ZERO;
+        // One speaker behind a long wall, and one in front of it.
+        let behind = vec2(4.0, 0.0);
+        let in_front = vec2(0.0, 1.0);
+
+        let mut space = Space::new();
+        let material = space.add_material(Material {
+            transmission,
+            ..Material::default()
+        });
+        space.add_wall(Wall {
+            start: vec2(2.0, -100.0),
+            end: vec2(2.0, 100.0),
+            shape: WallShape::Line,
+            material,
+            back_material: None,
+        });
+        space.add_microphone(Microphone {
+            position: microphone,
+        });
+        for position in [behind, in_front] {
+            space.add_speaker(Speaker {
+                position,
+                power: 1.0,
+                directivity: Directivity::Omnidirectional,
+                direction: Vec2::X,
+            });
+        }
+        let config = config(Detection::Volumetric { radius: 0.25 }, 1);
+        let tracer = Tracer::new(&space, &config);
+
+        const RUNS: usize = 10;
+        let mut transmitted = 0.0;
+        let mut reflected = 0.0;
+        for _ in 0..RUNS {
+            for direction in ray_directions() {
+                let recording =
+                    tracer.perform_trace(MicrophoneIndex(0), SpeakerIndex(0), direction);
+                for response in recording.responses {
+                    transmitted += response.loudness / (RAYS * RUNS) as f32;
+                }
+                let recording =
+                    tracer.perform_trace(MicrophoneIndex(0), SpeakerIndex(1), direction);
+                for response in recording
+                    .responses
+                    .iter()
+                    .filter(|response| response.bounces == 1)
+                {
+                    reflected += response.loudness / (RAYS * RUNS) as f32;
+                }
+            }
+        }
+
+        assert_close(
+            transmitted,
+            transmission / microphone.distance(behind),
+            0.05,
+        );
+        let image = vec2(4.0 - in_front.x, in_front.y);
+        assert_close(
+            reflected,
+            (1.0 - transmission) / microphone.distance(image),
+            0.05,
+        );
+    }
+
+    /// Rays stopped by Russian roulette are made up for by the ones that survive it, which must
+    /// leave the energy of every reflection order the same on average.
+    #[test]
+    fn russian_roulette_is_unbiased() {
+        fastrand::seed(0x5eed);
+        let mut space = box_room();
+        space.materials[0].diffuse = 0.5;
+        let config = config(Detection::ShadowRays, 4);
+        let expected = trace_energy(&space, &config);
+        let roulette = trace_energy(
+            &space,
+            &TracerConfig {
+                energy_threshold: 0.2,
+                ..config
+            },
+        );
+        for (&actual, &expected) in roulette.iter().zip(&expected) {
+            assert_close(actual, expected, 0.05);
+        }
+    }
+
+    /// Volumetric receivers count every path the rays take, so their first-order energy is the
+    /// sum over the speaker's mirror images in the four walls. Shadow rays only find one
+    /// reflection per ray instead, which makes their first-order energy much lower.
+    #[test]
+    fn volumetric_first_order_energy_matches_image_sources() {
+        let images = [
+            vec2(-SPEAKER.x, SPEAKER.y),
+            vec2(2.0 * ROOM_SIZE.x - SPEAKER.x, SPEAKER.y),
+            vec2(SPEAKER.x, -SPEAKER.y),
+            vec2(SPEAKER.x, 2.0 * ROOM_SIZE.y - SPEAKER.y),
+        ];
+        let expected: f32 = images
+            .iter()
+            .map(|&image| 1.0 / MICROPHONE.distance(image))
+            .sum();
+
+        let volumetric = energy_by_order(Detection::Volumetric { radius: 0.25 }, 1);
+        assert_close(volumetric[1], expected, 0.05);
+        let shadow_rays = energy_by_order(Detection::ShadowRays, 1);
+        assert!(shadow_rays[1] > 0.0 && shadow_rays[1] < volumetric[1]);
+    }
+
+    #[test]
+    fn volumetric_receivers_have_a_minimal_radius() {
+        for radius in [0.0, -1.0, f32::NAN] {
+            let energy = energy_by_order(Detection::Volumetric { radius }, 1);
+            assert!(energy.iter().all(|energy| energy.is_finite()));
+        }
+    }
+
+    /// Volumetric receivers estimate the same paths no matter which end they're traced from, so
+    /// tracing from the speaker must find the same energy at every microphone as tracing from
+    /// each microphone does. That includes the weighting by the speaker's directivity.
+    #[test]
+    fn source_traces_find_the_same_energy_as_microphone_traces() {
+        for directivity in [Directivity::Omnidirectional, Directivity::Cardioid] {
+            compare_source_and_microphone_traces(directivity);
+        }
+    }
+
+    fn compare_source_and_microphone_traces(directivity: Directivity) {
+        let mut space = box_room();
+        space.speakers[0].directivity = directivity;
+        space.add_microphone(Microphone {
+            position: vec2(8.5, 1.5),
+        });
+        let max_bounces = 2;
+        let config = config(Detection::Volumetric { radius: 0.25 }, max_bounces);
+        let tracer = Tracer::new(&space, &config);
+
+        let mut from_microphones = vec![vec![0.0; max_bounces + 1]; 2];
+        let mut from_speaker = vec![vec![0.0; max_bounces + 1]; 2];
+        for direction in ray_directions() {
+            for (microphone, energy) in from_microphones.iter_mut().enumerate() {
+                let recording =
+                    tracer.perform_trace(MicrophoneIndex(microphone), SpeakerIndex(0), direction);
+                add_energy(energy, &recording.responses);
+            }
+            let survey = tracer.perform_source_trace(SpeakerIndex(0), direction);
+            assert_eq!(survey.responses.len(), 2);
+            for (energy, responses) in from_speaker.iter_mut().zip(&survey.responses) {
+                add_energy(energy, responses);
+            }
+        }
+
+        for (expected, actual) in from_microphones.iter().zip(&from_speaker) {
+            for (&expected, &actual) in expected.iter().zip(actual) {
+                assert_close(actual, expected, 0.01);
+            }
+        }
+        // The microphones hear different amounts of energy, so mixing them up would show.
+        assert!((from_speaker[0][0] - from_speaker[1][0]).abs() > 0.05 * from_speaker[0][0]);
     }
 }
@@ -1,8 +1,10 @@
 use druid::{
     widget::{Controller, ControllerHost},
-    Env, Event, EventCtx, KbKey, KeyEvent, Selector, Target, Widget,
+    Env, Event, EventCtx, HotKey, KbKey, KeyEvent, Point, Selector, SysMods, Target, Widget,
 };
 
+use crate::widgets::tool::Tool;
+
 macro_rules! command {
     ($name:tt) => {
         Selector::new(concat!("net.liquidev.fizzerb.", $name))
@@ -10,16 +12,57 @@ macro_rules! command {
 }
 
 pub const DELETE: Selector = command!("delete");
+pub const CANCEL: Selector = command!("cancel");
+pub const UNDO: Selector = command!("undo");
+pub const REDO: Selector = command!("redo");
+pub const COPY: Selector = command!("copy");
+pub const CUT: Selector = command!("cut");
+pub const PASTE: Selector = command!("paste");
+pub const DUPLICATE: Selector = command!("duplicate");
+pub const SET_TOOL: Selector<Tool> = command!("set-tool");
+pub const INCREASE_SIDES: Selector = command!("increase-sides");
+pub const DECREASE_SIDES: Selector = command!("decrease-sides");
+pub const TOGGLE_SNAPPING: Selector = command!("toggle-snapping");
+/// Sent once a render finishes, with the places where rays escaped the space, in the space's units.
+pub const SHOW_LEAKS: Selector<Vec<Point>> = command!("show-leaks");
 
 pub struct Commander;
 
 impl Commander {
     fn consume_key(ctx: &mut EventCtx, keyboard: &KeyEvent) {
-        let selector = match keyboard.key {
-            KbKey::Delete => DELETE,
-            _ => return,
+        let shortcuts = [
+            (HotKey::new(None, KbKey::Delete), DELETE.with(())),
+            (HotKey::new(None, KbKey::Escape), CANCEL.with(())),
+            (HotKey::new(SysMods::Cmd, "z"), UNDO.with(())),
+            (HotKey::new(SysMods::CmdShift, "Z"), REDO.with(())),
+            (HotKey::new(SysMods::Cmd, "y"), REDO.with(())),
+            (HotKey::new(SysMods::Cmd, "c"), COPY.with(())),
+            (HotKey::new(SysMods::Cmd, "x"), CUT.with(())),
+            (HotKey::new(SysMods::Cmd, "v"), PASTE.with(())),
+            (HotKey::new(SysMods::Cmd, "d"), DUPLICATE.with(())),
+            (HotKey::new(None, "v"), SET_TOOL.with(Tool::Cursor)),
+            (HotKey::new(None, "w"), SET_TOOL.with(Tool::Wall)),
+            (HotKey::new(None, "p"), SET_TOOL.with(Tool::Polyline)),
+            (HotKey::new(None, "r"), SET_TOOL.with(Tool::Rectangle)),
+            (HotKey::new(None, "o"), SET_TOOL.with(Tool::Polygon)),
+            (HotKey::new(None, "a"), SET_TOOL.with(Tool::Arc)),
+            (HotKey::new(None, "b"), SET_TOOL.with(Tool::Bezier)),
+            (HotKey::new(None, "m"), SET_TOOL.with(Tool::Microphone)),
+            (HotKey::new(None, "s"), SET_TOOL.with(Tool::Speaker)),
+            (HotKey::new(None, "u"), SET_TOOL.with(Tool::Ruler)),
+            (HotKey::new(None, "k"), SET_TOOL.with(Tool::Calibrate)),
+            (HotKey::new(None, "]"), INCREASE_SIDES.with(())),
+            (HotKey::new(None, "["), DECREASE_SIDES.with(())),
+            (HotKey::new(None, "g"), TOGGLE_SNAPPING.with(())),
+        ];
+        let command = match shortcuts
+            .into_iter()
+            .find(|(hotkey, _)| hotkey.matches(keyboard))
+        {
+            Some((_, command)) => command,
+            None => return,
         };
-        ctx.submit_command(selector.with(()).to(Target::Widget(ctx.widget_id())));
+        ctx.submit_command(command.to(Target::Widget(ctx.widget_id())));
         ctx.set_handled();
     }
 }
@@ -33,8 +76,15 @@ where
             Event::WindowConnected => {
                 ctx.request_focus();
             }
+            // Take focus back from text boxes when clicking elsewhere. If the click lands on a
+            // text box, it requests focus after us and thus keeps it.
+            Event::MouseDown(_) => {
+                ctx.request_focus();
+            }
+            // Shortcuts are only handled while no other widget has focus, such that typing into
+            // text boxes does not trigger them.
             Event::KeyDown(keyboard) => {
-                if !keyboard.repeat {
+                if !keyboard.repeat && ctx.is_focused() {
                     Self::consume_key(ctx, keyboard);
                 }
             }
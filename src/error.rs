@@ -1,5 +1,7 @@
 use thiserror::Error;
 
+use crate::{import::ImportError, project::ProjectError};
+
 #[derive(Debug, Error)]
 pub enum Error {
     #[error("platform error: {0}")]
@@ -13,4 +15,10 @@ pub enum Error {
 
     #[error("WAV error: {0}")]
     Wav(#[from] hound::Error),
+
+    #[error("import error: {0}")]
+    Import(#[from] ImportError),
+
+    #[error("project error: {0}")]
+    Project(#[from] ProjectError),
 }
@@ -0,0 +1,257 @@
+//! Exporting spaces as SVG drawings, for use in documentation.
+
+use std::{
+    fmt::{self, Write as _},
+    path::Path,
+};
+
+use druid::{Affine, Rect};
+use fizzerb_tracer::RecordedRay;
+use tracing::info;
+
+use crate::{
+    error::Error,
+    math::{nice_step, GlamExtToDruid},
+    widgets::data::{Directivity, EditableSpace, Object},
+};
+
+/// The width of the exported drawing, in pixels.
+const DRAWING_WIDTH: f64 = 800.0;
+/// Spacing around the drawing and between the elements of its footer, in pixels.
+const MARGIN: f64 = 24.0;
+/// The height of the footer holding the scale bar and material legend, in pixels.
+const FOOTER_HEIGHT: f64 = 32.0;
+const FONT_SIZE: f64 = 12.0;
+
+const WALL_THICKNESS: f64 = 3.0;
+const MICROPHONE_RADIUS: f64 = 6.0;
+const MICROPHONE_THICKNESS: f64 = 2.0;
+const SPEAKER_RADIUS: f64 = 6.0;
+const DIRECTIVITY_RADIUS: f64 = 40.0;
+const DIRECTIVITY_THICKNESS: f64 = 1.0;
+const RAY_THICKNESS: f64 = 0.5;
+const MIN_SCALE_BAR_WIDTH: f64 = 80.0;
+const SCALE_BAR_THICKNESS: f64 = 1.5;
+const SCALE_BAR_TICK_SIZE: f64 = 6.0;
+
+const TEXT_COLOR: &str = "#071013";
+const MICROPHONE_COLOR: &str = "#23B5D3";
+const SPEAKER_COLOR: &str = "#EC5740";
+const BOUNCE_RAY_COLOR: &str = "#F2A541";
+const TRACE_RAY_COLOR: &str = "#8FB339";
+/// Colors of walls, indexed by their material. Wraps around if there are more materials.
+const MATERIAL_COLORS: &[&str] = &[
+    "#071013", "#7D8491", "#B5523B", "#8C5E34", "#3C91E6", "#6A4C93", "#C05299",
+];
+
+fn material_color(index: usize) -> &'static str {
+    MATERIAL_COLORS[index % MATERIAL_COLORS.len()]
+}
+
+/// Escapes text for use in XML content and attribute values.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Returns the area the drawing should cover, in space units.
+fn drawing_bounds(space: &EditableSpace, scale_hint: f64) -> Rect {
+    let bounds = space
+        .objects
+        .iter()
+        .map(|object| match object {
+            // Leave room for the directivity pattern.
+            Object::Speaker(speaker) if speaker.directivity != Directivity::Omnidirectional => {
+                Rect::from_center_size(speaker.position, (0.0, 0.0)).inflate(scale_hint, scale_hint)
+            }
+            object => object.bounding_box(),
+        })
+        .reduce(|a, b| a.union(b))
+        .unwrap_or(Rect::ZERO);
+    // Degenerate spaces still need a nonzero area to be scaled to the drawing's width.
+    if bounds.width() <= 0.0 {
+        bounds.inflate(0.5, 0.0)
+    } else {
+        bounds
+    }
+}
+
+/// Converts the space into an SVG document.
+///
+/// Walls are colored by their material, and listed in a legend next to a scale bar. The recorded
+/// rays, if any, are drawn over the space; their coordinates are in metres, as produced by the
+/// tracer.
+pub fn to_svg(space: &EditableSpace, rays: &[RecordedRay]) -> String {
+    let mut svg = String::new();
+    write_svg(&mut svg, space, rays).expect("writing to a String cannot fail");
+    svg
+}
+
+fn write_svg(svg: &mut String, space: &EditableSpace, rays: &[RecordedRay]) -> fmt::Result {
+    // The directivity patterns' size is fixed on the page, so it depends on the scale, which
+    // depends on the bounds. Approximate it using the bounds without the patterns.
+    let rough_bounds = drawing_bounds(space, 0.0);
+    let rough_scale = (DRAWING_WIDTH - 2.0 * MARGIN) / rough_bounds.width();
+    let bounds = drawing_bounds(space, DIRECTIVITY_RADIUS / rough_scale);
+
+    // Pixels per space unit.
+    let scale = (DRAWING_WIDTH - 2.0 * MARGIN) / bounds.width();
+    let height = bounds.height() * scale + 3.0 * MARGIN + FOOTER_HEIGHT;
+    let to_page = Affine::translate((MARGIN, MARGIN))
+        * Affine::scale(scale)
+        * Affine::translate(-bounds.origin().to_vec2());
+
+    writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{DRAWING_WIDTH}" height="{height:.1}" viewBox="0 0 {DRAWING_WIDTH} {height:.1}" font-family="sans-serif" font-size="{FONT_SIZE}">"#
+    )?;
+    writeln!(svg, r#"<rect width="100%" height="100%" fill="white"/>"#)?;
+
+    writeln!(svg, r#"<g id="walls" fill="none" stroke-linecap="round">"#)?;
+    for object in &space.objects {
+        if let Object::Wall(wall) = object {
+            writeln!(
+                svg,
+                r#"<path d="{}" stroke="{}" stroke-width="{WALL_THICKNESS}"/>"#,
+                (to_page * wall.to_path()).to_svg(),
+                material_color(wall.material.0),
+            )?;
+        }
+    }
+    writeln!(svg, "</g>")?;
+
+    if !rays.is_empty() {
+        let to_space_units = 1.0 / space.unit.to_metres();
+        writeln!(
+            svg,
+            r#"<g id="rays" stroke-width="{RAY_THICKNESS}" stroke-opacity="0.5">"#
+        )?;
+        for ray in rays {
+            let start = to_page * (ray.ray.start.to_druid().to_vec2() * to_space_units).to_point();
+            let end = to_page * (ray.hit.position.to_druid().to_vec2() * to_space_units).to_point();
+            let color = if ray.purpose.is_bounce() {
+                BOUNCE_RAY_COLOR
+            } else {
+                TRACE_RAY_COLOR
+            };
+            writeln!(
+                svg,
+                r#"<line x1="{:.2}" y1="{:.2}" x2="{:.2}" y2="{:.2}" stroke="{color}"/>"#,
+                start.x, start.y, end.x, end.y,
+            )?;
+        }
+        writeln!(svg, "</g>")?;
+    }
+
+    writeln!(svg, r#"<g id="sources-and-receivers">"#)?;
+    for object in &space.objects {
+        match object {
+            Object::Wall(_) => (),
+            Object::Microphone(microphone) => {
+                let center = to_page * microphone.position;
+                writeln!(
+                    svg,
+                    r#"<circle cx="{:.2}" cy="{:.2}" r="{}" fill="none" stroke="{MICROPHONE_COLOR}" stroke-width="{MICROPHONE_THICKNESS}"/>"#,
+                    center.x,
+                    center.y,
+                    MICROPHONE_RADIUS - MICROPHONE_THICKNESS * 0.5,
+                )?;
+            }
+            Object::Speaker(speaker) => {
+                let center = to_page * speaker.position;
+                if speaker.directivity != Directivity::Omnidirectional {
+                    let outline =
+                        speaker
+                            .directivity
+                            .outline(center, speaker.angle, DIRECTIVITY_RADIUS);
+                    writeln!(
+                        svg,
+                        r#"<path d="{}" fill="{SPEAKER_COLOR}" fill-opacity="0.1" stroke="{SPEAKER_COLOR}" stroke-width="{DIRECTIVITY_THICKNESS}"/>"#,
+                        outline.to_svg(),
+                    )?;
+                }
+                writeln!(
+                    svg,
+                    r#"<circle cx="{:.2}" cy="{:.2}" r="{SPEAKER_RADIUS}" fill="{SPEAKER_COLOR}"/>"#,
+                    center.x, center.y,
+                )?;
+            }
+        }
+    }
+    writeln!(svg, "</g>")?;
+
+    // The footer: a scale bar, followed by a legend of materials used by walls.
+    let footer_y = height - MARGIN - FOOTER_HEIGHT / 2.0;
+    let length = nice_step(MIN_SCALE_BAR_WIDTH / scale);
+    let left = MARGIN;
+    let right = MARGIN + length * scale;
+    writeln!(
+        svg,
+        r#"<g id="scale-bar" fill="none" stroke="{TEXT_COLOR}" stroke-width="{SCALE_BAR_THICKNESS}">"#
+    )?;
+    writeln!(
+        svg,
+        r#"<path d="M{left:.2},{top:.2} V{footer_y:.2} H{right:.2} V{top:.2}"/>"#,
+        top = footer_y - SCALE_BAR_TICK_SIZE,
+    )?;
+    writeln!(
+        svg,
+        r#"<text x="{:.2}" y="{:.2}" text-anchor="middle" fill="{TEXT_COLOR}" stroke="none">{}</text>"#,
+        (left + right) / 2.0,
+        footer_y - SCALE_BAR_TICK_SIZE - FONT_SIZE / 2.0,
+        escape(&space.unit.format(length)),
+    )?;
+    writeln!(svg, "</g>")?;
+
+    let mut used_materials: Vec<_> = space
+        .objects
+        .iter()
+        .filter_map(|object| match object {
+            Object::Wall(wall) => Some(wall.material),
+            _ => None,
+        })
+        .collect();
+    used_materials.sort_by_key(|material| material.0);
+    used_materials.dedup();
+    writeln!(svg, r#"<g id="legend" fill="{TEXT_COLOR}">"#)?;
+    let mut x = right + 2.0 * MARGIN;
+    for material in used_materials {
+        let name = space
+            .material(material)
+            .map_or("Unknown material", |material| material.name.as_str());
+        let color = material_color(material.0);
+        writeln!(
+            svg,
+            r#"<line x1="{x:.2}" y1="{footer_y:.2}" x2="{:.2}" y2="{footer_y:.2}" stroke="{color}" stroke-width="{WALL_THICKNESS}" stroke-linecap="round"/>"#,
+            x + FONT_SIZE,
+        )?;
+        x += FONT_SIZE + FONT_SIZE / 2.0;
+        writeln!(
+            svg,
+            r#"<text x="{x:.2}" y="{:.2}">{}</text>"#,
+            footer_y + FONT_SIZE / 3.0,
+            escape(name),
+        )?;
+        // Text can't be measured here, so estimate its width.
+        x += name.chars().count() as f64 * FONT_SIZE * 0.6 + MARGIN;
+    }
+    writeln!(svg, "</g>")?;
+
+    writeln!(svg, "</svg>")
+}
+
+/// Returns whether the path has the extension of an SVG file.
+pub fn is_svg(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .map_or(false, |extension| extension.eq_ignore_ascii_case("svg"))
+}
+
+/// Exports the space as an SVG file at the given path.
+pub fn export_file(space: &EditableSpace, path: &Path, rays: &[RecordedRay]) -> Result<(), Error> {
+    std::fs::write(path, to_svg(space, rays))?;
+    info!(?path, rays = rays.len(), "exported space");
+    Ok(())
+}
@@ -0,0 +1,451 @@
+//! ASCII DXF import.
+//!
+//! Only the `ENTITIES` section is read. `LINE`, `LWPOLYLINE`, `POLYLINE`, `ARC` and `CIRCLE`
+//! entities are supported; polyline bulges and arcs become arc-shaped walls. Blocks (`INSERT`
+//! entities) are not expanded.
+
+use druid::{Point, Vec2};
+use tracing::debug;
+
+use super::{DrawnWall, ImportError};
+use crate::widgets::data::WallShape;
+
+/// A single entity, as the list of group code/value pairs it consists of.
+struct Entity<'a> {
+    kind: &'a str,
+    line: usize,
+    groups: Vec<(i32, &'a str)>,
+}
+
+impl<'a> Entity<'a> {
+    fn value(&self, code: i32) -> Option<&'a str> {
+        self.groups
+            .iter()
+            .find(|&&(c, _)| c == code)
+            .map(|&(_, value)| value)
+    }
+
+    fn number(&self, code: i32) -> Result<f64, ImportError> {
+        let value = self.value(code).ok_or_else(|| ImportError::Dxf {
+            line: self.line,
+            message: format!("{} is missing group code {}", self.kind, code),
+        })?;
+        parse_number(value, self.line)
+    }
+
+    fn layer(&self) -> Option<&'a str> {
+        self.value(8)
+    }
+
+    fn flags(&self) -> i32 {
+        self.value(70)
+            .and_then(|value| value.trim().parse().ok())
+            .unwrap_or(0)
+    }
+}
+
+/// A vertex of a polyline. The bulge describes the arc going from this vertex to the next one.
+#[derive(Debug, Clone, Copy)]
+struct Vertex {
+    position: Point,
+    bulge: f64,
+}
+
+fn parse_number(value: &str, line: usize) -> Result<f64, ImportError> {
+    value.trim().parse().map_err(|_| ImportError::Dxf {
+        line,
+        message: format!("invalid number `{}`", value.trim()),
+    })
+}
+
+/// Reads the file as a list of group code/value pairs, along with their line numbers.
+fn groups(text: &str) -> Result<Vec<(usize, i32, &str)>, ImportError> {
+    let mut lines = text.lines().enumerate();
+    let mut groups = vec![];
+    while let Some((index, code)) = lines.next() {
+        let line = index + 1;
+        if code.trim().is_empty() {
+            continue;
+        }
+        let code = code.trim().parse().map_err(|_| ImportError::Dxf {
+            line,
+            message: format!("invalid group code `{}`", code.trim()),
+        })?;
+        let (_, value) = lines.next().ok_or_else(|| ImportError::Dxf {
+            line,
+            message: "group code without a value".into(),
+        })?;
+        groups.push((line, code, value));
+    }
+    Ok(groups)
+}
+
+/// Splits the `ENTITIES` section into entities.
+fn entities(text: &str) -> Result<Vec<Entity<'_>>, ImportError> {
+    let mut entities: Vec<Entity> = vec![];
+    let mut in_entities = false;
+    let groups = groups(text)?;
+    let mut iter = groups.iter().peekable();
+    while let Some(&(line, code, value)) = iter.next() {
+        let value = value.trim();
+        if code != 0 {
+            if in_entities {
+                if let Some(entity) = entities.last_mut() {
+                    entity.groups.push((code, value));
+                }
+            }
+            continue;
+        }
+        match value {
+            "SECTION" => {
+                in_entities =
+                    matches!(iter.peek(), Some(&&(_, 2, name)) if name.trim() == "ENTITIES");
+            }
+            "ENDSEC" => in_entities = false,
+            kind if in_entities => entities.push(Entity {
+                kind,
+                line,
+                groups: vec![],
+            }),
+            _ => (),
+        }
+    }
+    Ok(entities)
+}
+
+pub(super) fn parse(text: &str, scale: f64) -> Result<Vec<DrawnWall>, ImportError> {
+    // DXF's y axis points up, while the editor's points down.
+    let to_space = |x: f64, y: f64| Point::new(x * scale, -y * scale);
+    let vertex_to_space = |vertex: Vertex| Vertex {
+        position: to_space(vertex.position.x, vertex.position.y),
+        ..vertex
+    };
+
+    let entities = entities(text)?;
+    let mut walls = vec![];
+    // The vertices of the old-style POLYLINE currently being read, along with the polyline's
+    // entity.
+    let mut polyline: Option<(Vec<Vertex>, &Entity)> = None;
+    for entity in &entities {
+        match entity.kind {
+            "LINE" => walls.push(DrawnWall::line(
+                to_space(entity.number(10)?, entity.number(20)?),
+                to_space(entity.number(11)?, entity.number(21)?),
+                entity.layer(),
+            )),
+            "LWPOLYLINE" => {
+                let mut vertices: Vec<Vertex> = vec![];
+                for &(code, value) in &entity.groups {
+                    match code {
+                        10 => vertices.push(Vertex {
+                            position: Point::new(parse_number(value, entity.line)?, 0.0),
+                            bulge: 0.0,
+                        }),
+                        20 => {
+                            if let Some(vertex) = vertices.last_mut() {
+                                vertex.position.y = parse_number(value, entity.line)?;
+                            }
+                        }
+                        42 => {
+                            if let Some(vertex) = vertices.last_mut() {
+                                vertex.bulge = parse_number(value, entity.line)?;
+                            }
+                        }
+                        _ => (),
+                    }
+                }
+                let vertices: Vec<_> = vertices.into_iter().map(vertex_to_space).collect();
+                add_polyline(
+                    &vertices,
+                    entity.flags() & 1 != 0,
+                    entity.layer(),
+                    &mut walls,
+                );
+            }
+            "POLYLINE" => polyline = Some((vec![], entity)),
+            "VERTEX" => {
+                if let Some((vertices, _)) = &mut polyline {
+                    vertices.push(Vertex {
+                        position: to_space(entity.number(10)?, entity.number(20)?),
+                        bulge: entity
+                            .value(42)
+                            .map_or(Ok(0.0), |value| parse_number(value, entity.line))?,
+                    });
+                }
+            }
+            "SEQEND" => {
+                if let Some((vertices, polyline)) = polyline.take() {
+                    let closed = polyline.flags() & 1 != 0;
+                    add_polyline(&vertices, closed, polyline.layer(), &mut walls);
+                }
+            }
+            "ARC" | "CIRCLE" => {
+                let center = Point::new(entity.number(10)?, entity.number(20)?);
+                let radius = entity.number(40)?;
+                let (start_angle, end_angle) = if entity.kind == "ARC" {
+                    (entity.number(50)?, entity.number(51)?)
+                } else {
+                    (0.0, 360.0)
+                };
+                let vertices: Vec<_> = arc_vertices(center, radius, start_angle, end_angle)
+                    .into_iter()
+                    .map(vertex_to_space)
+                    .collect();
+                add_polyline(&vertices, false, entity.layer(), &mut walls);
+            }
+            kind => debug!(kind, line = entity.line, "skipping unsupported entity"),
+        }
+    }
+    Ok(walls)
+}
+
+/// Converts an arc going counterclockwise from `start_angle` to `end_angle` (in degrees) into
+/// polyline vertices, splitting it into pieces spanning at most half a circle.
+fn arc_vertices(center: Point, radius: f64, start_angle: f64, end_angle: f64) -> Vec<Vertex> {
+    let mut sweep = (end_angle - start_angle).rem_euclid(360.0);
+    if sweep == 0.0 {
+        sweep = 360.0;
+    }
+    let pieces = (sweep / 180.0).ceil().max(1.0) as usize;
+    let piece_sweep = sweep.to_radians() / pieces as f64;
+    let bulge = (piece_sweep / 4.0).tan();
+    (0..=pieces)
+        .map(|i| {
+            let angle = start_angle.to_radians() + piece_sweep * i as f64;
+            Vertex {
+                position: center + radius * Vec2::from_angle(angle),
+                bulge: if i < pieces { bulge } else { 0.0 },
+            }
+        })
+        .collect()
+}
+
+/// Adds walls connecting the vertices, which must already be in space coordinates.
+fn add_polyline(
+    vertices: &[Vertex],
+    closed: bool,
+    layer: Option<&str>,
+    walls: &mut Vec<DrawnWall>,
+) {
+    // Two vertices only need a closing segment if it's curved, otherwise it would be a duplicate
+    // of the first one.
+    let closing = match (vertices.first(), vertices.last()) {
+        (Some(first), Some(last)) if closed && (vertices.len() > 2 || last.bulge != 0.0) => {
+            Some((last, first))
+        }
+        _ => None,
+    };
+    let segments = vertices
+        .windows(2)
+        .map(|pair| (&pair[0], &pair[1]))
+        .chain(closing);
+    for (from, to) in segments {
+        let mut wall = DrawnWall::line(from.position, to.position, layer);
+        if from.bulge != 0.0 {
+            // The bulge is the tangent of a quarter of the arc's angle, which makes the sagitta
+            // the bulge times half the chord. Positive bulges go counterclockwise in DXF's
+            // coordinate system, which after flipping the y axis lines up with the direction of
+            // positive sagittas.
+            let half_chord = from.position.distance(to.position) * 0.5;
+            wall.shape = WallShape::Arc {
+                sagitta: from.bulge * half_chord,
+            };
+        }
+        walls.push(wall);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns a DXF file whose `ENTITIES` section consists of the given groups.
+    fn dxf(entities: &[(i32, &str)]) -> String {
+        let mut text = String::from("0\nSECTION\n2\nENTITIES\n");
+        for (code, value) in entities {
+            text += &format!("{code}\n{value}\n");
+        }
+        text + "0\nENDSEC\n0\nEOF\n"
+    }
+
+    fn parse_walls(entities: &[(i32, &str)]) -> Vec<DrawnWall> {
+        parse(&dxf(entities), 1.0).unwrap()
+    }
+
+    fn assert_close(actual: Point, expected: Point) {
+        assert!(
+            actual.distance(expected) < 1e-6,
+            "expected {expected:?}, got {actual:?}"
+        );
+    }
+
+    fn assert_ends(wall: &DrawnWall, start: (f64, f64), end: (f64, f64)) {
+        assert_close(wall.start, start.into());
+        assert_close(wall.end, end.into());
+    }
+
+    fn sagitta(wall: &DrawnWall) -> f64 {
+        match wall.shape {
+            WallShape::Arc { sagitta } => sagitta,
+            shape => panic!("expected an arc, got {shape:?}"),
+        }
+    }
+
+    fn error_line(text: &str) -> usize {
+        match groups(text) {
+            Err(ImportError::Dxf { line, .. }) => line,
+            _ => panic!("expected a DXF error"),
+        }
+    }
+
+    #[test]
+    fn groups_are_read_with_their_line_numbers() {
+        let groups = groups("  0\nSECTION\n\n2\n ENTITIES\n").unwrap();
+        assert_eq!(groups, [(1, 0, "SECTION"), (4, 2, " ENTITIES")]);
+    }
+
+    #[test]
+    fn malformed_groups_are_errors() {
+        assert_eq!(error_line("0\nSECTION\nX\nENTITIES\n"), 3);
+        assert_eq!(error_line("0\nSECTION\n2"), 3);
+    }
+
+    #[test]
+    fn lines_are_flipped_vertically() {
+        let walls = parse_walls(&[
+            (0, "LINE"),
+            (8, "Walls"),
+            (10, "1"),
+            (20, "2"),
+            (11, "3.5"),
+            (21, "-4"),
+        ]);
+        assert_eq!(walls.len(), 1);
+        assert_ends(&walls[0], (1.0, -2.0), (3.5, 4.0));
+        assert_eq!(walls[0].shape, WallShape::Line);
+        assert_eq!(walls[0].layer.as_deref(), Some("Walls"));
+    }
+
+    #[test]
+    fn entities_outside_the_entities_section_are_ignored() {
+        let text = format!(
+            "0\nSECTION\n2\nBLOCKS\n0\nLINE\n10\n0\n20\n0\n11\n1\n21\n1\n0\nENDSEC\n{}",
+            dxf(&[(0, "LINE"), (10, "0"), (20, "0"), (11, "0"), (21, "1")])
+        );
+        let walls = parse(&text, 1.0).unwrap();
+        assert_eq!(walls.len(), 1);
+        assert_ends(&walls[0], (0.0, 0.0), (0.0, -1.0));
+    }
+
+    #[test]
+    fn missing_coordinates_are_errors() {
+        let text = dxf(&[(0, "LINE"), (10, "0"), (20, "0"), (11, "1")]);
+        assert!(matches!(
+            parse(&text, 1.0),
+            Err(ImportError::Dxf { line: 5, .. })
+        ));
+    }
+
+    #[test]
+    fn closed_lightweight_polylines_get_a_closing_wall() {
+        let walls = parse_walls(&[
+            (0, "LWPOLYLINE"),
+            (70, "1"),
+            (10, "0"),
+            (20, "0"),
+            (10, "2"),
+            (20, "0"),
+            (10, "2"),
+            (20, "1"),
+        ]);
+        assert_eq!(walls.len(), 3);
+        assert_ends(&walls[0], (0.0, 0.0), (2.0, 0.0));
+        assert_ends(&walls[1], (2.0, 0.0), (2.0, -1.0));
+        assert_ends(&walls[2], (2.0, -1.0), (0.0, 0.0));
+    }
+
+    #[test]
+    fn bulges_become_sagittas() {
+        // A bulge of 1 is a half circle, so its sagitta is half the chord.
+        let walls = parse_walls(&[
+            (0, "LWPOLYLINE"),
+            (10, "0"),
+            (20, "0"),
+            (42, "1"),
+            (10, "2"),
+            (20, "0"),
+            (42, "-0.5"),
+            (10, "4"),
+            (20, "0"),
+        ]);
+        assert_eq!(walls.len(), 2);
+        assert!((sagitta(&walls[0]) - 1.0).abs() < 1e-9);
+        assert!((sagitta(&walls[1]) + 0.5).abs() < 1e-9);
+
+        let scaled = parse(
+            &dxf(&[
+                (0, "LWPOLYLINE"),
+                (10, "0"),
+                (20, "0"),
+                (42, "1"),
+                (10, "2"),
+                (20, "0"),
+            ]),
+            3.0,
+        )
+        .unwrap();
+        assert!((sagitta(&scaled[0]) - 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn old_style_polylines_collect_their_vertices() {
+        let walls = parse_walls(&[
+            (0, "POLYLINE"),
+            (8, "Glass"),
+            (70, "0"),
+            (0, "VERTEX"),
+            (10, "0"),
+            (20, "0"),
+            (0, "VERTEX"),
+            (10, "1"),
+            (20, "0"),
+            (42, "-1"),
+            (0, "VERTEX"),
+            (10, "1"),
+            (20, "2"),
+            (0, "SEQEND"),
+        ]);
+        assert_eq!(walls.len(), 2);
+        assert_ends(&walls[0], (0.0, 0.0), (1.0, 0.0));
+        assert_eq!(walls[0].shape, WallShape::Line);
+        assert_ends(&walls[1], (1.0, 0.0), (1.0, -2.0));
+        assert!((sagitta(&walls[1]) + 1.0).abs() < 1e-9);
+        assert!(walls
+            .iter()
+            .all(|wall| wall.layer.as_deref() == Some("Glass")));
+    }
+
+    #[test]
+    fn arcs_and_circles_are_split_into_half_circles_at_most() {
+        let walls = parse_walls(&[
+            (0, "ARC"),
+            (10, "0"),
+            (20, "0"),
+            (40, "1"),
+            (50, "0"),
+            (51, "90"),
+        ]);
+        assert_eq!(walls.len(), 1);
+        assert_ends(&walls[0], (1.0, 0.0), (0.0, -1.0));
+        let expected = 1.0 - std::f64::consts::FRAC_PI_4.cos();
+        assert!((sagitta(&walls[0]) - expected).abs() < 1e-9);
+
+        let walls = parse_walls(&[(0, "CIRCLE"), (10, "0"), (20, "0"), (40, "2")]);
+        assert_eq!(walls.len(), 2);
+        assert_ends(&walls[0], (2.0, 0.0), (-2.0, 0.0));
+        assert_ends(&walls[1], (-2.0, 0.0), (2.0, 0.0));
+        for wall in &walls {
+            assert!((sagitta(wall) - 2.0).abs() < 1e-9);
+        }
+    }
+}
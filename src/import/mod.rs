@@ -0,0 +1,174 @@
+//! Importing floor plans from vector drawings.
+//!
+//! Drawings are converted to walls. Everything else in them (text, fills, dimensions, etc.) is
+//! ignored.
+
+mod dxf;
+mod svg;
+
+use std::{collections::HashMap, path::Path};
+
+use druid::{im::HashSet, Point};
+use thiserror::Error;
+use tracing::info;
+
+use crate::{
+    sparse_set::Id,
+    widgets::data::{EditableSpace, MaterialIndex, Object, Wall, WallShape},
+};
+
+#[derive(Debug, Error)]
+pub enum ImportError {
+    #[error("unsupported file format; expected an .svg or .dxf file")]
+    UnsupportedFormat,
+
+    #[error("i/o error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("SVG error: {0}")]
+    Svg(#[from] roxmltree::Error),
+
+    #[error("DXF error on line {line}: {message}")]
+    Dxf { line: usize, message: String },
+
+    #[error("layer `{layer}` is mapped to material `{material}`, which does not exist")]
+    UnknownMaterial { layer: String, material: String },
+
+    #[error("expected a layer mapping of the form LAYER=MATERIAL, got `{0}`")]
+    InvalidLayerMapping(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Svg,
+    Dxf,
+}
+
+impl Format {
+    /// Determines the format of a file from its extension.
+    pub fn from_path(path: &Path) -> Option<Format> {
+        let extension = path.extension()?.to_str()?;
+        if extension.eq_ignore_ascii_case("svg") {
+            Some(Format::Svg)
+        } else if extension.eq_ignore_ascii_case("dxf") {
+            Some(Format::Dxf)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ImportOptions {
+    /// The factor all coordinates in the drawing are multiplied by. SVG coordinates are in CSS
+    /// pixels by then, with the drawing's view box already mapped onto its size.
+    pub scale: f64,
+    /// Maps names of layers in the drawing to names of materials in the space. Walls on layers
+    /// that are not mapped get the default material.
+    pub layer_materials: HashMap<String, String>,
+}
+
+impl Default for ImportOptions {
+    fn default() -> Self {
+        Self {
+            scale: 1.0,
+            layer_materials: HashMap::new(),
+        }
+    }
+}
+
+/// Parses a mapping of a layer to a material, written as `LAYER=MATERIAL`.
+pub fn parse_layer_material(mapping: &str) -> Result<(String, String), ImportError> {
+    mapping
+        .split_once('=')
+        .map(|(layer, material)| (layer.trim().to_owned(), material.trim().to_owned()))
+        .ok_or_else(|| ImportError::InvalidLayerMapping(mapping.to_owned()))
+}
+
+/// A wall read from a drawing, along with the name of the layer it was on.
+struct DrawnWall {
+    start: Point,
+    end: Point,
+    shape: WallShape,
+    layer: Option<String>,
+}
+
+impl DrawnWall {
+    fn line(start: Point, end: Point, layer: Option<&str>) -> Self {
+        Self {
+            start,
+            end,
+            shape: WallShape::Line,
+            layer: layer.map(str::to_owned),
+        }
+    }
+}
+
+/// Returns the material walls on the given layer should be made of.
+fn layer_material(
+    space: &EditableSpace,
+    options: &ImportOptions,
+    layer: Option<&str>,
+) -> Result<MaterialIndex, ImportError> {
+    let material = match layer.and_then(|layer| options.layer_materials.get(layer)) {
+        Some(material) => material,
+        None => return Ok(MaterialIndex::default()),
+    };
+    space
+        .materials
+        .iter()
+        .position(|m| m.name.eq_ignore_ascii_case(material))
+        .map(MaterialIndex)
+        .ok_or_else(|| ImportError::UnknownMaterial {
+            layer: layer.unwrap_or_default().to_owned(),
+            material: material.clone(),
+        })
+}
+
+/// Imports walls from a drawing into the space. Returns the IDs of all imported walls.
+pub fn import(
+    space: &mut EditableSpace,
+    text: &str,
+    format: Format,
+    options: &ImportOptions,
+) -> Result<HashSet<Id<Object>>, ImportError> {
+    let walls = match format {
+        Format::Svg => svg::parse(text, options.scale)?,
+        Format::Dxf => dxf::parse(text, options.scale)?,
+    };
+
+    // Resolve all materials up front, such that nothing is imported if any of them is missing.
+    let materials = walls
+        .iter()
+        .map(|wall| layer_material(space, options, wall.layer.as_deref()))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let ids: HashSet<_> = walls
+        .into_iter()
+        .zip(materials)
+        .filter(|(wall, _)| wall.start != wall.end)
+        .map(|(wall, material)| {
+            space.objects.insert(Object::Wall(Wall {
+                start: wall.start,
+                end: wall.end,
+                shape: wall.shape,
+                material,
+                back_material: None,
+            }))
+        })
+        .collect();
+    info!(count = ids.len(), ?format, "imported walls");
+    Ok(ids)
+}
+
+/// Imports walls from the drawing at the given path into the space, picking the format based on
+/// the file's extension.
+pub fn import_file(
+    space: &mut EditableSpace,
+    path: &Path,
+    options: &ImportOptions,
+) -> Result<HashSet<Id<Object>>, ImportError> {
+    let format = Format::from_path(path).ok_or(ImportError::UnsupportedFormat)?;
+    let text = std::fs::read_to_string(path)?;
+    import(space, &text, format, options)
+}
@@ -0,0 +1,691 @@
+//! SVG import.
+//!
+//! Lines, polylines, polygons, rectangles, circles, ellipses and paths are supported. Straight
+//! and Bézier segments become walls of the same shape, and circular arcs become arc-shaped
+//! walls; elliptical arcs are approximated with cubic Béziers. Groups act as layers; a group's
+//! layer name is taken from its Inkscape label, or its ID if it doesn't have one.
+//!
+//! The root element's `viewBox` is mapped onto its width and height, which puts coordinates in
+//! CSS pixels (96 per inch) before the import's scale is applied.
+
+use std::f64::consts::{PI, TAU};
+
+use druid::{
+    kurbo::{Arc, BezPath, CubicBez, Line, PathSeg, QuadBez, Rect, Shape, SvgArc},
+    Affine, Point, Vec2,
+};
+use roxmltree::{Document, Node};
+use tracing::{debug, warn};
+
+use super::{DrawnWall, ImportError};
+use crate::widgets::data::WallShape;
+
+const INKSCAPE_NAMESPACE: &str = "http://www.inkscape.org/namespaces/inkscape";
+
+/// The maximum distance between an elliptical arc and the cubic Béziers approximating it, in
+/// the drawing's units.
+const ARC_TOLERANCE: f64 = 0.01;
+
+/// Elements whose contents are not rendered directly.
+const NON_RENDERED_ELEMENTS: &[&str] = &[
+    "defs", "clipPath", "mask", "marker", "pattern", "symbol", "metadata", "title", "desc",
+];
+
+/// A piece of an element's outline.
+enum Piece {
+    Segment(PathSeg),
+    Arc(Arc),
+}
+
+pub(super) fn parse(text: &str, scale: f64) -> Result<Vec<DrawnWall>, ImportError> {
+    let document = Document::parse(text)?;
+    let mut walls = vec![];
+    // SVG's y axis points down, same as the editor's, so no flipping is needed.
+    visit(
+        document.root_element(),
+        Affine::scale(scale),
+        None,
+        &mut walls,
+    );
+    Ok(walls)
+}
+
+fn visit(node: Node, transform: Affine, layer: Option<&str>, walls: &mut Vec<DrawnWall>) {
+    let name = node.tag_name().name();
+    if NON_RENDERED_ELEMENTS.contains(&name) {
+        return;
+    }
+
+    let transform = match node.attribute("transform") {
+        Some(attribute) => match parse_transform(attribute) {
+            Some(local) => transform * local,
+            None => {
+                warn!(
+                    transform = attribute,
+                    "ignoring element with invalid transform"
+                );
+                return;
+            }
+        },
+        None => transform,
+    };
+
+    match name {
+        "svg" | "g" | "a" | "switch" => {
+            let transform = if name == "svg" {
+                transform * viewport_transform(node)
+            } else {
+                transform
+            };
+            let layer = if name == "g" {
+                node.attribute((INKSCAPE_NAMESPACE, "label"))
+                    .or_else(|| node.attribute("id"))
+                    .or(layer)
+            } else {
+                layer
+            };
+            for child in node.children().filter(|child| child.is_element()) {
+                visit(child, transform, layer, walls);
+            }
+        }
+        _ => match element_outline(node) {
+            Some(pieces) => {
+                for piece in pieces {
+                    add_piece(piece, transform, layer, walls);
+                }
+            }
+            None => debug!(element = name, "skipping unsupported element"),
+        },
+    }
+}
+
+/// Returns the transform from an `<svg>` element's user units to its parent's, which maps the
+/// element's `viewBox` onto its width and height according to its `preserveAspectRatio`.
+fn viewport_transform(node: Node) -> Affine {
+    let length = |name: &str| node.attribute(name).and_then(parse_length);
+    // The position of the outermost element has no effect.
+    let position = if node.parent_element().is_some() {
+        Vec2::new(length("x").unwrap_or(0.0), length("y").unwrap_or(0.0))
+    } else {
+        Vec2::ZERO
+    };
+    let view_box = match node.attribute("viewBox").and_then(parse_numbers).as_deref() {
+        Some(&[x, y, width, height]) if width > 0.0 && height > 0.0 => {
+            Rect::new(x, y, x + width, y + height)
+        }
+        _ => return Affine::translate(position),
+    };
+    // Relative and missing sizes can't be resolved without a viewer, so they're taken to be the
+    // size of the view box.
+    let width = length("width").unwrap_or_else(|| view_box.width());
+    let height = length("height").unwrap_or_else(|| view_box.height());
+    let scale_x = width / view_box.width();
+    let scale_y = height / view_box.height();
+    let to_origin = Affine::translate(-view_box.origin().to_vec2());
+
+    let mut words = node
+        .attribute("preserveAspectRatio")
+        .unwrap_or_default()
+        .split_whitespace()
+        .skip_while(|&word| word == "defer");
+    let align = words.next().unwrap_or("xMidYMid");
+    if align == "none" {
+        return Affine::translate(position)
+            * Affine::scale_non_uniform(scale_x, scale_y)
+            * to_origin;
+    }
+    let scale = if words.next() == Some("slice") {
+        scale_x.max(scale_y)
+    } else {
+        scale_x.min(scale_y)
+    };
+    let offset = |alignment: Option<&str>, free_space: f64| match alignment {
+        Some("Min") => 0.0,
+        Some("Max") => free_space,
+        _ => free_space / 2.0,
+    };
+    let offset = Vec2::new(
+        offset(align.get(1..4), width - view_box.width() * scale),
+        offset(align.get(5..8), height - view_box.height() * scale),
+    );
+    Affine::translate(position + offset) * Affine::scale(scale) * to_origin
+}
+
+/// Returns the outline of a shape element.
+fn element_outline(node: Node) -> Option<Vec<Piece>> {
+    let number = |name: &str| node.attribute(name).and_then(parse_length);
+    let number_or_zero = |name: &str| number(name).unwrap_or(0.0);
+    let ellipse = |radii: Vec2| {
+        let center = Point::new(number_or_zero("cx"), number_or_zero("cy"));
+        vec![Piece::Arc(Arc {
+            center,
+            radii,
+            start_angle: 0.0,
+            sweep_angle: TAU,
+            x_rotation: 0.0,
+        })]
+    };
+    match node.tag_name().name() {
+        "line" => {
+            let mut path = BezPath::new();
+            path.move_to((number_or_zero("x1"), number_or_zero("y1")));
+            path.line_to((number_or_zero("x2"), number_or_zero("y2")));
+            Some(path_pieces(&path))
+        }
+        "polyline" | "polygon" => {
+            let points = parse_points(node.attribute("points")?);
+            let mut path = BezPath::new();
+            let (first, rest) = points.split_first()?;
+            path.move_to(*first);
+            for &point in rest {
+                path.line_to(point);
+            }
+            if node.tag_name().name() == "polygon" {
+                path.close_path();
+            }
+            Some(path_pieces(&path))
+        }
+        "rect" => {
+            let origin = Point::new(number_or_zero("x"), number_or_zero("y"));
+            let rect = Rect::from_origin_size(origin, (number("width")?, number("height")?));
+            Some(rect.path_segments(0.0).map(Piece::Segment).collect())
+        }
+        "circle" => {
+            let radius = number("r")?;
+            Some(ellipse(Vec2::new(radius, radius)))
+        }
+        "ellipse" => Some(ellipse(Vec2::new(number("rx")?, number("ry")?))),
+        "path" => {
+            let data = node.attribute("d")?;
+            let pieces = parse_path_data(data);
+            if pieces.is_none() {
+                warn!(data, "ignoring path with invalid data");
+            }
+            pieces
+        }
+        _ => None,
+    }
+}
+
+fn path_pieces(path: &BezPath) -> Vec<Piece> {
+    path.segments().map(Piece::Segment).collect()
+}
+
+/// Transforms a piece of an outline and adds walls for it.
+fn add_piece(piece: Piece, transform: Affine, layer: Option<&str>, walls: &mut Vec<DrawnWall>) {
+    match piece {
+        Piece::Segment(segment) => walls.push(segment_wall(transform * segment, layer)),
+        Piece::Arc(arc) if is_similarity(transform) && is_circular(&arc) => {
+            // Arc-shaped walls can't span more than half a circle, so longer arcs are split.
+            let pieces = (arc.sweep_angle.abs() / PI).ceil().max(1.0);
+            let piece_sweep = arc.sweep_angle / pieces;
+            let point = |angle: f64| {
+                transform * (arc.center + arc.radii.x * Vec2::from_angle(angle + arc.x_rotation))
+            };
+            for i in 0..pieces as usize {
+                let start_angle = arc.start_angle + piece_sweep * i as f64;
+                let start = point(start_angle);
+                let end = point(start_angle + piece_sweep);
+                let apex = point(start_angle + piece_sweep / 2.0);
+                let direction = (end - start).normalize();
+                let normal = Vec2::new(-direction.y, direction.x);
+                let mut wall = DrawnWall::line(start, end, layer);
+                wall.shape = WallShape::Arc {
+                    sagitta: (apex - start.midpoint(end)).dot(normal),
+                };
+                walls.push(wall);
+            }
+        }
+        Piece::Arc(arc) => {
+            for segment in arc.path_segments(ARC_TOLERANCE) {
+                walls.push(segment_wall(transform * segment, layer));
+            }
+        }
+    }
+}
+
+fn segment_wall(segment: PathSeg, layer: Option<&str>) -> DrawnWall {
+    match segment {
+        PathSeg::Line(line) => DrawnWall::line(line.p0, line.p1, layer),
+        PathSeg::Quad(quad) => {
+            let mut wall = DrawnWall::line(quad.p0, quad.p2, layer);
+            wall.shape = WallShape::QuadraticBezier { control: quad.p1 };
+            wall
+        }
+        PathSeg::Cubic(cubic) => {
+            let mut wall = DrawnWall::line(cubic.p0, cubic.p3, layer);
+            wall.shape = WallShape::CubicBezier {
+                control_start: cubic.p1,
+                control_end: cubic.p2,
+            };
+            wall
+        }
+    }
+}
+
+/// Returns whether the transform keeps circles circular, ie. whether it only consists of
+/// uniform scaling, rotation, reflection and translation.
+fn is_similarity(transform: Affine) -> bool {
+    let [a, b, c, d, _, _] = transform.as_coeffs();
+    let scale = a * a + b * b;
+    let tolerance = scale * 1e-9;
+    (a * c + b * d).abs() <= tolerance && (c * c + d * d - scale).abs() <= tolerance
+}
+
+fn is_circular(arc: &Arc) -> bool {
+    (arc.radii.x - arc.radii.y).abs() <= arc.radii.x.max(arc.radii.y) * 1e-9
+}
+
+/// Parses the value of a path's `d` attribute. Unlike [`BezPath::from_svg`], this keeps arcs as
+/// arcs instead of approximating them with Béziers.
+fn parse_path_data(data: &str) -> Option<Vec<Piece>> {
+    let mut lexer = PathLexer { rest: data };
+    let mut pieces = vec![];
+    let mut current = Point::ZERO;
+    let mut subpath_start = Point::ZERO;
+    // The command which is repeated when coordinates follow without a command letter.
+    let mut repeated = None;
+    // The last control point of the previous segment, for reflecting in smooth curves.
+    let mut cubic_control = None;
+    let mut quad_control = None;
+
+    loop {
+        let command = match lexer.command() {
+            Some(command) => command,
+            None if lexer.rest.is_empty() => break,
+            None => repeated?,
+        };
+        let offset = if command.is_ascii_lowercase() {
+            current.to_vec2()
+        } else {
+            Vec2::ZERO
+        };
+        let reflect = |control: Option<Point>| match control {
+            Some(control) => current + (current - control),
+            None => current,
+        };
+        repeated = Some(command);
+        let mut next_cubic_control = None;
+        let mut next_quad_control = None;
+        match command.to_ascii_uppercase() {
+            'M' => {
+                current = lexer.point()? + offset;
+                subpath_start = current;
+                // Coordinates following a move are lines.
+                repeated = Some(if command == 'm' { 'l' } else { 'L' });
+            }
+            'L' => {
+                let end = lexer.point()? + offset;
+                pieces.push(Piece::Segment(PathSeg::Line(Line::new(current, end))));
+                current = end;
+            }
+            'H' => {
+                let end = Point::new(lexer.number()? + offset.x, current.y);
+                pieces.push(Piece::Segment(PathSeg::Line(Line::new(current, end))));
+                current = end;
+            }
+            'V' => {
+                let end = Point::new(current.x, lexer.number()? + offset.y);
+                pieces.push(Piece::Segment(PathSeg::Line(Line::new(current, end))));
+                current = end;
+            }
+            'C' | 'S' => {
+                let control_start = if command.eq_ignore_ascii_case(&'S') {
+                    reflect(cubic_control)
+                } else {
+                    lexer.point()? + offset
+                };
+                let control_end = lexer.point()? + offset;
+                let end = lexer.point()? + offset;
+                pieces.push(Piece::Segment(PathSeg::Cubic(CubicBez::new(
+                    current,
+                    control_start,
+                    control_end,
+                    end,
+                ))));
+                next_cubic_control = Some(control_end);
+                current = end;
+            }
+            'Q' | 'T' => {
+                let control = if command.eq_ignore_ascii_case(&'T') {
+                    reflect(quad_control)
+                } else {
+                    lexer.point()? + offset
+                };
+                let end = lexer.point()? + offset;
+                pieces.push(Piece::Segment(PathSeg::Quad(QuadBez::new(
+                    current, control, end,
+                ))));
+                next_quad_control = Some(control);
+                current = end;
+            }
+            'A' => {
+                let radii = lexer.point()?.to_vec2();
+                let x_rotation = lexer.number()?.to_radians();
+                let large_arc = lexer.flag()?;
+                let sweep = lexer.flag()?;
+                let end = lexer.point()? + offset;
+                let arc = SvgArc {
+                    from: current,
+                    to: end,
+                    radii,
+                    x_rotation,
+                    large_arc,
+                    sweep,
+                };
+                pieces.push(match Arc::from_svg_arc(&arc) {
+                    Some(arc) => Piece::Arc(arc),
+                    None => Piece::Segment(PathSeg::Line(Line::new(current, end))),
+                });
+                current = end;
+            }
+            'Z' => {
+                if current != subpath_start {
+                    pieces.push(Piece::Segment(PathSeg::Line(Line::new(
+                        current,
+                        subpath_start,
+                    ))));
+                }
+                current = subpath_start;
+                repeated = None;
+            }
+            _ => return None,
+        }
+        cubic_control = next_cubic_control;
+        quad_control = next_quad_control;
+    }
+    Some(pieces)
+}
+
+/// Splits path data into command letters, numbers and flags.
+struct PathLexer<'a> {
+    rest: &'a str,
+}
+
+impl<'a> PathLexer<'a> {
+    fn skip_separators(&mut self) {
+        self.rest = self
+            .rest
+            .trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+    }
+
+    /// Reads a command letter, if one comes next.
+    fn command(&mut self) -> Option<char> {
+        self.skip_separators();
+        let command = self.rest.chars().next().filter(char::is_ascii_alphabetic)?;
+        self.rest = &self.rest[1..];
+        Some(command)
+    }
+
+    fn number(&mut self) -> Option<f64> {
+        self.skip_separators();
+        let bytes = self.rest.as_bytes();
+        let skip_sign = |index: usize| match bytes.get(index) {
+            Some(b'+' | b'-') => index + 1,
+            _ => index,
+        };
+        let skip_digits = |mut index: usize| {
+            while bytes.get(index).is_some_and(u8::is_ascii_digit) {
+                index += 1;
+            }
+            index
+        };
+        let mut end = skip_digits(skip_sign(0));
+        if bytes.get(end) == Some(&b'.') {
+            end = skip_digits(end + 1);
+        }
+        if matches!(bytes.get(end), Some(b'e' | b'E')) {
+            let exponent_start = skip_sign(end + 1);
+            let exponent_end = skip_digits(exponent_start);
+            if exponent_end > exponent_start {
+                end = exponent_end;
+            }
+        }
+        let number = self.rest[..end].parse().ok()?;
+        self.rest = &self.rest[end..];
+        Some(number)
+    }
+
+    fn point(&mut self) -> Option<Point> {
+        Some(Point::new(self.number()?, self.number()?))
+    }
+
+    /// Reads an arc flag. Flags are a single digit, so they don't need to be separated from
+    /// what follows them.
+    fn flag(&mut self) -> Option<bool> {
+        self.skip_separators();
+        let flag = match self.rest.as_bytes().first()? {
+            b'0' => false,
+            b'1' => true,
+            _ => return None,
+        };
+        self.rest = &self.rest[1..];
+        Some(flag)
+    }
+}
+
+/// Parses a length, converting absolute units to pixels. Relative units (percentages, `em`s,
+/// etc.) are not supported.
+fn parse_length(text: &str) -> Option<f64> {
+    let text = text.trim();
+    let number_length = text
+        .trim_end_matches(|c: char| c.is_ascii_alphabetic() || c == '%')
+        .len();
+    let (number, unit) = text.split_at(number_length);
+    let pixels_per_unit = match unit {
+        "" | "px" => 1.0,
+        "in" => 96.0,
+        "cm" => 96.0 / 2.54,
+        "mm" => 96.0 / 25.4,
+        "Q" => 96.0 / 101.6,
+        "pt" => 96.0 / 72.0,
+        "pc" => 16.0,
+        _ => return None,
+    };
+    Some(number.trim().parse::<f64>().ok()? * pixels_per_unit)
+}
+
+/// Splits a list of numbers separated by whitespace and/or commas.
+fn parse_numbers(text: &str) -> Option<Vec<f64>> {
+    text.split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|number| !number.is_empty())
+        .map(|number| number.parse().ok())
+        .collect()
+}
+
+fn parse_points(text: &str) -> Vec<Point> {
+    parse_numbers(text)
+        .unwrap_or_default()
+        .chunks_exact(2)
+        .map(|pair| Point::new(pair[0], pair[1]))
+        .collect()
+}
+
+/// Parses the value of a `transform` attribute.
+fn parse_transform(text: &str) -> Option<Affine> {
+    let mut transform = Affine::IDENTITY;
+    let mut rest = text.trim();
+    while !rest.is_empty() {
+        let open = rest.find('(')?;
+        let close = rest.find(')')?;
+        let function = rest[..open].trim();
+        let arguments = parse_numbers(&rest[open + 1..close])?;
+        let local = match (function, arguments.as_slice()) {
+            ("matrix", &[a, b, c, d, e, f]) => Affine::new([a, b, c, d, e, f]),
+            ("translate", &[x]) => Affine::translate((x, 0.0)),
+            ("translate", &[x, y]) => Affine::translate((x, y)),
+            ("scale", &[s]) => Affine::scale(s),
+            ("scale", &[x, y]) => Affine::scale_non_uniform(x, y),
+            ("rotate", &[angle]) => Affine::rotate(angle.to_radians()),
+            ("rotate", &[angle, x, y]) => {
+                Affine::translate((x, y))
+                    * Affine::rotate(angle.to_radians())
+                    * Affine::translate((-x, -y))
+            }
+            ("skewX", &[angle]) => {
+                let skew = angle.to_radians().tan();
+                Affine::new([1.0, 0.0, skew, 1.0, 0.0, 0.0])
+            }
+            ("skewY", &[angle]) => {
+                let skew = angle.to_radians().tan();
+                Affine::new([1.0, skew, 0.0, 1.0, 0.0, 0.0])
+            }
+            _ => return None,
+        };
+        transform *= local;
+        rest = rest[close + 1..].trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+    }
+    Some(transform)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_walls(body: &str) -> Vec<DrawnWall> {
+        let text = format!(r#"<svg xmlns="http://www.w3.org/2000/svg">{body}</svg>"#);
+        parse(&text, 1.0).unwrap()
+    }
+
+    fn assert_close(actual: Point, expected: Point) {
+        assert!(
+            actual.distance(expected) < 1e-6,
+            "expected {expected:?}, got {actual:?}"
+        );
+    }
+
+    fn assert_ends(wall: &DrawnWall, start: (f64, f64), end: (f64, f64)) {
+        assert_close(wall.start, start.into());
+        assert_close(wall.end, end.into());
+    }
+
+    fn sagitta(wall: &DrawnWall) -> f64 {
+        match wall.shape {
+            WallShape::Arc { sagitta } => sagitta,
+            shape => panic!("expected an arc, got {shape:?}"),
+        }
+    }
+
+    #[test]
+    fn lexer_reads_compact_numbers() {
+        let mut lexer = PathLexer {
+            rest: "M1-2.5.5,1e1 L+3E-1",
+        };
+        assert_eq!(lexer.command(), Some('M'));
+        assert_eq!(lexer.number(), Some(1.0));
+        assert_eq!(lexer.number(), Some(-2.5));
+        assert_eq!(lexer.number(), Some(0.5));
+        assert_eq!(lexer.number(), Some(10.0));
+        assert_eq!(lexer.command(), Some('L'));
+        assert_eq!(lexer.number(), Some(0.3));
+        assert_eq!(lexer.number(), None);
+        assert!(lexer.rest.is_empty());
+    }
+
+    #[test]
+    fn lexer_reads_unseparated_flags() {
+        let mut lexer = PathLexer { rest: "011.5" };
+        assert_eq!(lexer.flag(), Some(false));
+        assert_eq!(lexer.flag(), Some(true));
+        assert_eq!(lexer.number(), Some(1.5));
+        assert_eq!(PathLexer { rest: "2" }.flag(), None);
+    }
+
+    #[test]
+    fn line_becomes_wall() {
+        let walls = parse_walls(r#"<line x1="1" y1="2" x2="3" y2="4"/>"#);
+        assert_eq!(walls.len(), 1);
+        assert_ends(&walls[0], (1.0, 2.0), (3.0, 4.0));
+        assert_eq!(walls[0].shape, WallShape::Line);
+        assert_eq!(walls[0].layer, None);
+    }
+
+    #[test]
+    fn polylines_stay_open_and_polygons_are_closed() {
+        let walls = parse_walls(r#"<polyline points="0,0 10,0 10 5"/>"#);
+        assert_eq!(walls.len(), 2);
+        assert_ends(&walls[0], (0.0, 0.0), (10.0, 0.0));
+        assert_ends(&walls[1], (10.0, 0.0), (10.0, 5.0));
+
+        let walls = parse_walls(r#"<polygon points="0,0 10,0 10 5"/>"#);
+        assert_eq!(walls.len(), 3);
+        assert_ends(&walls[2], (10.0, 5.0), (0.0, 0.0));
+    }
+
+    #[test]
+    fn relative_path_commands_follow_the_current_point() {
+        let walls = parse_walls(r#"<path d="m10 10 10 0 v10 h-10z"/>"#);
+        assert_eq!(walls.len(), 4);
+        assert_ends(&walls[0], (10.0, 10.0), (20.0, 10.0));
+        assert_ends(&walls[1], (20.0, 10.0), (20.0, 20.0));
+        assert_ends(&walls[2], (20.0, 20.0), (10.0, 20.0));
+        assert_ends(&walls[3], (10.0, 20.0), (10.0, 10.0));
+    }
+
+    #[test]
+    fn circular_path_arcs_become_arc_walls() {
+        // Both arcs go from (0, 0) to (10, 0) over a half circle; the sweep flag picks the side.
+        let walls = parse_walls(r#"<path d="M0 0 A5 5 0 0 1 10 0 M0 0 a5 5 0 0 0 10 0"/>"#);
+        assert_eq!(walls.len(), 2);
+        assert_ends(&walls[0], (0.0, 0.0), (10.0, 0.0));
+        assert_ends(&walls[1], (0.0, 0.0), (10.0, 0.0));
+        assert!((sagitta(&walls[0]) + 5.0).abs() < 1e-6);
+        assert!((sagitta(&walls[1]) - 5.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn full_circles_are_split_into_half_circles() {
+        let walls = parse_walls(r#"<circle cx="5" cy="5" r="2"/>"#);
+        assert_eq!(walls.len(), 2);
+        assert_ends(&walls[0], (7.0, 5.0), (3.0, 5.0));
+        assert_ends(&walls[1], (3.0, 5.0), (7.0, 5.0));
+        for wall in &walls {
+            assert!((sagitta(wall).abs() - 2.0).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn stretched_arcs_are_flattened_to_cubics() {
+        let walls = parse_walls(r#"<g transform="scale(2, 1)"><circle r="1"/></g>"#);
+        assert!(walls.len() > 2);
+        assert_close(walls[0].start, Point::new(2.0, 0.0));
+        assert_close(walls[walls.len() - 1].end, Point::new(2.0, 0.0));
+        for pair in walls.windows(2) {
+            assert_close(pair[0].end, pair[1].start);
+        }
+        for wall in &walls {
+            assert!(matches!(wall.shape, WallShape::CubicBezier { .. }));
+            // The ends of every piece lie on the stretched circle.
+            let on_ellipse = |point: Point| (point.x / 2.0).hypot(point.y);
+            assert!((on_ellipse(wall.start) - 1.0).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn view_box_is_mapped_onto_the_size() {
+        // The view box is twice as wide as it is tall, so it's scaled by 5mm per unit to fit the
+        // height and centered horizontally.
+        let text = r#"<svg xmlns="http://www.w3.org/2000/svg" width="100mm" height="50mm"
+            viewBox="0 0 10 10"><line x1="0" y1="0" x2="10" y2="10"/></svg>"#;
+        let walls = parse(text, 1.0).unwrap();
+        let mm = 96.0 / 25.4;
+        assert_ends(&walls[0], (25.0 * mm, 0.0), (75.0 * mm, 50.0 * mm));
+
+        let stretched = text.replace("viewBox", r#"preserveAspectRatio="none" viewBox"#);
+        let walls = parse(&stretched, 2.0).unwrap();
+        assert_ends(&walls[0], (0.0, 0.0), (200.0 * mm, 100.0 * mm));
+    }
+
+    #[test]
+    fn groups_are_layers() {
+        let walls = parse_walls(
+            r#"<g id="outer" xmlns:inkscape="http://www.inkscape.org/namespaces/inkscape">
+                <line x2="1"/>
+                <g id="layer2" inkscape:label="Concrete"><line x2="1"/></g>
+            </g>
+            <defs><line x2="1"/></defs>"#,
+        );
+        let layers: Vec<_> = walls.iter().map(|wall| wall.layer.as_deref()).collect();
+        assert_eq!(layers, [Some("outer"), Some("Concrete")]);
+    }
+}
@@ -1,18 +1,29 @@
 #![windows_subsystem = "windows"]
 
-use std::{path::PathBuf, sync::Arc, thread};
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    thread,
+};
 
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use commands::commander;
 use druid::{
-    widget::{Flex, Padding, ZStack},
-    AppLauncher, Data, Lens, UnitPoint, Widget, WidgetExt, WindowDesc,
+    im::Vector,
+    widget::{Controller, CrossAxisAlignment, Either, Flex, Padding, SizedBox, ZStack},
+    AppLauncher, Data, Env, Event, EventCtx, FileDialogOptions, FileSpec, Lens, Target, UnitPoint,
+    Widget, WidgetExt, WindowDesc,
 };
+use import::ImportOptions;
 use project::Project;
 use rendering::RenderSettings;
-use tracing::metadata::LevelFilter;
+use tracing::{error, metadata::LevelFilter, warn};
 use tracing_subscriber::{prelude::*, EnvFilter};
-use widgets::{Button, SpaceEditor};
+use widgets::{
+    background::IMAGE_EXTENSIONS, background_panel, data::EditableSpace, export_panel,
+    import_panel, inspector, problems_dialog, render_settings_panel, tool::Tool, Button,
+    ExportSettings, ImportSettings, SpaceEditor,
+};
 
 use crate::error::Error;
 
@@ -22,6 +33,8 @@ mod style;
 mod commands;
 
 mod error;
+mod export;
+mod import;
 mod math;
 mod project;
 mod rendering;
@@ -31,29 +44,406 @@ mod widgets;
 #[derive(Clone, Data, Lens)]
 struct RootData {
     project: Project,
+    /// Problems found while loading the project, or while importing and exporting files, shown
+    /// to the user until they dismiss them.
+    problems: Vector<String>,
+    /// The options floor plans are imported with.
+    import: ImportSettings,
+    /// Whether the import panel is shown.
+    import_open: bool,
+    /// The options the space is exported with.
+    export: ExportSettings,
+    /// Whether the export panel is shown.
+    export_open: bool,
+    /// Whether the render settings panel is shown.
+    render_settings_open: bool,
+}
+
+fn tool_button(label: &str, tool: Tool) -> impl Widget<RootData> {
+    Button::new(label)
+        .checked(move |data: &RootData| data.project.space_editor.tool == tool)
+        .on_click(move |_ctx, data: &mut RootData, _env| {
+            data.project.space_editor.tool = tool;
+        })
+}
+
+fn toolbar() -> impl Widget<RootData> {
+    Flex::row()
+        .with_child(tool_button("Cursor", Tool::Cursor))
+        .with_default_spacer()
+        .with_child(tool_button("Wall", Tool::Wall))
+        .with_default_spacer()
+        .with_child(tool_button("Polyline", Tool::Polyline))
+        .with_default_spacer()
+        .with_child(tool_button("Rectangle", Tool::Rectangle))
+        .with_default_spacer()
+        .with_child(tool_button("Polygon", Tool::Polygon))
+        .with_default_spacer()
+        .with_child(tool_button("Arc", Tool::Arc))
+        .with_default_spacer()
+        .with_child(tool_button("Curve", Tool::Bezier))
+        .with_default_spacer()
+        .with_child(tool_button("Microphone", Tool::Microphone))
+        .with_default_spacer()
+        .with_child(tool_button("Speaker", Tool::Speaker))
+        .with_default_spacer()
+        .with_child(tool_button("Ruler", Tool::Ruler))
+        .with_default_spacer()
+        .with_child(
+            Button::new("Snap")
+                .checked(|data: &RootData| data.project.space_editor.snapping.enabled)
+                .on_click(|_ctx, data: &mut RootData, _env| {
+                    let snapping = &mut data.project.space_editor.snapping;
+                    snapping.enabled = !snapping.enabled;
+                }),
+        )
 }
 
 fn root() -> impl Widget<RootData> {
-    let render_button = Button::new("Render").on_click(|_ctx, data: &mut RootData, _env| {
+    let render_button = Button::new("Render").on_click(|ctx, data: &mut RootData, _env| {
         let editable_space = Arc::clone(&data.project.space_editor.space);
-        thread::spawn(move || rendering::render(editable_space, &RenderSettings::default()));
+        let settings = data.project.render_settings.clone();
+        let sink = ctx.get_external_handle();
+        thread::spawn(move || {
+            let leaks = rendering::render(editable_space, &settings);
+            if let Err(error) = sink.submit_command(commands::SHOW_LEAKS, leaks, Target::Auto) {
+                error!(%error, "cannot show leaks found while rendering");
+            }
+        });
     });
 
     let space_editor = SpaceEditor::new()
         .lens(Project::space_editor)
         .lens(RootData::project);
-    let bottom_right = Flex::row().with_child(render_button);
+    // Changing the unit does not rescale the space; rather, it changes how its coordinates are
+    // interpreted.
+    let unit_button = Button::new(|data: &RootData, _env: &Env| {
+        format!("Unit: {}", data.project.space_editor.space.unit.symbol())
+    })
+    .on_click(|_ctx, data: &mut RootData, _env| {
+        let space = data.project.space_editor.edit_space();
+        space.unit = space.unit.next();
+    });
+    let import_button = Button::new("Import…")
+        .checked(|data: &RootData| data.import_open)
+        .on_click(|_ctx, data: &mut RootData, _env| {
+            data.import_open = !data.import_open;
+        });
+    let background_button =
+        Button::new("Background…").on_click(|ctx, _data: &mut RootData, _env| {
+            let images = FileSpec::new("Images", IMAGE_EXTENSIONS);
+            let options = FileDialogOptions::new()
+                .allowed_types(vec![images])
+                .title("Open background image");
+            ctx.submit_command(druid::commands::SHOW_OPEN_PANEL.with(options));
+        });
+    let export_button = Button::new("Export…")
+        .checked(|data: &RootData| data.export_open)
+        .on_click(|_ctx, data: &mut RootData, _env| {
+            data.export_open = !data.export_open;
+        });
+    let settings_button = Button::new("Settings")
+        .checked(|data: &RootData| data.render_settings_open)
+        .on_click(|_ctx, data: &mut RootData, _env| {
+            data.render_settings_open = !data.render_settings_open;
+        });
+    let buttons = Flex::row()
+        .with_child(import_button)
+        .with_default_spacer()
+        .with_child(background_button)
+        .with_default_spacer()
+        .with_child(export_button)
+        .with_default_spacer()
+        .with_child(unit_button)
+        .with_default_spacer()
+        .with_child(settings_button)
+        .with_default_spacer()
+        .with_child(render_button);
+    let bottom_right = Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::End)
+        .with_child(Either::new(
+            |data: &RootData, _env| data.import_open,
+            import_panel().lens(RootData::import),
+            SizedBox::empty(),
+        ))
+        .with_default_spacer()
+        .with_child(Either::new(
+            |data: &RootData, _env| data.export_open,
+            export_panel().lens(RootData::export),
+            SizedBox::empty(),
+        ))
+        .with_default_spacer()
+        .with_child(
+            background_panel()
+                .lens(Project::space_editor)
+                .lens(RootData::project),
+        )
+        .with_default_spacer()
+        .with_child(Either::new(
+            |data: &RootData, _env| data.render_settings_open,
+            render_settings_panel()
+                .lens(Project::render_settings)
+                .lens(RootData::project),
+            SizedBox::empty(),
+        ))
+        .with_default_spacer()
+        .with_child(buttons);
+
+    let stack = ZStack::new(space_editor)
+        .with_aligned_child(
+            Padding::new(style::WINDOW_PADDING, toolbar()),
+            UnitPoint::TOP_LEFT,
+        )
+        .with_aligned_child(
+            Padding::new(
+                style::WINDOW_PADDING,
+                inspector()
+                    .lens(Project::space_editor)
+                    .lens(RootData::project),
+            ),
+            UnitPoint::TOP_RIGHT,
+        )
+        .with_aligned_child(
+            Padding::new(style::WINDOW_PADDING, bottom_right),
+            UnitPoint::BOTTOM_RIGHT,
+        )
+        .with_centered_child(problems_dialog().lens(RootData::problems));
+    commander(
+        stack
+            .controller(FloorPlanImporter)
+            .controller(SpaceExporter),
+    )
+}
 
-    let stack = ZStack::new(space_editor).with_aligned_child(
-        Padding::new(style::WINDOW_PADDING, bottom_right),
-        UnitPoint::BOTTOM_RIGHT,
-    );
-    commander(stack)
+/// Imports floor plans picked through the import panel into the space, with the panel's options.
+struct FloorPlanImporter;
+
+impl FloorPlanImporter {
+    fn import(data: &mut RootData, path: &Path) {
+        let result = data
+            .import
+            .to_options()
+            .and_then(|options| data.project.space_editor.import(path, &options));
+        match result {
+            Ok(()) => data.import_open = false,
+            Err(error) => {
+                error!(%error, ?path, "cannot import floor plan");
+                data.problems
+                    .push_back(format!("{}: {error}", path.display()));
+            }
+        }
+    }
+}
+
+impl<W> Controller<RootData, W> for FloorPlanImporter
+where
+    W: Widget<RootData>,
+{
+    fn event(
+        &mut self,
+        child: &mut W,
+        ctx: &mut EventCtx,
+        event: &Event,
+        data: &mut RootData,
+        env: &Env,
+    ) {
+        // Other kinds of files are left for whoever asked for them.
+        if let Event::Command(command) = event {
+            if let Some(file) = command.get(druid::commands::OPEN_FILE) {
+                if import::Format::from_path(file.path()).is_some() {
+                    Self::import(data, file.path());
+                    ctx.set_handled();
+                    return;
+                }
+            }
+        }
+        child.event(ctx, event, data, env);
+    }
+}
+
+/// Exports the space to drawings picked through the export panel, with the rays asked for drawn
+/// over it.
+struct SpaceExporter;
+
+impl SpaceExporter {
+    fn export(data: &mut RootData, path: &Path) {
+        let space = &data.project.space_editor.space;
+        let settings = RenderSettings {
+            max_bounces: data.export.bounces,
+            ..data.project.render_settings.clone()
+        };
+        let rays = rendering::trace_rays(space, &settings, data.export.rays);
+        match export::export_file(space, path, &rays) {
+            Ok(()) => data.export_open = false,
+            Err(error) => {
+                error!(%error, ?path, "cannot export space");
+                data.problems
+                    .push_back(format!("{}: {error}", path.display()));
+            }
+        }
+    }
+}
+
+impl<W> Controller<RootData, W> for SpaceExporter
+where
+    W: Widget<RootData>,
+{
+    fn event(
+        &mut self,
+        child: &mut W,
+        ctx: &mut EventCtx,
+        event: &Event,
+        data: &mut RootData,
+        env: &Env,
+    ) {
+        if let Event::Command(command) = event {
+            if let Some(file) = command.get(druid::commands::SAVE_FILE_AS) {
+                if export::is_svg(file.path()) {
+                    Self::export(data, file.path());
+                    ctx.set_handled();
+                    return;
+                }
+            }
+        }
+        child.event(ctx, event, data, env);
+    }
 }
 
 #[derive(Parser)]
 struct Args {
     space_file: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Imports walls from an SVG or DXF floor plan into a new project.
+    Import {
+        /// The floor plan to import.
+        input: PathBuf,
+        /// Where to write the project. If omitted, the project is written to standard output.
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// The factor all coordinates in the floor plan are multiplied by.
+        #[arg(long, default_value_t = 1.0)]
+        scale: f64,
+        /// Makes walls on the given layer use the given material. Can be repeated.
+        #[arg(long = "material", value_name = "LAYER=MATERIAL")]
+        #[arg(value_parser = import::parse_layer_material)]
+        layer_materials: Vec<(String, String)>,
+    },
+    /// Rewrites a project in the current format version.
+    Upgrade {
+        /// The project to upgrade.
+        input: PathBuf,
+        /// Where to write the project. If omitted, the project is written to standard output.
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Leaves out the editor's view state (pan, zoom, active tool, etc.), keeping only the
+        /// acoustic scene.
+        #[arg(long)]
+        strip_view_state: bool,
+    },
+    /// Exports a project's space as an SVG drawing.
+    Export {
+        /// The project to export.
+        input: PathBuf,
+        /// Where to write the drawing. If omitted, the drawing is written to standard output.
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// The number of rays to trace from the first microphone and draw over the space.
+        #[arg(long, default_value_t = 0)]
+        rays: usize,
+        /// The maximum number of times each drawn ray bounces off of walls.
+        #[arg(long, default_value_t = 4)]
+        bounces: usize,
+    },
+}
+
+fn import_floor_plan(
+    input: &Path,
+    output: Option<&Path>,
+    options: &ImportOptions,
+) -> Result<(), Error> {
+    let mut space = EditableSpace::new();
+    import::import_file(&mut space, input, options)?;
+
+    let mut project = Project::new();
+    project.space_editor.space = Arc::new(space);
+    // A freshly imported project has nothing worth saving in its view state.
+    let json = project.to_json(false)?;
+    match output {
+        Some(path) => std::fs::write(path, json)?,
+        None => println!("{json}"),
+    }
+    Ok(())
+}
+
+/// Loads a project for a command, logging any problems found in it.
+fn load_project(path: &Path) -> Result<Project, Error> {
+    let (project, problems) = Project::from_json(&std::fs::read_to_string(path)?)?;
+    for problem in problems {
+        warn!(%problem, "problem in project");
+    }
+    Ok(project)
+}
+
+/// Loads the project to open in the editor. Failing to load the project doesn't prevent the
+/// editor from opening; an empty project is opened instead, and the error is shown as a problem.
+fn open_project(path: &Path) -> (Project, Vector<String>) {
+    let result = std::fs::read_to_string(path)
+        .map_err(Error::from)
+        .and_then(|json| Ok(Project::from_json(&json)?));
+    match result {
+        Ok((project, problems)) => {
+            for problem in &problems {
+                warn!(%problem, "problem in project");
+            }
+            (project, problems.iter().map(ToString::to_string).collect())
+        }
+        Err(error) => {
+            error!(%error, ?path, "cannot open project");
+            let message = format!("{}: {error}", path.display());
+            (Project::new(), Vector::unit(message))
+        }
+    }
+}
+
+fn upgrade_project(
+    input: &Path,
+    output: Option<&Path>,
+    strip_view_state: bool,
+) -> Result<(), Error> {
+    let project = load_project(input)?;
+    let json = project.to_json(!strip_view_state)?;
+    match output {
+        Some(path) => std::fs::write(path, json)?,
+        None => println!("{json}"),
+    }
+    Ok(())
+}
+
+fn export_drawing(
+    input: &Path,
+    output: Option<&Path>,
+    rays: usize,
+    bounces: usize,
+) -> Result<(), Error> {
+    let project = load_project(input)?;
+    let space = &project.space_editor.space;
+
+    let settings = RenderSettings {
+        max_bounces: bounces,
+        ..project.render_settings
+    };
+    let rays = rendering::trace_rays(space, &settings, rays);
+    match output {
+        Some(path) => export::export_file(space, path, &rays)?,
+        None => print!("{}", export::to_svg(space, &rays)),
+    }
+    Ok(())
 }
 
 fn main() -> Result<(), Error> {
@@ -63,18 +453,47 @@ fn main() -> Result<(), Error> {
                 .with_default_directive(LevelFilter::DEBUG.into())
                 .from_env_lossy(),
         )
-        .with(tracing_subscriber::fmt::layer().without_time());
+        // Logs go to stderr, to keep stdout clean for commands that print their output there.
+        .with(
+            tracing_subscriber::fmt::layer()
+                .without_time()
+                .with_writer(std::io::stderr),
+        );
     tracing::subscriber::set_global_default(subscriber)
         .expect("cannot set default tracing subscriber");
 
     let args = Args::parse();
-    let project = {
-        if let Some(path) = &args.space_file {
-            let json = std::fs::read_to_string(path)?;
-            serde_json::from_str(&json)?
-        } else {
-            Project::new()
-        }
+    if let Some(command) = args.command {
+        return match command {
+            Command::Import {
+                input,
+                output,
+                scale,
+                layer_materials,
+            } => {
+                let options = ImportOptions {
+                    scale,
+                    layer_materials: layer_materials.into_iter().collect(),
+                };
+                import_floor_plan(&input, output.as_deref(), &options)
+            }
+            Command::Upgrade {
+                input,
+                output,
+                strip_view_state,
+            } => upgrade_project(&input, output.as_deref(), strip_view_state),
+            Command::Export {
+                input,
+                output,
+                rays,
+                bounces,
+            } => export_drawing(&input, output.as_deref(), rays, bounces),
+        };
+    }
+
+    let (project, problems) = match &args.space_file {
+        Some(path) => open_project(path),
+        None => (Project::new(), Vector::new()),
     };
 
     let window = WindowDesc::new(root())
@@ -86,7 +505,15 @@ fn main() -> Result<(), Error> {
         .configure_env(|env, _| {
             style::configure_env(env).expect("cannot configure styles");
         })
-        .launch(RootData { project })?;
+        .launch(RootData {
+            project,
+            problems,
+            import: ImportSettings::default(),
+            import_open: false,
+            export: ExportSettings::default(),
+            export_open: false,
+            render_settings_open: false,
+        })?;
 
     Ok(())
 }
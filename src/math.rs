@@ -21,6 +21,17 @@ impl DruidExtToGlam for druid::Point {
     }
 }
 
+pub trait GlamExtToDruid {
+    fn to_druid(self) -> druid::Point;
+}
+
+impl GlamExtToDruid for glam::Vec2 {
+    #[inline(always)]
+    fn to_druid(self) -> druid::Point {
+        Point::new(self.x as f64, self.y as f64)
+    }
+}
+
 pub trait PointExtHitTests {
     fn in_circle(self, center: Point, radius: f64) -> bool;
 
@@ -48,3 +59,16 @@ impl PointExtHitTests for Point {
         self.distance_to_line_squared(a, b) <= radius * radius
     }
 }
+
+/// Returns the smallest "nice" step of the form 1, 2, or 5 times a power of ten that's greater
+/// than or equal to `min_step`.
+///
+/// This is used for picking grid and ruler spacings that are easy to read.
+pub fn nice_step(min_step: f64) -> f64 {
+    let magnitude = 10_f64.powf(min_step.log10().floor());
+    [1.0, 2.0, 5.0, 10.0]
+        .into_iter()
+        .map(|multiplier| multiplier * magnitude)
+        .find(|&step| step >= min_step)
+        .unwrap_or(10.0 * magnitude)
+}
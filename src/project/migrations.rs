@@ -0,0 +1,104 @@
+//! Upgrades of project documents from older versions of the format.
+//!
+//! Migrations operate on untyped JSON, since the structs the older versions were deserialized
+//! into no longer exist.
+
+use serde_json::{Map, Value};
+use tracing::debug;
+
+use super::{ProjectError, FORMAT_VERSION};
+
+/// Upgrades a document from one version of the format to the next.
+type Migration = fn(&mut Value) -> Result<(), ProjectError>;
+
+/// All migrations, in order. The migration at index `i` upgrades a document from version `i` to
+/// version `i + 1`.
+const MIGRATIONS: &[Migration] = &[unversioned_to_1, split_scene_and_view];
+
+/// Upgrades a document from the given version to [`FORMAT_VERSION`], updating its
+/// `format_version` along the way.
+pub(super) fn upgrade(document: &mut Value, version: u32) -> Result<(), ProjectError> {
+    debug_assert_eq!(MIGRATIONS.len(), FORMAT_VERSION as usize);
+    for (index, migration) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+        migration(document)?;
+        if let Some(project) = document.as_object_mut() {
+            project.insert("format_version".into(), (index + 1).into());
+        }
+        debug!(from = index, to = index + 1, "upgraded project");
+    }
+    Ok(())
+}
+
+/// Files saved before the format was versioned. Their layout is the same as version 1's, which
+/// only adds the version itself.
+fn unversioned_to_1(_document: &mut Value) -> Result<(), ProjectError> {
+    Ok(())
+}
+
+/// Version 2 moves the space and render settings into the `scene` section, and the rest of the
+/// space editor's state into the `view` section.
+fn split_scene_and_view(document: &mut Value) -> Result<(), ProjectError> {
+    let error = |message: &str| ProjectError::Migration {
+        version: 1,
+        message: message.to_owned(),
+    };
+
+    let project = document
+        .as_object_mut()
+        .ok_or_else(|| error("expected the project to be an object"))?;
+    let mut view = match project.remove("space_editor") {
+        Some(Value::Object(space_editor)) => space_editor,
+        _ => return Err(error("expected `space_editor` to be an object")),
+    };
+
+    let mut scene = Map::new();
+    if let Some(space) = view.remove("space") {
+        scene.insert("space".into(), space);
+    }
+    if let Some(render_settings) = project.remove("render_settings") {
+        scene.insert("render_settings".into(), render_settings);
+    }
+    project.insert("scene".into(), Value::Object(scene));
+    // Everything left over is view state: the transform, tool, snapping and background.
+    project.insert("view".into(), Value::Object(view));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn unversioned_documents_are_upgraded_to_the_current_version() {
+        let mut document = json!({
+            "space_editor": {
+                "space": { "objects": [] },
+                "transform": {},
+                "tool": "Cursor",
+            },
+            "render_settings": {},
+        });
+        upgrade(&mut document, 0).unwrap();
+
+        assert_eq!(document["format_version"], FORMAT_VERSION);
+        assert_eq!(document["scene"]["space"], json!({ "objects": [] }));
+        assert_eq!(document["scene"]["render_settings"], json!({}));
+        assert_eq!(
+            document["view"],
+            json!({ "transform": {}, "tool": "Cursor" })
+        );
+    }
+
+    #[test]
+    fn current_documents_are_left_alone() {
+        let mut document = json!({
+            "format_version": FORMAT_VERSION,
+            "scene": { "space": { "objects": [] } },
+        });
+        let original = document.clone();
+        upgrade(&mut document, FORMAT_VERSION).unwrap();
+        assert_eq!(document, original);
+    }
+}
@@ -0,0 +1,212 @@
+//! Project file format.
+//!
+//! Project files are split into two sections: the acoustic scene, which holds everything that
+//! affects rendered impulse responses, and the editor's view state. The view state is optional,
+//! such that scene files kept under version control don't change when the view is merely panned.
+//!
+//! Project files also carry a `format_version`. Files written by older versions of fizzerb are
+//! upgraded to the current format when loading, by the migrations in [`migrations`]. Fields added
+//! without changing the layout of existing ones should instead be given a `#[serde(default)]`, so
+//! that they don't need a migration.
+
+mod migrations;
+mod validation;
+
+use std::sync::Arc;
+
+use druid::{im::HashSet, Data, Lens};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use thiserror::Error;
+
+pub use self::validation::Problem;
+use crate::{
+    rendering::RenderSettings,
+    widgets::{
+        background::BackgroundImage, data::EditableSpace, snapping::Snapping, tool::Tool,
+        transform::Transform, SpaceEditorProjectData,
+    },
+};
+
+/// The version of the project format written by this version of fizzerb.
+///
+/// Bump this whenever the layout of existing fields changes, and add a migration from the
+/// previous version to [`migrations::MIGRATIONS`].
+pub const FORMAT_VERSION: u32 = 2;
+
+#[derive(Debug, Error)]
+pub enum ProjectError {
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("invalid format version `{0}`")]
+    InvalidVersion(Value),
+
+    #[error(
+        "the project uses format version {version}, but only versions up to {} are supported; \
+         it was probably saved by a newer version of fizzerb",
+        FORMAT_VERSION
+    )]
+    UnsupportedVersion { version: u64 },
+
+    #[error("cannot upgrade the project from format version {version}: {message}")]
+    Migration { version: u32, message: String },
+}
+
+#[derive(Clone, Data, Lens)]
+pub struct Project {
+    pub render_settings: RenderSettings,
+    pub space_editor: SpaceEditorProjectData,
+}
+
+/// The acoustic scene: everything that affects the rendered impulse responses.
+#[derive(Deserialize, Serialize)]
+struct Scene {
+    space: Arc<EditableSpace>,
+    #[serde(default)]
+    render_settings: RenderSettings,
+}
+
+/// The state of the editor, which does not affect the rendered impulse responses.
+#[derive(Default, Deserialize, Serialize)]
+#[serde(default)]
+struct ViewState {
+    transform: Transform,
+    tool: Tool,
+    snapping: Snapping,
+    background: Option<BackgroundImage>,
+}
+
+/// The layout of project files.
+#[derive(Deserialize, Serialize)]
+struct Document {
+    // The version is checked before deserializing the rest of the document.
+    #[serde(skip_deserializing)]
+    format_version: u32,
+    scene: Scene,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    view: Option<ViewState>,
+}
+
+impl Project {
+    pub fn new() -> Self {
+        Self {
+            render_settings: RenderSettings::default(),
+            space_editor: SpaceEditorProjectData {
+                space: Arc::new(EditableSpace::new()),
+                transform: Transform::default(),
+                tool: Tool::Cursor,
+                snapping: Snapping::default(),
+                selection: HashSet::new(),
+                background: None,
+            },
+        }
+    }
+
+    fn from_document(document: Document) -> Self {
+        let view = document.view.unwrap_or_default();
+        Self {
+            render_settings: document.scene.render_settings,
+            space_editor: SpaceEditorProjectData {
+                space: document.scene.space,
+                transform: view.transform,
+                tool: view.tool,
+                snapping: view.snapping,
+                selection: HashSet::new(),
+                background: view.background,
+            },
+        }
+    }
+
+    fn to_document(&self, include_view_state: bool) -> Document {
+        let space_editor = &self.space_editor;
+        Document {
+            format_version: FORMAT_VERSION,
+            scene: Scene {
+                space: Arc::clone(&space_editor.space),
+                render_settings: self.render_settings.clone(),
+            },
+            view: include_view_state.then(|| ViewState {
+                transform: space_editor.transform.clone(),
+                tool: space_editor.tool,
+                snapping: space_editor.snapping.clone(),
+                background: space_editor.background.clone(),
+            }),
+        }
+    }
+
+    /// Loads a project from JSON, upgrading it from older versions of the format if needed.
+    ///
+    /// Problems with individual parts of the project don't prevent loading it. Such parts are
+    /// repaired or left out, and the problems are returned along with the project.
+    pub fn from_json(json: &str) -> Result<(Self, Vec<Problem>), ProjectError> {
+        let mut document: Value = serde_json::from_str(json)?;
+        // Files saved before the format was versioned don't have a version.
+        let version = match document.get("format_version") {
+            Some(version) => version
+                .as_u64()
+                .ok_or_else(|| ProjectError::InvalidVersion(version.clone()))?,
+            None => 0,
+        };
+        if version > u64::from(FORMAT_VERSION) {
+            return Err(ProjectError::UnsupportedVersion { version });
+        }
+        migrations::upgrade(&mut document, version as u32)?;
+        let problems = validation::validate(&mut document);
+        let project = Self::from_document(serde_json::from_value(document)?);
+        Ok((project, problems))
+    }
+
+    /// Serializes the project into JSON using the current format version. Without view state, the
+    /// project is opened with the default view.
+    pub fn to_json(&self, include_view_state: bool) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(&self.to_document(include_view_state))
+    }
+}
+
+impl Default for Project {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::Path};
+
+    use super::*;
+
+    #[test]
+    fn example_projects_load_without_problems() {
+        let directory = Path::new(env!("CARGO_MANIFEST_DIR")).join("projects");
+        let mut loaded = 0;
+        for entry in fs::read_dir(&directory).unwrap() {
+            let path = entry.unwrap().path();
+            if path.extension().and_then(|extension| extension.to_str()) != Some("json") {
+                continue;
+            }
+            let json = fs::read_to_string(&path).unwrap();
+            let (project, problems) = Project::from_json(&json)
+                .unwrap_or_else(|error| panic!("cannot load {}: {}", path.display(), error));
+            assert!(
+                problems.is_empty(),
+                "{} has problems: {:?}",
+                path.display(),
+                problems
+            );
+            assert!(project.space_editor.space.objects.iter().next().is_some());
+            loaded += 1;
+        }
+        assert!(loaded > 0, "no projects in {}", directory.display());
+    }
+
+    #[test]
+    fn saved_projects_use_the_current_version() {
+        let json = Project::new().to_json(true).unwrap();
+        let document: Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(document["format_version"], FORMAT_VERSION);
+
+        let (_, problems) = Project::from_json(&json).unwrap();
+        assert!(problems.is_empty(), "{:?}", problems);
+    }
+}
@@ -0,0 +1,351 @@
+//! Validation of project documents.
+//!
+//! Validation runs on the upgraded document before it's deserialized. Instead of failing on the
+//! first problem, it collects all of them, and repairs or leaves out the parts of the document
+//! they're in, such that the rest of the project can still be opened.
+
+use std::{collections::HashSet, fmt};
+
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::Value;
+
+use super::ViewState;
+use crate::{
+    rendering::RenderSettings,
+    widgets::data::{Material, Object, Wall, WallShape},
+};
+
+/// A problem found in a project, along with what was done about it.
+#[derive(Debug, Clone)]
+pub struct Problem {
+    /// The path to the offending value, such as `scene.space.objects[3].Wall.start.x`.
+    pub path: String,
+    pub message: String,
+}
+
+impl Problem {
+    fn new(path: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+/// Validates the document, repairing it where possible. Returns all problems found.
+pub(super) fn validate(document: &mut Value) -> Vec<Problem> {
+    let mut problems = vec![];
+
+    // Sections other than the space are not essential, and fall back to defaults if they're
+    // invalid.
+    if let Some(scene) = document.get_mut("scene") {
+        check_section::<RenderSettings>(
+            scene,
+            "scene",
+            "render_settings",
+            "the default render settings are used instead",
+            &mut problems,
+        );
+    }
+    check_section::<ViewState>(
+        document,
+        "",
+        "view",
+        "the default view is used instead",
+        &mut problems,
+    );
+
+    if let Some(space) = document.pointer_mut("/scene/space") {
+        validate_space(space, &mut problems);
+    }
+
+    problems
+}
+
+/// Joins a path to a field of an object.
+fn join(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_owned()
+    } else {
+        format!("{path}.{key}")
+    }
+}
+
+/// Removes the section at `parent[key]` if it cannot be deserialized into `T`.
+fn check_section<T>(
+    parent: &mut Value,
+    parent_path: &str,
+    key: &str,
+    consequence: &str,
+    problems: &mut Vec<Problem>,
+) where
+    T: DeserializeOwned,
+{
+    let error = match parent.get(key) {
+        Some(section) => match T::deserialize(section) {
+            Ok(_) => return,
+            Err(error) => error,
+        },
+        None => return,
+    };
+    problems.push(Problem::new(
+        join(parent_path, key),
+        format!("{error}; {consequence}"),
+    ));
+    if let Some(parent) = parent.as_object_mut() {
+        parent.remove(key);
+    }
+}
+
+fn validate_space(space: &mut Value, problems: &mut Vec<Problem>) {
+    // Walls always refer to a material, so a space can't do without them.
+    if space
+        .get("materials")
+        .and_then(Value::as_array)
+        .is_some_and(Vec::is_empty)
+    {
+        problems.push(Problem::new(
+            "scene.space.materials",
+            "the space has no materials; the preset materials are added instead",
+        ));
+        space["materials"] =
+            serde_json::to_value(Material::presets()).expect("materials are always serializable");
+    }
+    let material_count = space
+        .get("materials")
+        .and_then(Value::as_array)
+        .map_or_else(|| Material::presets().len(), Vec::len);
+    let objects = match space.get_mut("objects").and_then(Value::as_array_mut) {
+        Some(objects) => objects,
+        // Left for deserialization to report.
+        None => return,
+    };
+
+    let mut used_indices = HashSet::new();
+    let entries = std::mem::take(objects);
+    for (index, mut entry) in entries.into_iter().enumerate() {
+        let path = format!("scene.space.objects[{index}]");
+        if validate_object(
+            &path,
+            &mut entry,
+            material_count,
+            &mut used_indices,
+            problems,
+        ) {
+            objects.push(entry);
+        }
+    }
+}
+
+/// The distance below which points of a wall are considered to coincide, in the space's units.
+const LENGTH_TOLERANCE: f64 = 1e-6;
+
+/// Returns whether all points defining the wall's curve coincide. An arc's curve is spanned
+/// between its endpoints, so an arc whose endpoints coincide has zero length no matter its
+/// sagitta; Béziers can still curve away from coinciding endpoints through their control points.
+fn has_zero_length(wall: &Wall) -> bool {
+    let control_points = match wall.shape {
+        WallShape::Line | WallShape::Arc { .. } => vec![],
+        WallShape::QuadraticBezier { control } => vec![control],
+        WallShape::CubicBezier {
+            control_start,
+            control_end,
+        } => vec![control_start, control_end],
+    };
+    std::iter::once(wall.end)
+        .chain(control_points)
+        .all(|point| point.distance(wall.start) < LENGTH_TOLERANCE)
+}
+
+/// Fields of objects which may be `null`, because they're optional.
+const OPTIONAL_FIELDS: &[&str] = &["back_material"];
+
+/// Finds values that are `null`. Numbers that are NaN or infinite are saved as `null`.
+fn find_nulls(value: &Value, path: &str, nulls: &mut Vec<String>) {
+    match value {
+        Value::Null => nulls.push(path.to_owned()),
+        Value::Array(array) => {
+            for (index, element) in array.iter().enumerate() {
+                find_nulls(element, &format!("{path}[{index}]"), nulls);
+            }
+        }
+        Value::Object(object) => {
+            for (key, element) in object {
+                if element.is_null() && OPTIONAL_FIELDS.contains(&key.as_str()) {
+                    continue;
+                }
+                find_nulls(element, &join(path, key), nulls);
+            }
+        }
+        _ => (),
+    }
+}
+
+/// Validates a single entry of the space's objects. Returns whether the object should be kept.
+fn validate_object(
+    path: &str,
+    entry: &mut Value,
+    material_count: usize,
+    used_indices: &mut HashSet<u32>,
+    problems: &mut Vec<Problem>,
+) -> bool {
+    const LEFT_OUT: &str = "the object was left out";
+
+    #[derive(Deserialize)]
+    struct Entry {
+        id: (u32, u32),
+        #[serde(flatten)]
+        object: Object,
+    }
+
+    let mut nulls = vec![];
+    find_nulls(entry, path, &mut nulls);
+    if !nulls.is_empty() {
+        problems.extend(nulls.into_iter().map(|path| {
+            Problem::new(
+                path,
+                format!("expected a number, but found NaN, infinity or nothing; {LEFT_OUT}"),
+            )
+        }));
+        return false;
+    }
+
+    let Entry { id, object } = match Entry::deserialize(&*entry) {
+        Ok(parsed) => parsed,
+        Err(error) => {
+            problems.push(Problem::new(
+                path,
+                format!("invalid object ({error}); {LEFT_OUT}"),
+            ));
+            return false;
+        }
+    };
+    if !used_indices.insert(id.0) {
+        problems.push(Problem::new(
+            join(path, "id"),
+            format!("another object already has the ID {}; {LEFT_OUT}", id.0),
+        ));
+        return false;
+    }
+
+    match object {
+        Object::Wall(wall) => {
+            if has_zero_length(&wall) {
+                problems.push(Problem::new(
+                    path,
+                    format!("the wall has zero length; {LEFT_OUT}"),
+                ));
+                return false;
+            }
+            if wall.material.0 >= material_count {
+                problems.push(Problem::new(
+                    join(path, "Wall.material"),
+                    format!(
+                        "the wall refers to material {}, but there are only {material_count} \
+                         materials; the default material is used instead",
+                        wall.material.0
+                    ),
+                ));
+                entry["Wall"]["material"] = 0.into();
+            }
+            if let Some(back_material) = wall.back_material {
+                if back_material.0 >= material_count {
+                    problems.push(Problem::new(
+                        join(path, "Wall.back_material"),
+                        format!(
+                            "the wall's back refers to material {}, but there are only \
+                             {material_count} materials; the front's material is used instead",
+                            back_material.0
+                        ),
+                    ));
+                    if let Some(wall) = entry["Wall"].as_object_mut() {
+                        wall.remove("back_material");
+                    }
+                }
+            }
+        }
+        Object::Microphone(_) => (),
+        Object::Speaker(speaker) => {
+            if speaker.power < 0.0 {
+                problems.push(Problem::new(
+                    join(path, "Speaker.power"),
+                    format!(
+                        "the speaker's power is negative ({}); it is set to 0 instead",
+                        speaker.power
+                    ),
+                ));
+                entry["Speaker"]["power"] = 0.into();
+            }
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn wall(id: u32, end: Value, shape: Value) -> Value {
+        json!({
+            "id": [id, 0],
+            "Wall": {
+                "start": { "x": 1.0, "y": 1.0 },
+                "end": end,
+                "shape": shape,
+                "material": 0,
+            },
+        })
+    }
+
+    #[test]
+    fn spaces_without_materials_get_the_presets() {
+        let mut space = json!({
+            "materials": [],
+            "objects": [wall(0, json!({ "x": 2.0, "y": 1.0 }), json!("Line"))],
+        });
+        let mut problems = vec![];
+        validate_space(&mut space, &mut problems);
+
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].path, "scene.space.materials");
+        let materials = space["materials"].as_array().unwrap();
+        assert_eq!(materials.len(), Material::presets().len());
+        assert_eq!(space["objects"][0]["Wall"]["material"], 0);
+    }
+
+    #[test]
+    fn only_walls_without_extent_have_zero_length() {
+        let loop_shape = json!({
+            "CubicBezier": {
+                "control_start": { "x": 2.0, "y": 1.0 },
+                "control_end": { "x": 2.0, "y": 2.0 },
+            },
+        });
+        let mut space = json!({
+            "objects": [
+                wall(0, json!({ "x": 1.0, "y": 1.0 + 1e-9 }), json!("Line")),
+                wall(1, json!({ "x": 1.0, "y": 1.0 }), json!({ "Arc": { "sagitta": 1.0 } })),
+                wall(2, json!({ "x": 1.0, "y": 1.0 }), loop_shape),
+            ],
+        });
+        let mut problems = vec![];
+        validate_space(&mut space, &mut problems);
+
+        let paths: Vec<_> = problems
+            .iter()
+            .map(|problem| problem.path.as_str())
+            .collect();
+        assert_eq!(paths, ["scene.space.objects[0]", "scene.space.objects[1]"]);
+        let objects = space["objects"].as_array().unwrap();
+        assert_eq!(objects.len(), 1);
+        assert_eq!(objects[0]["id"], json!([2, 0]));
+    }
+}
@@ -2,23 +2,51 @@
 
 use std::{path::Path, sync::Arc};
 
-use druid::{Data, Lens};
-use fizzerb_impulse::{Compressor, ImpulseRenderer};
-use fizzerb_model::{MicrophoneIndex, SpeakerIndex};
-use fizzerb_tracer::{Tracer, TracerConfig, SPEED_OF_SOUND_IN_AIR};
+use druid::{Data, Lens, Point};
+use fizzerb_impulse::{trimmed_length, Compressor, FadeOut, ImpulseRenderer};
+use fizzerb_model::{MicrophoneIndex, Response, Space, SpeakerIndex};
+use fizzerb_tracer::{
+    find_gaps, find_leaks, Detection, Diagnostics, RecordedRay, Tracer, TracerConfig,
+    SPEED_OF_SOUND_IN_AIR,
+};
 use hound::{SampleFormat, WavSpec, WavWriter};
 use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
-use tracing::{debug, debug_span, error, info, info_span};
+use tracing::{debug, debug_span, error, info, info_span, warn};
 
-use crate::{error::Error, widgets::data::EditableSpace};
+use crate::{error::Error, math::GlamExtToDruid, widgets::data::EditableSpace};
 
 #[derive(Debug, Clone, Data, Lens, Deserialize, Serialize)]
+#[serde(default)]
 pub struct RenderSettings {
     pub max_bounces: usize,
     pub samples: usize,
+    /// The share of their initial energy below which rays may be stopped early. Responses are
+    /// scaled by this share as an amplitude, so the default of 0.001 is 60 dB below a ray's
+    /// initial level.
+    pub energy_threshold: f32,
+    /// The length of the impulse response in seconds. Sound arriving later is not traced, and all
+    /// saved files are this long unless `trim_silence` is enabled.
+    pub max_ir_seconds: f32,
+    /// Whether to cut off the silence at the end of the impulse responses. Impulse responses
+    /// rendered together are still cut to the same length.
+    pub trim_silence: bool,
+    /// The length in seconds of the fade out at the end of the impulse responses. Zero disables
+    /// fading out.
+    pub fade_out_seconds: f32,
 
     pub speed_of_sound: f32,
+    /// The radius of the receivers in metres, when rays should physically hit them rather than
+    /// reach them through shadow rays.
+    pub receiver_radius: Option<f32>,
+    /// Whether rays should leave the speaker and be received by all microphones at once, rather
+    /// than leave each microphone in turn. Faster with many microphones.
+    pub trace_from_speaker: bool,
+    /// Whether to include the direct path from the speaker to the microphone. Disable to render
+    /// only the reflections.
+    pub include_direct_sound: bool,
+    /// Whether sound bends around the free ends of walls.
+    pub diffraction: bool,
 
     pub compressor_gain: f32,
     pub compressor_threshold: f32,
@@ -33,8 +61,16 @@ impl Default for RenderSettings {
         Self {
             max_bounces: 512,
             samples: 256,
+            energy_threshold: 0.001,
+            max_ir_seconds: 4.0,
+            trim_silence: false,
+            fade_out_seconds: 0.0,
 
             speed_of_sound: SPEED_OF_SOUND_IN_AIR,
+            receiver_radius: None,
+            trace_from_speaker: false,
+            include_direct_sound: true,
+            diffraction: false,
 
             compressor_gain: 1.0,
             compressor_threshold: 0.8,
@@ -46,7 +82,31 @@ impl Default for RenderSettings {
     }
 }
 
-pub fn render(editable_space: Arc<EditableSpace>, settings: &RenderSettings) {
+impl RenderSettings {
+    fn detection(&self) -> Detection {
+        match self.receiver_radius {
+            Some(radius) => Detection::Volumetric { radius },
+            None => Detection::ShadowRays,
+        }
+    }
+
+    fn tracer_config(&self, record_rays: bool) -> TracerConfig {
+        TracerConfig {
+            speed_of_sound: self.speed_of_sound,
+            max_bounces: self.max_bounces,
+            energy_threshold: self.energy_threshold,
+            max_time: self.max_ir_seconds,
+            record_rays,
+            detection: self.detection(),
+            include_direct_sound: self.include_direct_sound,
+            diffraction: self.diffraction,
+        }
+    }
+}
+
+/// Renders impulse responses for every microphone, and saves them. Returns the places where rays
+/// escaped the space while rendering, in the space's units.
+pub fn render(editable_space: Arc<EditableSpace>, settings: &RenderSettings) -> Vec<Point> {
     let _span = info_span!("render").entered();
     info!(?settings, "use settings");
 
@@ -58,54 +118,223 @@ pub fn render(editable_space: Arc<EditableSpace>, settings: &RenderSettings) {
         speakers = model.speakers.len(),
         "model stats",
     );
+    warn_about_gaps(&model);
 
     // TEMPORARY: I'm too lazy to mix speakers down at the moment. Excuse my idiocy.
     let speaker = if !model.speakers.is_empty() {
         SpeakerIndex(0)
     } else {
-        return;
-    };
-    let tracer_config = TracerConfig {
-        speed_of_sound: settings.speed_of_sound,
-        max_bounces: settings.max_bounces,
-        record_rays: false,
+        return vec![];
     };
-    for (index, _) in model.microphones.iter().enumerate() {
-        let microphone = MicrophoneIndex(index);
-        let _span = debug_span!("microphone", ?index).entered();
+    let tracer_config = settings.tracer_config(false);
+    let tracer = Tracer::new(&model, &tracer_config);
+
+    let mut diagnostics = Diagnostics::default();
+    let mut impulse_responses = Vec::with_capacity(model.microphones.len());
+    if settings.trace_from_speaker {
+        let _span = debug_span!("speaker", index = speaker.0).entered();
 
-        debug!("gathering recordings");
-        let recordings: Vec<_> = (0..1024)
+        debug!("gathering surveys");
+        let surveys: Vec<_> = (0..1024)
             .into_par_iter()
-            .map(|_| {
-                let tracer = Tracer::new(&model, &tracer_config);
-                let angle = fastrand::f32() * 2.0 * std::f32::consts::PI;
-                let start_ray = glam::Vec2::from_angle(angle);
-                tracer.perform_trace(microphone, speaker, start_ray)
-            })
+            .map(|_| tracer.perform_source_trace(speaker, random_start_ray()))
             .collect();
-        debug!(total = recordings.len(), "recordings gathered");
+        debug!(total = surveys.len(), "surveys gathered");
+        let survey_diagnostics: Diagnostics = surveys.iter().map(|survey| survey.stats).collect();
+        report_diagnostics(&survey_diagnostics);
+        diagnostics.merge(survey_diagnostics);
+
+        for index in 0..model.microphones.len() {
+            let _span = debug_span!("microphone", ?index).entered();
+            let responses = surveys
+                .iter()
+                .map(|survey| survey.responses[index].as_slice());
+            impulse_responses.push(mix(settings, responses));
+        }
+    } else {
+        for index in 0..model.microphones.len() {
+            let microphone = MicrophoneIndex(index);
+            let _span = debug_span!("microphone", ?index).entered();
+
+            debug!("gathering recordings");
+            let recordings: Vec<_> = (0..1024)
+                .into_par_iter()
+                .map(|_| tracer.perform_trace(microphone, speaker, random_start_ray()))
+                .collect();
+            debug!(total = recordings.len(), "recordings gathered");
+            let recording_diagnostics: Diagnostics =
+                recordings.iter().map(|recording| recording.stats).collect();
+            report_diagnostics(&recording_diagnostics);
+            diagnostics.merge(recording_diagnostics);
 
-        debug!("mixing recordings into final impulse");
-        let mut impulse_renderer = ImpulseRenderer::new(settings.sample_rate as f32);
-        for recording in recordings {
-            impulse_renderer.add_responses(&recording.responses);
+            let responses = recordings
+                .iter()
+                .map(|recording| recording.responses.as_slice());
+            impulse_responses.push(mix(settings, responses));
         }
+    }
+    save_impulse_responses(settings, impulse_responses);
+
+    leak_points(&editable_space, &model, &diagnostics)
+}
+
+fn random_start_ray() -> glam::Vec2 {
+    let angle = fastrand::f32() * 2.0 * std::f32::consts::PI;
+    glam::Vec2::from_angle(angle)
+}
+
+/// Logs a warning for every gap in the walls through which rays escape from around a microphone
+/// or speaker. Sound escaping the room is lost, which makes for a shorter, quieter response.
+fn warn_about_gaps(model: &Space) {
+    let microphones = model
+        .microphones
+        .iter()
+        .enumerate()
+        .map(|(index, microphone)| ("microphone", index, microphone.position));
+    let speakers = model
+        .speakers
+        .iter()
+        .enumerate()
+        .map(|(index, speaker)| ("speaker", index, speaker.position));
+    for (kind, index, position) in microphones.chain(speakers) {
+        for gap in find_gaps(model, position) {
+            let [a, b] = gap.sides;
+            warn!(
+                index,
+                kind,
+                ?position,
+                side_a = ?a,
+                side_b = ?b,
+                escape = ?gap.escape.start,
+                "rays escape the space through a gap in the walls",
+            );
+        }
+    }
+}
 
-        debug!("rendering the impulse");
-        let impulse_response = impulse_renderer.render(
-            settings.compressor_gain,
-            Compressor {
-                sample_rate: settings.sample_rate as f32,
-                threshold: settings.compressor_threshold,
-                release: settings.compressor_release,
-            },
+/// Rays which still carry more than this share of their energy on average when they're cut off
+/// leave out a noticeable part of the reverb's tail.
+const SIGNIFICANT_ENERGY_AT_CUTOFF: f32 = 0.1;
+
+/// Logs statistics about the traced rays, warning about those which point to problems with the
+/// space or the render settings.
+fn report_diagnostics(diagnostics: &Diagnostics) {
+    info!(
+        rays = diagnostics.rays,
+        escaped = diagnostics.escapes.len(),
+        average_bounces = diagnostics.average_bounces(),
+        cut_off = diagnostics.cut_off,
+        average_energy_at_cutoff = diagnostics.average_energy_at_cutoff(),
+        absorbed = diagnostics.absorbed,
+        timed_out = diagnostics.timed_out,
+        "ray statistics",
+    );
+    if !diagnostics.escapes.is_empty() {
+        warn!(
+            share = diagnostics.escaped_share(),
+            "rays escaped the space through gaps in the walls, which makes it sound drier",
+        );
+    }
+    if diagnostics.average_energy_at_cutoff() > SIGNIFICANT_ENERGY_AT_CUTOFF {
+        warn!(
+            energy = diagnostics.average_energy_at_cutoff(),
+            "rays were cut off while still carrying much of their energy; raise the maximal \
+             number of bounces for a longer tail",
         );
+    }
+}
 
+/// Finds where the traced rays escaped the space, in the space's units.
+fn leak_points(
+    editable_space: &EditableSpace,
+    model: &Space,
+    diagnostics: &Diagnostics,
+) -> Vec<Point> {
+    let to_space_units = 1.0 / editable_space.unit.to_metres();
+    find_leaks(model, &diagnostics.escapes)
+        .into_iter()
+        .map(|leak| {
+            debug!(position = ?leak.position, rays = leak.rays, "leak");
+            (leak.position.to_druid().to_vec2() * to_space_units).to_point()
+        })
+        .collect()
+}
+
+/// Mixes the responses of all traced rays into a single impulse response.
+fn mix<'a>(settings: &RenderSettings, responses: impl Iterator<Item = &'a [Response]>) -> Vec<f32> {
+    debug!("mixing recordings into final impulse");
+    let mut impulse_renderer =
+        ImpulseRenderer::new(settings.sample_rate as f32, settings.max_ir_seconds);
+    for responses in responses {
+        impulse_renderer.add_responses(responses);
+    }
+
+    debug!("rendering the impulse");
+    impulse_renderer.render(
+        settings.compressor_gain,
+        Compressor {
+            sample_rate: settings.sample_rate as f32,
+            threshold: settings.compressor_threshold,
+            release: settings.compressor_release,
+        },
+    )
+}
+
+/// Saves the impulse responses of all microphones, indexed by microphone. All of them are cut to
+/// the same length, so that they line up with each other.
+fn save_impulse_responses(settings: &RenderSettings, mut impulse_responses: Vec<Vec<f32>>) {
+    if settings.trim_silence {
+        let length = impulse_responses
+            .iter()
+            .map(|impulse_response| trimmed_length(impulse_response))
+            .max()
+            .unwrap_or(0);
+        debug!(length, "trimming silence");
+        for impulse_response in &mut impulse_responses {
+            impulse_response.truncate(length);
+        }
+    }
+
+    let fade_out = FadeOut {
+        sample_rate: settings.sample_rate as f32,
+        duration: settings.fade_out_seconds,
+    };
+    for (index, mut impulse_response) in impulse_responses.into_iter().enumerate() {
+        fade_out.run(&mut impulse_response);
         save_wav(settings, &impulse_response, index);
     }
 }
 
+/// Traces `count` rays from the first microphone (or the first speaker, when tracing from
+/// speakers), evenly spread around it, and returns them for visualization. The rays' coordinates
+/// are in metres.
+pub fn trace_rays(
+    editable_space: &EditableSpace,
+    settings: &RenderSettings,
+    count: usize,
+) -> Vec<RecordedRay> {
+    let model = editable_space.to_model();
+    if model.microphones.is_empty() || model.speakers.is_empty() {
+        return vec![];
+    }
+
+    let tracer_config = settings.tracer_config(true);
+    let tracer = Tracer::new(&model, &tracer_config);
+    (0..count)
+        .flat_map(|index| {
+            let angle = index as f32 / count as f32 * 2.0 * std::f32::consts::PI;
+            let start_ray = glam::Vec2::from_angle(angle);
+            if settings.trace_from_speaker {
+                tracer.perform_source_trace(SpeakerIndex(0), start_ray).rays
+            } else {
+                tracer
+                    .perform_trace(MicrophoneIndex(0), SpeakerIndex(0), start_ray)
+                    .rays
+            }
+        })
+        .collect()
+}
+
 fn save_wav(settings: &RenderSettings, impulse_response: &[f32], microphone_index: usize) {
     fn inner(
         settings: &RenderSettings,
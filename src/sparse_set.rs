@@ -150,7 +150,10 @@ impl Sparse {
 }
 
 mod id {
-    use std::fmt::Debug;
+    use std::{
+        fmt::Debug,
+        hash::{Hash, Hasher},
+    };
 
     use super::*;
 
@@ -179,6 +182,13 @@ mod id {
     }
 
     impl<T> Eq for Id<T> {}
+
+    impl<T> Hash for Id<T> {
+        fn hash<H: Hasher>(&self, state: &mut H) {
+            self.index.hash(state);
+            self.generation.hash(state);
+        }
+    }
 }
 
 mod serialization {
@@ -36,6 +36,9 @@ pub fn configure_env(env: &mut Env) -> Result<(), Error> {
     env.set(WINDOW_PADDING, 16.0);
 
     widgets::button::style::configure_env(env);
+    widgets::import_panel::style::configure_env(env);
+    widgets::inspector::style::configure_env(env);
+    widgets::problems::style::configure_env(env);
     widgets::space_editor::style::configure_env(env);
 
     Ok(())
@@ -6,6 +6,7 @@ use druid::{
 pub struct Button<T> {
     inner: Label<T>,
     inner_size: Size,
+    is_checked: Option<Box<dyn Fn(&T) -> bool>>,
 }
 
 impl<T> Button<T>
@@ -20,9 +21,16 @@ where
         Self {
             inner: label,
             inner_size: Size::ZERO,
+            is_checked: None,
         }
     }
 
+    /// Makes the button display as checked whenever `is_checked` returns `true`.
+    pub fn checked(mut self, is_checked: impl Fn(&T) -> bool + 'static) -> Self {
+        self.is_checked = Some(Box::new(is_checked));
+        self
+    }
+
     pub fn on_click(
         self,
         f: impl Fn(&mut EventCtx<'_, '_>, &mut T, &Env) + 'static,
@@ -61,6 +69,11 @@ where
     }
 
     fn update(&mut self, ctx: &mut UpdateCtx, old_data: &T, data: &T, env: &Env) {
+        if let Some(is_checked) = &self.is_checked {
+            if is_checked(old_data) != is_checked(data) {
+                ctx.request_paint();
+            }
+        }
         self.inner.update(ctx, old_data, data, env);
     }
 
@@ -81,6 +94,8 @@ where
             env.get(style::ACTIVE_COLOR)
         } else if ctx.is_hot() {
             env.get(style::HOT_COLOR)
+        } else if self.is_checked.as_ref().map_or(false, |f| f(data)) {
+            env.get(style::CHECKED_COLOR)
         } else {
             env.get(style::INACTIVE_COLOR)
         };
@@ -107,6 +122,7 @@ pub mod style {
     pub const INACTIVE_COLOR: Key<Color> = style_key!("button.inactive.color");
     pub const HOT_COLOR: Key<Color> = style_key!("button.hot.color");
     pub const ACTIVE_COLOR: Key<Color> = style_key!("button.active.color");
+    pub const CHECKED_COLOR: Key<Color> = style_key!("button.checked.color");
 
     pub fn configure_env(env: &mut Env) {
         env.set(LABELLED_HEIGHT, 36.0);
@@ -116,5 +132,6 @@ pub mod style {
         env.set(INACTIVE_COLOR, color(0xE2E5E9));
         env.set(HOT_COLOR, color(0xCDD3DA));
         env.set(ACTIVE_COLOR, color(0xA2AEBB));
+        env.set(CHECKED_COLOR, color(0xB8C1CC));
     }
 }
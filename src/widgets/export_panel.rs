@@ -0,0 +1,62 @@
+//! Panel for choosing what goes into exported drawings.
+
+use druid::{
+    widget::{CrossAxisAlignment, Flex, Label},
+    Data, FileDialogOptions, FileSpec, Lens, Widget, WidgetExt,
+};
+
+use crate::widgets::{
+    inspector::{self, field, number_box},
+    Button,
+};
+
+/// The options the space is exported with in the editor, as edited in the export panel.
+#[derive(Clone, Data, Lens)]
+pub struct ExportSettings {
+    /// The number of rays to trace from the first microphone and draw over the space.
+    pub rays: usize,
+    /// The maximum number of times each drawn ray bounces off of walls.
+    pub bounces: usize,
+}
+
+impl Default for ExportSettings {
+    fn default() -> Self {
+        Self {
+            rays: 0,
+            bounces: 4,
+        }
+    }
+}
+
+/// Returns the panel for adjusting the export options and picking where to save the drawing.
+pub fn export_panel() -> impl Widget<ExportSettings> {
+    let choose_button =
+        Button::new("Choose file…").on_click(|ctx, _data: &mut ExportSettings, _env| {
+            let svg = FileSpec::new("SVG image", &["svg"]);
+            let options = FileDialogOptions::new()
+                .allowed_types(vec![svg])
+                .default_type(svg)
+                .title("Export space");
+            ctx.submit_command(druid::commands::SHOW_SAVE_PANEL.with(options));
+        });
+    Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(Label::new("Export space"))
+        .with_default_spacer()
+        .with_child(field(
+            "Rays",
+            Label::new(""),
+            number_box(0).lens(ExportSettings::rays),
+        ))
+        .with_default_spacer()
+        .with_child(field(
+            "Bounces",
+            Label::new(""),
+            number_box(0).lens(ExportSettings::bounces),
+        ))
+        .with_default_spacer()
+        .with_child(choose_button)
+        .padding(inspector::style::PADDING)
+        .background(inspector::style::BACKGROUND)
+        .rounded(inspector::style::CORNER_RADIUS)
+}
@@ -0,0 +1,92 @@
+//! Panel for choosing how floor plans are imported.
+
+use druid::{
+    widget::{CrossAxisAlignment, Flex, Label, TextBox},
+    Data, FileDialogOptions, FileSpec, Lens, Widget, WidgetExt,
+};
+
+use crate::{
+    import::{self, ImportError, ImportOptions},
+    widgets::{
+        inspector::{self, field, number_box},
+        Button,
+    },
+};
+
+/// The options floor plans are imported with in the editor, as edited in the import panel.
+#[derive(Clone, Data, Lens)]
+pub struct ImportSettings {
+    /// The factor all coordinates in the floor plan are multiplied by.
+    pub scale: f64,
+    /// Comma-separated mappings of layers to materials, each written as `LAYER=MATERIAL`.
+    pub layer_materials: String,
+}
+
+impl ImportSettings {
+    pub fn to_options(&self) -> Result<ImportOptions, ImportError> {
+        let layer_materials = self
+            .layer_materials
+            .split(',')
+            .filter(|mapping| !mapping.trim().is_empty())
+            .map(import::parse_layer_material)
+            .collect::<Result<_, _>>()?;
+        Ok(ImportOptions {
+            scale: self.scale,
+            layer_materials,
+        })
+    }
+}
+
+impl Default for ImportSettings {
+    fn default() -> Self {
+        Self {
+            scale: 1.0,
+            layer_materials: String::new(),
+        }
+    }
+}
+
+/// Returns the panel for adjusting the import options and picking the floor plan to import.
+pub fn import_panel() -> impl Widget<ImportSettings> {
+    let choose_button =
+        Button::new("Choose file…").on_click(|ctx, _data: &mut ImportSettings, _env| {
+            let floor_plans = FileSpec::new("Floor plans", &["svg", "dxf"]);
+            let options = FileDialogOptions::new()
+                .allowed_types(vec![floor_plans])
+                .title("Import floor plan");
+            ctx.submit_command(druid::commands::SHOW_OPEN_PANEL.with(options));
+        });
+    Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(Label::new("Import floor plan"))
+        .with_default_spacer()
+        .with_child(field(
+            "Scale",
+            Label::new("×"),
+            number_box(3).lens(ImportSettings::scale),
+        ))
+        .with_default_spacer()
+        .with_child(Label::new("Layer materials"))
+        .with_default_spacer()
+        .with_child(
+            TextBox::new()
+                .with_placeholder("LAYER=MATERIAL, …")
+                .fix_width(style::LAYERS_WIDTH)
+                .lens(ImportSettings::layer_materials),
+        )
+        .with_default_spacer()
+        .with_child(choose_button)
+        .padding(inspector::style::PADDING)
+        .background(inspector::style::BACKGROUND)
+        .rounded(inspector::style::CORNER_RADIUS)
+}
+
+pub mod style {
+    use druid::{Env, Key};
+
+    pub const LAYERS_WIDTH: Key<f64> = style_key!("import-panel.layers-width");
+
+    pub fn configure_env(env: &mut Env) {
+        env.set(LAYERS_WIDTH, 240.0);
+    }
+}
@@ -0,0 +1,491 @@
+//! Panel for editing the properties of the selected object numerically.
+
+use druid::{
+    im::Vector,
+    lens,
+    lens::Map,
+    text::ParseFormatter,
+    widget::{
+        CrossAxisAlignment, Either, EnvScope, Flex, Label, SizedBox, Slider, TextBox, ViewSwitcher,
+    },
+    ArcStr, Data, Env, Key, Lens, LensExt, Point, Vec2, Widget, WidgetExt,
+};
+
+use crate::{
+    sparse_set::Id,
+    widgets::{
+        space_editor::{
+            background::BackgroundImage,
+            data::{Directivity, MaterialIndex, Microphone, Object, Speaker, Wall},
+        },
+        tool::Tool,
+        Button, SpaceEditorProjectData,
+    },
+};
+
+/// The symbol of the unit of length used by the space, made available to the fields' labels.
+const LENGTH_UNIT: Key<ArcStr> = key!("inspector.length-unit");
+
+/// Returns the ID of the selected object, if exactly one object is selected.
+fn selected_id(data: &SpaceEditorProjectData) -> Option<Id<Object>> {
+    if data.selection.len() == 1 {
+        data.selection.iter().next().copied()
+    } else {
+        None
+    }
+}
+
+fn selected_object(data: &SpaceEditorProjectData) -> Option<&Object> {
+    selected_id(data).and_then(|id| data.space.objects.get(id))
+}
+
+/// Returns a lens focusing on the selected object, if it's of the variant extracted by `get`.
+///
+/// When the selected object is of a different variant, the lens yields a default value and
+/// discards all changes made to it, so it should only be used under an [`Either`] checking for the
+/// variant.
+fn selected<T>(
+    get: fn(&Object) -> Option<&T>,
+    wrap: fn(T) -> Object,
+) -> impl Lens<SpaceEditorProjectData, T>
+where
+    T: Data + Default,
+{
+    Map::new(
+        move |data: &SpaceEditorProjectData| {
+            selected_object(data)
+                .and_then(get)
+                .cloned()
+                .unwrap_or_default()
+        },
+        move |data: &mut SpaceEditorProjectData, value: T| {
+            let id = match selected_id(data) {
+                Some(id) => id,
+                None => return,
+            };
+            // Only write changes back to the space when there are any, to not create needless
+            // copies of it (and undo steps.)
+            let is_changed = data
+                .space
+                .objects
+                .get(id)
+                .and_then(get)
+                .map_or(false, |current| !current.same(&value));
+            if is_changed {
+                if let Some(object) = data.edit_space().objects.get_mut(id) {
+                    *object = wrap(value);
+                }
+            }
+        },
+    )
+}
+
+fn selected_wall() -> impl Lens<SpaceEditorProjectData, Wall> {
+    selected(
+        |object| match object {
+            Object::Wall(wall) => Some(wall),
+            _ => None,
+        },
+        Object::Wall,
+    )
+}
+
+fn selected_microphone() -> impl Lens<SpaceEditorProjectData, Microphone> {
+    selected(
+        |object| match object {
+            Object::Microphone(microphone) => Some(microphone),
+            _ => None,
+        },
+        Object::Microphone,
+    )
+}
+
+fn selected_speaker() -> impl Lens<SpaceEditorProjectData, Speaker> {
+    selected(
+        |object| match object {
+            Object::Speaker(speaker) => Some(speaker),
+            _ => None,
+        },
+        Object::Speaker,
+    )
+}
+
+/// Lens for the length of a wall. Changing the length moves the wall's end point.
+fn wall_length() -> impl Lens<Wall, f64> {
+    Map::new(
+        |wall: &Wall| wall.start.distance(wall.end),
+        |wall: &mut Wall, length: f64| {
+            let direction = wall_direction(wall);
+            wall.end = wall.start + direction * length;
+        },
+    )
+}
+
+/// Lens for the angle of a wall, in degrees. Changing the angle rotates the wall around its start
+/// point.
+fn wall_angle() -> impl Lens<Wall, f64> {
+    Map::new(
+        |wall: &Wall| (wall.end - wall.start).atan2().to_degrees(),
+        |wall: &mut Wall, angle: f64| {
+            let length = wall.start.distance(wall.end);
+            wall.end = wall.start + Vec2::from_angle(angle.to_radians()) * length;
+        },
+    )
+}
+
+fn wall_direction(wall: &Wall) -> Vec2 {
+    let delta = wall.end - wall.start;
+    if delta.hypot2() > 0.0 {
+        delta.normalize()
+    } else {
+        Vec2::new(1.0, 0.0)
+    }
+}
+
+pub(super) fn number_box<T>(precision: usize) -> impl Widget<T>
+where
+    T: Data + std::fmt::Display + std::str::FromStr,
+    <T as std::str::FromStr>::Err: std::error::Error + 'static,
+{
+    TextBox::new()
+        .with_formatter(ParseFormatter::with_format_fn(move |value: &T| {
+            format!("{:.*}", precision, value)
+        }))
+        .validate_while_editing(false)
+        .update_data_while_editing(true)
+        .fix_width(style::FIELD_WIDTH)
+}
+
+/// Returns a label showing the symbol of the space's unit of length.
+fn length_unit<T: Data>() -> Label<T> {
+    Label::new(|_data: &T, env: &Env| env.get(LENGTH_UNIT).to_string())
+}
+
+/// Returns a row with a labelled field, followed by the field's unit.
+pub(super) fn field<T: Data>(
+    label: &str,
+    unit: Label<T>,
+    widget: impl Widget<T> + 'static,
+) -> impl Widget<T> {
+    Flex::row()
+        .with_child(Label::new(label).fix_width(style::LABEL_WIDTH))
+        .with_child(widget)
+        .with_spacer(style::UNIT_SPACING)
+        .with_child(unit)
+}
+
+fn point_fields(label: &str) -> impl Widget<Point> {
+    Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(field(
+            &format!("{label} X"),
+            length_unit(),
+            number_box(2).lens(lens!(Point, x)),
+        ))
+        .with_default_spacer()
+        .with_child(field(
+            &format!("{label} Y"),
+            length_unit(),
+            number_box(2).lens(lens!(Point, y)),
+        ))
+}
+
+/// Returns buttons for picking one of the space's materials for the front or back side of the
+/// selected wall. The back side can also use the same material as the front.
+fn material_picker(back: bool) -> impl Widget<SpaceEditorProjectData> {
+    ViewSwitcher::new(
+        |data: &SpaceEditorProjectData, _env| data.space.materials.clone(),
+        move |materials: &Vector<_>, _data, _env| {
+            let mut column = Flex::column().cross_axis_alignment(CrossAxisAlignment::Start);
+            if back {
+                column.add_child(
+                    Button::new("Same as front")
+                        .checked(|wall: &Wall| wall.back_material.is_none())
+                        .on_click(|_ctx, wall: &mut Wall, _env| wall.back_material = None)
+                        .lens(selected_wall()),
+                );
+                column.add_spacer(style::MATERIAL_SPACING);
+            }
+            for (index, material) in materials.iter().enumerate() {
+                let index = MaterialIndex(index);
+                column.add_child(
+                    Button::new(material.name.as_str())
+                        .checked(move |wall: &Wall| {
+                            if back {
+                                wall.back_material == Some(index)
+                            } else {
+                                wall.material == index
+                            }
+                        })
+                        .on_click(move |_ctx, wall: &mut Wall, _env| {
+                            if back {
+                                wall.back_material = Some(index);
+                            } else {
+                                wall.material = index;
+                            }
+                        })
+                        .lens(selected_wall()),
+                );
+                column.add_spacer(style::MATERIAL_SPACING);
+            }
+            Box::new(column)
+        },
+    )
+}
+
+fn wall_inspector() -> impl Widget<SpaceEditorProjectData> {
+    let fields = Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(point_fields("Start").lens(lens!(Wall, start)))
+        .with_default_spacer()
+        .with_child(point_fields("End").lens(lens!(Wall, end)))
+        .with_default_spacer()
+        .with_child(field(
+            "Length",
+            length_unit(),
+            number_box(2).lens(wall_length()),
+        ))
+        .with_default_spacer()
+        .with_child(field(
+            "Angle",
+            Label::new("°"),
+            number_box(1).lens(wall_angle()),
+        ))
+        .lens(selected_wall());
+    Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(Label::new("Wall"))
+        .with_default_spacer()
+        .with_child(fields)
+        .with_default_spacer()
+        .with_child(Label::new("Front material"))
+        .with_default_spacer()
+        .with_child(material_picker(false))
+        .with_default_spacer()
+        .with_child(Label::new("Back material"))
+        .with_default_spacer()
+        .with_child(material_picker(true))
+}
+
+fn microphone_inspector() -> impl Widget<SpaceEditorProjectData> {
+    Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(Label::new("Microphone"))
+        .with_default_spacer()
+        .with_child(point_fields("Position").lens(lens!(Microphone, position)))
+        .lens(selected_microphone())
+}
+
+/// Lens for the direction a speaker is facing, in degrees.
+fn speaker_angle() -> impl Lens<Speaker, f64> {
+    Map::new(
+        |speaker: &Speaker| speaker.angle.to_degrees(),
+        |speaker: &mut Speaker, angle: f64| speaker.angle = angle.to_radians(),
+    )
+}
+
+/// Returns buttons for picking the directivity pattern of a speaker.
+fn directivity_picker() -> impl Widget<Speaker> {
+    let mut column = Flex::column().cross_axis_alignment(CrossAxisAlignment::Start);
+    for directivity in Directivity::ALL {
+        column.add_child(
+            Button::new(directivity.name())
+                .checked(move |speaker: &Speaker| speaker.directivity == directivity)
+                .on_click(move |_ctx, speaker: &mut Speaker, _env| {
+                    speaker.directivity = directivity;
+                }),
+        );
+        column.add_spacer(style::MATERIAL_SPACING);
+    }
+    column
+}
+
+fn speaker_inspector() -> impl Widget<SpaceEditorProjectData> {
+    Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(Label::new("Speaker"))
+        .with_default_spacer()
+        .with_child(point_fields("Position").lens(lens!(Speaker, position)))
+        .with_default_spacer()
+        .with_child(field(
+            "Power",
+            Label::new(""),
+            number_box(2).lens(lens!(Speaker, power)),
+        ))
+        .with_default_spacer()
+        .with_child(field(
+            "Facing",
+            Label::new("°"),
+            number_box(1).lens(speaker_angle()),
+        ))
+        .with_default_spacer()
+        .with_child(Label::new("Directivity"))
+        .with_default_spacer()
+        .with_child(directivity_picker())
+        .lens(selected_speaker())
+}
+
+/// Returns a widget that's only shown when the selected object satisfies `predicate`.
+fn when_selected(
+    predicate: fn(&Object) -> bool,
+    widget: impl Widget<SpaceEditorProjectData> + 'static,
+) -> impl Widget<SpaceEditorProjectData> {
+    Either::new(
+        move |data: &SpaceEditorProjectData, _env| selected_object(data).map_or(false, predicate),
+        widget,
+        SizedBox::empty(),
+    )
+}
+
+/// Lens for the background image's properties. Like [`selected`], it yields a default value and
+/// discards changes when there is no background image.
+fn background() -> impl Lens<SpaceEditorProjectData, BackgroundImage> {
+    Map::new(
+        |data: &SpaceEditorProjectData| data.background.clone().unwrap_or_default(),
+        |data: &mut SpaceEditorProjectData, value: BackgroundImage| {
+            if let Some(background) = &mut data.background {
+                if !background.same(&value) {
+                    *background = value;
+                }
+            }
+        },
+    )
+}
+
+/// Lens for the rotation of the background image, in degrees.
+fn background_rotation() -> impl Lens<BackgroundImage, f64> {
+    Map::new(
+        |background: &BackgroundImage| background.rotation.to_degrees(),
+        |background: &mut BackgroundImage, angle: f64| background.rotation = angle.to_radians(),
+    )
+}
+
+fn background_fields() -> impl Widget<SpaceEditorProjectData> {
+    let fields = Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(point_fields("Position").lens(lens!(BackgroundImage, position)))
+        .with_default_spacer()
+        .with_child(field(
+            "Pixel size",
+            length_unit(),
+            number_box(4).lens(lens!(BackgroundImage, scale)),
+        ))
+        .with_default_spacer()
+        .with_child(field(
+            "Rotation",
+            Label::new("°"),
+            number_box(1).lens(background_rotation()),
+        ))
+        .with_default_spacer()
+        .with_child(field(
+            "Opacity",
+            Label::new(""),
+            Slider::new()
+                .with_range(0.0, 1.0)
+                .fix_width(style::FIELD_WIDTH)
+                .lens(lens!(BackgroundImage, opacity)),
+        ))
+        .with_default_spacer()
+        .with_child(field(
+            "Known length",
+            length_unit(),
+            number_box(2).lens(lens!(BackgroundImage, calibration_length)),
+        ))
+        .lens(background());
+    let buttons = Flex::row()
+        .with_child(
+            Button::new("Calibrate")
+                .checked(|data: &SpaceEditorProjectData| data.tool == Tool::Calibrate)
+                .on_click(|_ctx, data: &mut SpaceEditorProjectData, _env| {
+                    data.tool = Tool::Calibrate;
+                }),
+        )
+        .with_default_spacer()
+        .with_child(Button::new("Remove").on_click(
+            |_ctx, data: &mut SpaceEditorProjectData, _env| {
+                data.background = None;
+            },
+        ));
+    Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(Label::new("Background"))
+        .with_default_spacer()
+        .with_child(fields)
+        .with_default_spacer()
+        .with_child(buttons)
+}
+
+/// Wraps the contents of a panel in the panel's background, and makes the space's unit available
+/// to the contents' labels.
+fn panel(
+    contents: impl Widget<SpaceEditorProjectData> + 'static,
+) -> impl Widget<SpaceEditorProjectData> {
+    let panel = contents
+        .padding(style::PADDING)
+        .background(style::BACKGROUND)
+        .rounded(style::CORNER_RADIUS);
+    EnvScope::new(
+        |env, data: &SpaceEditorProjectData| {
+            env.set(LENGTH_UNIT, data.space.unit.symbol());
+        },
+        panel,
+    )
+}
+
+/// Returns the inspector panel, which shows editable properties of the selected object when
+/// exactly one object is selected.
+pub fn inspector() -> impl Widget<SpaceEditorProjectData> {
+    let contents = Flex::column()
+        .with_child(when_selected(
+            |object| matches!(object, Object::Wall(_)),
+            wall_inspector(),
+        ))
+        .with_child(when_selected(
+            |object| matches!(object, Object::Microphone(_)),
+            microphone_inspector(),
+        ))
+        .with_child(when_selected(
+            |object| matches!(object, Object::Speaker(_)),
+            speaker_inspector(),
+        ));
+    Either::new(
+        |data: &SpaceEditorProjectData, _env| selected_object(data).is_some(),
+        panel(contents),
+        SizedBox::empty(),
+    )
+}
+
+/// Returns the panel for adjusting the background image, shown when there is one.
+pub fn background_panel() -> impl Widget<SpaceEditorProjectData> {
+    Either::new(
+        |data: &SpaceEditorProjectData, _env| data.background.is_some(),
+        panel(background_fields()),
+        SizedBox::empty(),
+    )
+}
+
+pub mod style {
+    use druid::{Color, Env, Key};
+
+    use crate::style::color;
+
+    pub const BACKGROUND: Key<Color> = style_key!("inspector.background");
+    pub const PADDING: Key<f64> = style_key!("inspector.padding");
+    pub const CORNER_RADIUS: Key<f64> = style_key!("inspector.corner-radius");
+
+    pub const LABEL_WIDTH: Key<f64> = style_key!("inspector.label-width");
+    pub const FIELD_WIDTH: Key<f64> = style_key!("inspector.field-width");
+    pub const UNIT_SPACING: Key<f64> = style_key!("inspector.unit-spacing");
+    pub const MATERIAL_SPACING: Key<f64> = style_key!("inspector.material-spacing");
+
+    pub fn configure_env(env: &mut Env) {
+        env.set(BACKGROUND, color(0xFFFFFF));
+        env.set(PADDING, 12.0);
+        env.set(CORNER_RADIUS, 8.0);
+
+        env.set(LABEL_WIDTH, 80.0);
+        env.set(FIELD_WIDTH, 80.0);
+        env.set(UNIT_SPACING, 4.0);
+        env.set(MATERIAL_SPACING, 4.0);
+    }
+}
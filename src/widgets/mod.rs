@@ -1,5 +1,15 @@
 pub mod button;
+pub mod export_panel;
+pub mod import_panel;
+pub mod inspector;
+pub mod problems;
+pub mod render_settings;
 pub mod space_editor;
 
 pub use button::*;
+pub use export_panel::*;
+pub use import_panel::*;
+pub use inspector::*;
+pub use problems::*;
+pub use render_settings::*;
 pub use space_editor::*;
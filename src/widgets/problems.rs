@@ -0,0 +1,68 @@
+//! Dialog listing problems found while loading a project, or while importing and exporting files.
+
+use druid::{
+    im::Vector,
+    widget::{CrossAxisAlignment, Either, Flex, Label, LineBreaking, List, SizedBox},
+    Env, Widget, WidgetExt,
+};
+
+use crate::widgets::Button;
+
+/// Returns a dialog listing the given problems, shown while there are any. Dismissing the dialog
+/// clears the list.
+pub fn problems_dialog() -> impl Widget<Vector<String>> {
+    let list = List::new(|| {
+        Label::new(|problem: &String, _env: &Env| problem.clone())
+            .with_line_break_mode(LineBreaking::WordWrap)
+            .fix_width(style::WIDTH)
+    })
+    .with_spacing(style::SPACING)
+    .scroll()
+    .vertical()
+    .fix_height(style::LIST_HEIGHT);
+    let dialog = Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(
+            Label::new("There were problems with some files")
+                .with_line_break_mode(LineBreaking::WordWrap)
+                .fix_width(style::WIDTH),
+        )
+        .with_default_spacer()
+        .with_child(list)
+        .with_default_spacer()
+        .with_child(
+            Button::new("OK").on_click(|_ctx, problems: &mut Vector<String>, _env| {
+                problems.clear();
+            }),
+        )
+        .padding(style::PADDING)
+        .background(style::BACKGROUND)
+        .rounded(style::CORNER_RADIUS);
+    Either::new(
+        |problems: &Vector<String>, _env| !problems.is_empty(),
+        dialog,
+        SizedBox::empty(),
+    )
+}
+
+pub mod style {
+    use druid::{Color, Env, Key};
+
+    use crate::style::color;
+
+    pub const BACKGROUND: Key<Color> = style_key!("problems.background");
+    pub const PADDING: Key<f64> = style_key!("problems.padding");
+    pub const CORNER_RADIUS: Key<f64> = style_key!("problems.corner-radius");
+    pub const WIDTH: Key<f64> = style_key!("problems.width");
+    pub const LIST_HEIGHT: Key<f64> = style_key!("problems.list-height");
+    pub const SPACING: Key<f64> = style_key!("problems.spacing");
+
+    pub fn configure_env(env: &mut Env) {
+        env.set(BACKGROUND, color(0xFFFFFF));
+        env.set(PADDING, 16.0);
+        env.set(CORNER_RADIUS, 8.0);
+        env.set(WIDTH, 400.0);
+        env.set(LIST_HEIGHT, 200.0);
+        env.set(SPACING, 8.0);
+    }
+}
@@ -0,0 +1,119 @@
+//! Panel for adjusting how impulse responses are rendered.
+
+use druid::{
+    lens::Map,
+    widget::{CrossAxisAlignment, Either, Flex, Label, SizedBox},
+    Lens, Widget, WidgetExt,
+};
+
+use crate::{
+    rendering::RenderSettings,
+    widgets::{
+        inspector::{field, number_box, style},
+        Button,
+    },
+};
+
+/// The radius receivers start out with when switching to volumetric receivers, in metres.
+const DEFAULT_RECEIVER_RADIUS: f32 = 0.25;
+
+/// Lens for the radius of volumetric receivers. Like the inspector's lenses, it yields a default
+/// value and discards changes when receivers aren't volumetric.
+fn receiver_radius() -> impl Lens<RenderSettings, f32> {
+    Map::new(
+        |settings: &RenderSettings| settings.receiver_radius.unwrap_or(DEFAULT_RECEIVER_RADIUS),
+        |settings: &mut RenderSettings, radius: f32| {
+            if settings.receiver_radius.is_some() {
+                settings.receiver_radius = Some(radius);
+            }
+        },
+    )
+}
+
+/// Lens for the energy threshold in decibels relative to the rays' initial level. The threshold
+/// scales responses as an amplitude, hence the factor of 20. Levels above 0 dB are clamped.
+fn energy_threshold_db() -> impl Lens<RenderSettings, f32> {
+    Map::new(
+        |settings: &RenderSettings| 20.0 * settings.energy_threshold.log10(),
+        |settings: &mut RenderSettings, level: f32| {
+            settings.energy_threshold = 10f32.powf(level.min(0.0) / 20.0);
+        },
+    )
+}
+
+/// Returns a button switching the setting focused on by `setting` on and off.
+fn toggle(
+    label: &str,
+    setting: impl Lens<RenderSettings, bool> + 'static,
+) -> impl Widget<RenderSettings> {
+    Button::new(label)
+        .checked(|enabled: &bool| *enabled)
+        .on_click(|_ctx, enabled: &mut bool, _env| *enabled = !*enabled)
+        .lens(setting)
+}
+
+fn detection_fields() -> impl Widget<RenderSettings> {
+    Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(
+            Button::new("Volumetric receivers")
+                .checked(|settings: &RenderSettings| settings.receiver_radius.is_some())
+                .on_click(|_ctx, settings: &mut RenderSettings, _env| {
+                    settings.receiver_radius = match settings.receiver_radius {
+                        Some(_) => None,
+                        None => Some(DEFAULT_RECEIVER_RADIUS),
+                    };
+                }),
+        )
+        .with_default_spacer()
+        .with_child(Either::new(
+            |settings: &RenderSettings, _env| settings.receiver_radius.is_some(),
+            field(
+                "Radius",
+                Label::new("m"),
+                number_box(2).lens(receiver_radius()),
+            ),
+            SizedBox::empty(),
+        ))
+}
+
+/// Returns the panel for adjusting the render settings.
+pub fn render_settings_panel() -> impl Widget<RenderSettings> {
+    Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(Label::new("Render settings"))
+        .with_default_spacer()
+        .with_child(detection_fields())
+        .with_default_spacer()
+        .with_child(field(
+            "Ray cutoff",
+            Label::new("dB"),
+            number_box(1).lens(energy_threshold_db()),
+        ))
+        .with_default_spacer()
+        .with_child(toggle(
+            "Trace from speaker",
+            RenderSettings::trace_from_speaker,
+        ))
+        .with_default_spacer()
+        .with_child(field(
+            "Length",
+            Label::new("s"),
+            number_box(1).lens(RenderSettings::max_ir_seconds),
+        ))
+        .with_default_spacer()
+        .with_child(toggle("Trim silence", RenderSettings::trim_silence))
+        .with_default_spacer()
+        .with_child(field(
+            "Fade out",
+            Label::new("s"),
+            number_box(2).lens(RenderSettings::fade_out_seconds),
+        ))
+        .with_default_spacer()
+        .with_child(toggle("Direct sound", RenderSettings::include_direct_sound))
+        .with_default_spacer()
+        .with_child(toggle("Diffraction", RenderSettings::diffraction))
+        .padding(style::PADDING)
+        .background(style::BACKGROUND)
+        .rounded(style::CORNER_RADIUS)
+}
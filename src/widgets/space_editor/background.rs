@@ -0,0 +1,142 @@
+//! Reference image painted beneath the space, for tracing over existing floor plans.
+
+use std::path::{Path, PathBuf};
+
+use druid::{
+    piet::{Image as _, ImageBuf, InterpolationMode, PietImage},
+    Affine, Data, Env, PaintCtx, Point, RenderContext, Size,
+};
+use serde::{Deserialize, Serialize};
+use tracing::error;
+
+use super::{style, SpaceEditorProjectData};
+
+/// File extensions of images that can be used as backgrounds.
+pub const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg"];
+
+#[derive(Debug, Clone, Default, Data, Deserialize, Serialize)]
+pub struct BackgroundImage {
+    #[data(same_fn = "PartialEq::eq")]
+    pub path: PathBuf,
+    /// The position of the image's top left corner.
+    pub position: Point,
+    /// The size of a single pixel of the image, in space units.
+    pub scale: f64,
+    /// The rotation of the image around its top left corner, in radians.
+    pub rotation: f64,
+    pub opacity: f64,
+    /// The known length of the line measured by the calibration tool.
+    #[serde(default = "BackgroundImage::default_calibration_length")]
+    pub calibration_length: f64,
+}
+
+impl BackgroundImage {
+    /// Creates a background image centered at `center`, spanning `width` space units.
+    pub fn new(path: PathBuf, image_size: Size, center: Point, width: f64) -> Self {
+        let scale = width / image_size.width.max(1.0);
+        Self {
+            path,
+            position: center - image_size.to_vec2() * scale / 2.0,
+            scale,
+            rotation: 0.0,
+            opacity: 0.5,
+            calibration_length: Self::default_calibration_length(),
+        }
+    }
+
+    fn default_calibration_length() -> f64 {
+        1.0
+    }
+
+    /// Returns the transform from image pixels to space coordinates.
+    pub fn affine(&self) -> Affine {
+        Affine::translate(self.position.to_vec2())
+            * Affine::rotate(self.rotation)
+            * Affine::scale(self.scale)
+    }
+
+    /// Rescales the image such that the distance between `start` and `end` becomes
+    /// [`calibration_length`][Self::calibration_length]. The point under `start` stays in place.
+    pub fn calibrate(&mut self, start: Point, end: Point) {
+        let measured = start.distance(end);
+        if measured <= 0.0 || self.calibration_length <= 0.0 {
+            return;
+        }
+        let pixel = self.affine().inverse() * start;
+        self.scale *= self.calibration_length / measured;
+        let moved = self.affine() * pixel;
+        self.position += start - moved;
+    }
+}
+
+/// Returns whether the file at the path looks like it can be used as a background.
+pub fn is_image(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .map_or(false, |extension| {
+            IMAGE_EXTENSIONS
+                .iter()
+                .any(|e| e.eq_ignore_ascii_case(extension))
+        })
+}
+
+pub fn load(path: &Path) -> Option<ImageBuf> {
+    match ImageBuf::from_file(path) {
+        Ok(image) => Some(image),
+        Err(error) => {
+            error!(%error, ?path, "cannot load background image");
+            None
+        }
+    }
+}
+
+/// Paints the project's background image, keeping it loaded for as long as its path doesn't
+/// change.
+pub struct BackgroundPainter {
+    path: Option<PathBuf>,
+    buffer: Option<ImageBuf>,
+    image: Option<PietImage>,
+}
+
+impl BackgroundPainter {
+    pub fn new() -> Self {
+        Self {
+            path: None,
+            buffer: None,
+            image: None,
+        }
+    }
+
+    pub fn paint(&mut self, ctx: &mut PaintCtx, data: &SpaceEditorProjectData, env: &Env) {
+        let background = match &data.background {
+            Some(background) => background,
+            None => return,
+        };
+
+        if self.path.as_ref() != Some(&background.path) {
+            self.path = Some(background.path.clone());
+            self.buffer = load(&background.path);
+            self.image = None;
+        }
+        let buffer = match &self.buffer {
+            Some(buffer) => buffer,
+            None => return,
+        };
+        let image = self
+            .image
+            .get_or_insert_with(|| buffer.to_image(ctx.render_ctx));
+        let bounds = image.size().to_rect();
+
+        let transform = data.transform.to_screen_affine(ctx.size()) * background.affine();
+        ctx.with_save(|ctx| {
+            ctx.transform(transform);
+            ctx.draw_image(image, bounds, InterpolationMode::Bilinear);
+            // piet can't draw images with transparency, but since the image is painted directly
+            // onto the editor's background, fading it into that color has the same effect.
+            let fade = env
+                .get(style::BACKGROUND)
+                .with_alpha(1.0 - background.opacity.clamp(0.0, 1.0));
+            ctx.fill(bounds, &fade);
+        });
+    }
+}
@@ -0,0 +1,111 @@
+//! Copying and pasting objects through the system clipboard.
+//!
+//! Objects are stored on the clipboard as an [`EditableSpace`] in the same JSON representation as
+//! in project files, which makes it possible to copy objects between different windows.
+
+use druid::{im::HashSet, Application, Point, Vec2};
+use tracing::{error, info};
+
+use super::{
+    data::{EditableSpace, Object},
+    SpaceEditorProjectData,
+};
+use crate::sparse_set::Id;
+
+/// Returns a space containing only the selected objects.
+fn selected_fragment(data: &SpaceEditorProjectData) -> EditableSpace {
+    let mut fragment = EditableSpace::new();
+    for &id in &data.selection {
+        if let Some(object) = data.space.objects.get(id) {
+            fragment.objects.insert(object.clone());
+        }
+    }
+    fragment
+}
+
+/// Returns the average of the centers of all objects in the fragment, or `None` if it's empty.
+fn centroid(fragment: &EditableSpace) -> Option<Point> {
+    let count = fragment.objects.iter().count();
+    if count == 0 {
+        return None;
+    }
+    let sum = fragment
+        .objects
+        .iter()
+        .fold(Vec2::ZERO, |sum, object| sum + object.center().to_vec2());
+    Some((sum / count as f64).to_point())
+}
+
+/// Inserts all objects from the fragment into the space, such that their centroid lies at
+/// `position`, and selects them.
+fn insert_fragment(data: &mut SpaceEditorProjectData, fragment: &EditableSpace, position: Point) {
+    let centroid = match centroid(fragment) {
+        Some(centroid) => centroid,
+        None => return,
+    };
+    let offset = position - centroid;
+
+    let space = data.edit_space();
+    let inserted: HashSet<Id<Object>> = fragment
+        .objects
+        .iter()
+        .map(|object| {
+            let mut object = object.clone();
+            object.translate(offset);
+            space.objects.insert(object)
+        })
+        .collect();
+    info!(count = inserted.len(), "paste");
+    data.selection = inserted;
+}
+
+/// Copies the selected objects to the clipboard. Returns whether anything was copied.
+pub fn copy(data: &SpaceEditorProjectData) -> bool {
+    if data.selection.is_empty() {
+        return false;
+    }
+    match serde_json::to_string(&selected_fragment(data)) {
+        Ok(json) => {
+            Application::global().clipboard().put_string(json);
+            true
+        }
+        Err(error) => {
+            error!(%error, "cannot serialize copied objects");
+            false
+        }
+    }
+}
+
+/// Copies the selected objects to the clipboard and removes them from the space.
+pub fn cut(data: &mut SpaceEditorProjectData) {
+    if copy(data) {
+        let selection = std::mem::take(&mut data.selection);
+        let space = data.edit_space();
+        for &id in &selection {
+            space.objects.remove(id);
+        }
+    }
+}
+
+/// Pastes objects from the clipboard, centered at the given position.
+pub fn paste(data: &mut SpaceEditorProjectData, position: Point) {
+    let json = match Application::global().clipboard().get_string() {
+        Some(json) => json,
+        None => return,
+    };
+    match serde_json::from_str::<EditableSpace>(&json) {
+        Ok(fragment) => insert_fragment(data, &fragment, position),
+        // The clipboard may contain arbitrary text from other applications, so this is not
+        // considered an error.
+        Err(error) => info!(%error, "clipboard does not contain objects"),
+    }
+}
+
+/// Duplicates the selected objects without going through the clipboard, placing the copies
+/// `offset` away from the originals.
+pub fn duplicate(data: &mut SpaceEditorProjectData, offset: Vec2) {
+    let fragment = selected_fragment(data);
+    if let Some(centroid) = centroid(&fragment) {
+        insert_fragment(data, &fragment, centroid + offset);
+    }
+}
@@ -1,29 +1,373 @@
-use druid::Data;
+use std::f64::consts::TAU;
+
+use druid::{
+    im::Vector,
+    kurbo::{self, BezPath, CubicBez, ParamCurve, PathEl, QuadBez, Shape},
+    Affine, Data, Point, Rect, Vec2,
+};
 use fizzerb_model as model;
 use model::Space;
 use serde::{Deserialize, Serialize};
 
-use crate::{math::DruidExtToGlam, sparse_set::SparseSet};
+use super::units::Unit;
+use crate::{
+    math::{DruidExtToGlam, GlamExtToDruid, PointExtHitTests},
+    sparse_set::SparseSet,
+};
+
+/// The accuracy with which curved walls are approximated by line segments, for painting and hit
+/// testing.
+const FLATTENING_TOLERANCE: f64 = 0.01;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Data, Deserialize, Serialize)]
-pub struct MaterialIndex(usize);
+pub struct MaterialIndex(pub usize);
 
-#[derive(Debug, Clone, PartialEq, Data, Deserialize, Serialize)]
+impl MaterialIndex {
+    pub fn to_model(self) -> model::MaterialIndex {
+        model::MaterialIndex(self.0)
+    }
+}
+
+/// The shape of a wall's curve. See [`model::WallShape`].
+#[derive(Debug, Clone, Copy, PartialEq, Default, Data, Deserialize, Serialize)]
+pub enum WallShape {
+    #[default]
+    Line,
+    Arc {
+        sagitta: f64,
+    },
+    QuadraticBezier {
+        control: druid::Point,
+    },
+    CubicBezier {
+        control_start: druid::Point,
+        control_end: druid::Point,
+    },
+}
+
+impl WallShape {
+    pub fn from_model(shape: model::WallShape) -> Self {
+        match shape {
+            model::WallShape::Line => WallShape::Line,
+            model::WallShape::Arc { sagitta } => WallShape::Arc {
+                sagitta: sagitta as f64,
+            },
+            model::WallShape::QuadraticBezier { control } => WallShape::QuadraticBezier {
+                control: control.to_druid(),
+            },
+            model::WallShape::CubicBezier {
+                control_start,
+                control_end,
+            } => WallShape::CubicBezier {
+                control_start: control_start.to_druid(),
+                control_end: control_end.to_druid(),
+            },
+        }
+    }
+
+    /// Scales all lengths of the shape by the given factor, relative to the origin.
+    pub fn scale(self, factor: f64) -> Self {
+        let scale_point = |point: Point| (point.to_vec2() * factor).to_point();
+        match self {
+            WallShape::Line => WallShape::Line,
+            WallShape::Arc { sagitta } => WallShape::Arc {
+                sagitta: sagitta * factor,
+            },
+            WallShape::QuadraticBezier { control } => WallShape::QuadraticBezier {
+                control: scale_point(control),
+            },
+            WallShape::CubicBezier {
+                control_start,
+                control_end,
+            } => WallShape::CubicBezier {
+                control_start: scale_point(control_start),
+                control_end: scale_point(control_end),
+            },
+        }
+    }
+
+    pub fn to_model(self) -> model::WallShape {
+        match self {
+            WallShape::Line => model::WallShape::Line,
+            WallShape::Arc { sagitta } => model::WallShape::Arc {
+                sagitta: sagitta as f32,
+            },
+            WallShape::QuadraticBezier { control } => model::WallShape::QuadraticBezier {
+                control: control.to_glam(),
+            },
+            WallShape::CubicBezier {
+                control_start,
+                control_end,
+            } => model::WallShape::CubicBezier {
+                control_start: control_start.to_glam(),
+                control_end: control_end.to_glam(),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default, Data, Deserialize, Serialize)]
 pub struct Wall {
     pub start: druid::Point,
     pub end: druid::Point,
+    #[serde(default)]
+    pub shape: WallShape,
+    #[serde(default)]
     pub material: MaterialIndex,
+    /// The material of the wall's back side, if it differs from the front's. See
+    /// [`model::Side`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub back_material: Option<MaterialIndex>,
 }
 
-#[derive(Debug, Clone, PartialEq, Data, Deserialize, Serialize)]
+impl Wall {
+    pub fn from_model(wall: &model::Wall) -> Self {
+        Self {
+            start: wall.start.to_druid(),
+            end: wall.end.to_druid(),
+            shape: WallShape::from_model(wall.shape),
+            material: MaterialIndex(wall.material.0),
+            back_material: wall.back_material.map(|material| MaterialIndex(material.0)),
+        }
+    }
+
+    /// Returns the normal vector of the straight line between this wall's endpoints.
+    pub fn normal(&self) -> Vec2 {
+        let direction = (self.end - self.start).normalize();
+        Vec2::new(-direction.y, direction.x)
+    }
+
+    /// Returns the arc this wall is shaped like, or `None` if it's not a (non-degenerate) arc.
+    fn arc(&self) -> Option<kurbo::Arc> {
+        if let WallShape::Arc { sagitta } = self.shape {
+            let half_chord = self.start.distance(self.end) * 0.5;
+            if sagitta == 0.0 || half_chord == 0.0 {
+                return None;
+            }
+            let normal = self.normal();
+            let radius = (half_chord * half_chord + sagitta * sagitta) / (2.0 * sagitta.abs());
+            let apex = self.start.midpoint(self.end) + normal * sagitta;
+            let center = apex - normal * sagitta.signum() * radius;
+
+            let start_angle = (self.start - center).atan2();
+            let end_angle = (self.end - center).atan2();
+            let apex_angle = (apex - center).atan2();
+            let positive_sweep = (end_angle - start_angle).rem_euclid(TAU);
+            let sweep_angle = if (apex_angle - start_angle).rem_euclid(TAU) <= positive_sweep {
+                positive_sweep
+            } else {
+                positive_sweep - TAU
+            };
+            Some(kurbo::Arc {
+                center,
+                radii: Vec2::new(radius, radius),
+                start_angle,
+                sweep_angle,
+                x_rotation: 0.0,
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Returns the points which control the wall's curve (excluding its endpoints.)
+    ///
+    /// For arcs, this is the middle point of the arc.
+    pub fn control_points(&self) -> Vec<Point> {
+        match self.shape {
+            WallShape::Line => vec![],
+            WallShape::Arc { sagitta } => {
+                vec![self.start.midpoint(self.end) + self.normal() * sagitta]
+            }
+            WallShape::QuadraticBezier { control } => vec![control],
+            WallShape::CubicBezier {
+                control_start,
+                control_end,
+            } => vec![control_start, control_end],
+        }
+    }
+
+    /// Returns the point halfway along the wall's curve.
+    pub fn midpoint(&self) -> Point {
+        match self.shape {
+            WallShape::Line => self.start.midpoint(self.end),
+            WallShape::Arc { sagitta } => self.start.midpoint(self.end) + self.normal() * sagitta,
+            WallShape::QuadraticBezier { control } => {
+                QuadBez::new(self.start, control, self.end).eval(0.5)
+            }
+            WallShape::CubicBezier {
+                control_start,
+                control_end,
+            } => CubicBez::new(self.start, control_start, control_end, self.end).eval(0.5),
+        }
+    }
+
+    /// Moves the control point with the given index (as returned by [`Wall::control_points`]) to
+    /// the given position.
+    pub fn set_control_point(&mut self, index: usize, position: Point) {
+        let normal = self.normal();
+        let midpoint = self.start.midpoint(self.end);
+        match (&mut self.shape, index) {
+            (WallShape::Arc { sagitta }, 0) => *sagitta = (position - midpoint).dot(normal),
+            (WallShape::QuadraticBezier { control }, 0) => *control = position,
+            (WallShape::CubicBezier { control_start, .. }, 0) => *control_start = position,
+            (WallShape::CubicBezier { control_end, .. }, 1) => *control_end = position,
+            _ => (),
+        }
+    }
+
+    /// Moves the entire wall, including its control points, by the given amount.
+    pub fn translate(&mut self, delta: Vec2) {
+        self.start += delta;
+        self.end += delta;
+        match &mut self.shape {
+            WallShape::Line | WallShape::Arc { .. } => (),
+            WallShape::QuadraticBezier { control } => *control += delta,
+            WallShape::CubicBezier {
+                control_start,
+                control_end,
+            } => {
+                *control_start += delta;
+                *control_end += delta;
+            }
+        }
+    }
+
+    /// Applies the given transform to the wall.
+    ///
+    /// Arcs stay arcs, so under non-uniform scaling only their endpoints and middle points are
+    /// transformed exactly.
+    pub fn transform(&mut self, affine: Affine) {
+        let control_points = self.control_points();
+        self.start = affine * self.start;
+        self.end = affine * self.end;
+        for (index, point) in control_points.into_iter().enumerate() {
+            self.set_control_point(index, affine * point);
+        }
+        // Mirroring flips the wall's normal, so its sides swap places.
+        if affine.determinant() < 0.0 {
+            if let Some(back_material) = self.back_material {
+                self.back_material = Some(self.material);
+                self.material = back_material;
+            }
+        }
+    }
+
+    /// Returns the path of the wall's curve.
+    pub fn to_path(&self) -> BezPath {
+        let mut path = BezPath::new();
+        path.move_to(self.start);
+        match self.shape {
+            WallShape::Arc { .. } => {
+                if let Some(arc) = self.arc() {
+                    path.extend(arc.append_iter(FLATTENING_TOLERANCE));
+                } else {
+                    path.line_to(self.end);
+                }
+            }
+            WallShape::Line => path.line_to(self.end),
+            WallShape::QuadraticBezier { control } => path.quad_to(control, self.end),
+            WallShape::CubicBezier {
+                control_start,
+                control_end,
+            } => path.curve_to(control_start, control_end, self.end),
+        }
+        path
+    }
+
+    /// Returns whether the given point lies within `distance` of the wall's curve.
+    pub fn is_near(&self, point: Point, distance: f64) -> bool {
+        if let WallShape::Line = self.shape {
+            return point.near_line(self.start, self.end, distance);
+        }
+
+        let mut is_near = false;
+        let mut previous = self.start;
+        self.to_path()
+            .flatten(FLATTENING_TOLERANCE, |element| match element {
+                PathEl::MoveTo(p) => previous = p,
+                PathEl::LineTo(p) => {
+                    is_near |= point.near_line(previous, p, distance);
+                    previous = p;
+                }
+                _ => (),
+            });
+        is_near
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default, Data, Deserialize, Serialize)]
 pub struct Microphone {
     pub position: druid::Point,
 }
 
-#[derive(Debug, Clone, PartialEq, Data, Deserialize, Serialize)]
+/// See [`model::Directivity`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Data, Deserialize, Serialize)]
+pub enum Directivity {
+    #[default]
+    Omnidirectional,
+    Cardioid,
+    Supercardioid,
+    Bidirectional,
+}
+
+impl Directivity {
+    pub const ALL: [Directivity; 4] = [
+        Directivity::Omnidirectional,
+        Directivity::Cardioid,
+        Directivity::Supercardioid,
+        Directivity::Bidirectional,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Directivity::Omnidirectional => "Omni",
+            Directivity::Cardioid => "Cardioid",
+            Directivity::Supercardioid => "Supercardioid",
+            Directivity::Bidirectional => "Figure-8",
+        }
+    }
+
+    pub fn to_model(self) -> model::Directivity {
+        match self {
+            Directivity::Omnidirectional => model::Directivity::Omnidirectional,
+            Directivity::Cardioid => model::Directivity::Cardioid,
+            Directivity::Supercardioid => model::Directivity::Supercardioid,
+            Directivity::Bidirectional => model::Directivity::Bidirectional,
+        }
+    }
+
+    /// Returns the polar plot of the directivity pattern, for a speaker at `center` facing
+    /// `angle`. Directions with full gain reach `radius` away from the center.
+    pub fn outline(self, center: Point, angle: f64, radius: f64) -> BezPath {
+        const SEGMENTS: usize = 72;
+
+        let pattern = self.to_model();
+        let mut path = BezPath::new();
+        for i in 0..SEGMENTS {
+            let offset = TAU * i as f64 / SEGMENTS as f64;
+            let gain = pattern.gain(offset.cos() as f32) as f64;
+            let point = center + Vec2::from_angle(angle + offset) * radius * gain;
+            if i == 0 {
+                path.move_to(point);
+            } else {
+                path.line_to(point);
+            }
+        }
+        path.close_path();
+        path
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default, Data, Deserialize, Serialize)]
 pub struct Speaker {
     pub position: druid::Point,
     pub power: f32,
+    #[serde(default)]
+    pub directivity: Directivity,
+    /// The direction the speaker is facing, in radians.
+    #[serde(default)]
+    pub angle: f64,
 }
 
 #[derive(Debug, Clone, PartialEq, Data, Deserialize, Serialize)]
@@ -33,45 +377,157 @@ pub enum Object {
     Speaker(Speaker),
 }
 
+impl Object {
+    /// Moves the object by the given amount.
+    pub fn translate(&mut self, delta: Vec2) {
+        match self {
+            Object::Wall(wall) => wall.translate(delta),
+            Object::Microphone(Microphone { position })
+            | Object::Speaker(Speaker { position, .. }) => *position += delta,
+        }
+    }
+
+    /// Applies the given transform to the object.
+    pub fn transform(&mut self, affine: Affine) {
+        match self {
+            Object::Wall(wall) => wall.transform(affine),
+            Object::Microphone(Microphone { position }) => *position = affine * *position,
+            Object::Speaker(speaker) => {
+                // Speakers keep facing the same way relative to the rest of the space.
+                let facing = speaker.position + Vec2::from_angle(speaker.angle);
+                speaker.position = affine * speaker.position;
+                speaker.angle = (affine * facing - speaker.position).atan2();
+            }
+        }
+    }
+
+    /// Returns the point the object is centered around.
+    pub fn center(&self) -> Point {
+        match self {
+            Object::Wall(wall) => wall.midpoint(),
+            Object::Microphone(Microphone { position })
+            | Object::Speaker(Speaker { position, .. }) => *position,
+        }
+    }
+
+    /// Returns the smallest rectangle containing the object's shape.
+    pub fn bounding_box(&self) -> Rect {
+        match self {
+            Object::Wall(wall) => wall.to_path().bounding_box(),
+            Object::Microphone(Microphone { position })
+            | Object::Speaker(Speaker { position, .. }) => Rect::from_points(*position, *position),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Data, Deserialize, Serialize)]
+pub struct Material {
+    pub name: String,
+    /// See [`model::Material::diffuse`].
+    pub diffuse: f32,
+    /// See [`model::Material::roughness`].
+    pub roughness: f32,
+    /// See [`model::Material::transmission`].
+    #[serde(default)]
+    pub transmission: f32,
+}
+
+impl Material {
+    fn new(name: &str, diffuse: f32, roughness: f32, transmission: f32) -> Self {
+        Self {
+            name: name.to_owned(),
+            diffuse,
+            roughness,
+            transmission,
+        }
+    }
+
+    /// Returns the materials new spaces start out with.
+    pub fn presets() -> Vector<Material> {
+        Vector::from(vec![
+            Material::new("Default", 1.0, 0.0, 0.0),
+            Material::new("Concrete", 0.98, 0.05, 0.0),
+            Material::new("Brick", 0.97, 0.2, 0.0),
+            Material::new("Wood", 0.9, 0.1, 0.0),
+            Material::new("Glass", 0.96, 0.0, 0.0),
+            Material::new("Carpet", 0.7, 0.6, 0.0),
+            Material::new("Curtain", 0.5, 0.8, 0.0),
+            Material::new("Drywall", 0.95, 0.05, 0.1),
+        ])
+    }
+}
+
 #[derive(Debug, Clone, Data, Deserialize, Serialize)]
 pub struct EditableSpace {
     #[data(same_fn = "PartialEq::eq")]
     pub objects: SparseSet<Object>,
+    /// The materials walls can be made of, indexed by [`MaterialIndex`].
+    #[serde(default = "Material::presets")]
+    pub materials: Vector<Material>,
+    /// The unit all coordinates in the space are expressed in.
+    #[serde(default)]
+    pub unit: Unit,
 }
 
 impl EditableSpace {
     pub fn new() -> Self {
         Self {
             objects: SparseSet::new(),
+            materials: Material::presets(),
+            unit: Unit::default(),
         }
     }
 
+    /// Returns the material with the given index, if it exists.
+    pub fn material(&self, index: MaterialIndex) -> Option<&Material> {
+        self.materials.get(index.0)
+    }
+
+    /// Converts the space to a model for the tracer, whose coordinates are always in metres.
     pub fn to_model(&self) -> Space {
         let mut space = Space::new();
 
-        let default_material = space.add_material(model::Material {
-            diffuse: 1.0,
-            roughness: 0.0,
-        });
+        let scale = self.unit.to_metres();
+        let to_metres = |point: Point| (point.to_vec2() * scale).to_point().to_glam();
+
+        let materials: Vec<_> = self
+            .materials
+            .iter()
+            .map(|material| {
+                space.add_material(model::Material {
+                    diffuse: material.diffuse,
+                    roughness: material.roughness,
+                    transmission: material.transmission.clamp(0.0, 1.0),
+                })
+            })
+            .collect();
+        // Walls referring to materials that don't exist fall back to the default material.
+        let default_material = space.add_material(model::Material::default());
+        let material =
+            |index: MaterialIndex| materials.get(index.0).copied().unwrap_or(default_material);
 
         for object in &self.objects {
             match object {
                 Object::Wall(wall) => {
                     space.add_wall(model::Wall {
-                        start: wall.start.to_glam(),
-                        end: wall.end.to_glam(),
-                        material: default_material,
+                        start: to_metres(wall.start),
+                        end: to_metres(wall.end),
+                        shape: wall.shape.scale(scale).to_model(),
+                        material: material(wall.material),
+                        back_material: wall.back_material.map(material),
                     });
                 }
                 Object::Microphone(microphone) => {
                     space.add_microphone(model::Microphone {
-                        position: microphone.position.to_glam(),
+                        position: to_metres(microphone.position),
                     });
                 }
                 Object::Speaker(speaker) => {
                     space.add_speaker(model::Speaker {
-                        position: speaker.position.to_glam(),
+                        position: to_metres(speaker.position),
                         power: speaker.power,
+                        directivity: speaker.directivity.to_model(),
+                        direction: Vec2::from_angle(speaker.angle).to_glam(),
                     });
                 }
             }
@@ -0,0 +1,76 @@
+//! Undo/redo history of space edits.
+
+use std::{collections::VecDeque, sync::Arc};
+
+use super::data::EditableSpace;
+
+/// The maximum number of steps that can be undone.
+const MAX_UNDO_STEPS: usize = 256;
+
+/// A history of snapshots of the edited space.
+///
+/// Since all edits go through [`Arc::make_mut`], snapshots share all data that was not modified
+/// between them, so keeping many of them around is cheap.
+pub struct History {
+    undo_stack: VecDeque<Arc<EditableSpace>>,
+    redo_stack: Vec<Arc<EditableSpace>>,
+    gesture: Gesture,
+}
+
+/// Used for coalescing all changes made during a single mouse gesture (such as dragging an
+/// object around) into a single step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Gesture {
+    None,
+    Started,
+    Recorded,
+}
+
+impl History {
+    pub fn new() -> Self {
+        Self {
+            undo_stack: VecDeque::new(),
+            redo_stack: Vec::new(),
+            gesture: Gesture::None,
+        }
+    }
+
+    /// Records the state of the space before a change was made to it.
+    pub fn record(&mut self, previous: Arc<EditableSpace>) {
+        match self.gesture {
+            Gesture::Recorded => return,
+            Gesture::Started => self.gesture = Gesture::Recorded,
+            Gesture::None => (),
+        }
+
+        self.undo_stack.push_back(previous);
+        if self.undo_stack.len() > MAX_UNDO_STEPS {
+            self.undo_stack.pop_front();
+        }
+        self.redo_stack.clear();
+    }
+
+    /// Starts a gesture. Until the gesture ends, only the first change is recorded.
+    pub fn begin_gesture(&mut self) {
+        self.gesture = Gesture::Started;
+    }
+
+    pub fn end_gesture(&mut self) {
+        self.gesture = Gesture::None;
+    }
+
+    /// Returns the state of the space before the last change, or `None` if there's nothing to
+    /// undo.
+    pub fn undo(&mut self, current: Arc<EditableSpace>) -> Option<Arc<EditableSpace>> {
+        let previous = self.undo_stack.pop_back()?;
+        self.redo_stack.push(current);
+        Some(previous)
+    }
+
+    /// Returns the state of the space before the last undo, or `None` if there's nothing to redo.
+    pub fn redo(&mut self, current: Arc<EditableSpace>) -> Option<Arc<EditableSpace>> {
+        let next = self.redo_stack.pop()?;
+        self.undo_stack.push_back(current);
+        Some(next)
+    }
+}
@@ -1,29 +1,49 @@
+pub mod background;
+mod clipboard;
 pub mod data;
+mod history;
+pub mod snapping;
 pub mod style;
 pub mod tool;
 pub mod transform;
+pub mod units;
 
-use std::sync::Arc;
+use std::{path::Path, sync::Arc};
 
 use druid::{
+    im::HashSet,
     kurbo::{Circle, Line},
     piet::{LineCap, StrokeStyle},
-    Affine, BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, LifeCycle, LifeCycleCtx,
-    PaintCtx, Point, RenderContext, Size, UpdateCtx, Widget,
+    BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, LifeCycle, LifeCycleCtx, PaintCtx,
+    Point, RenderContext, Size, UpdateCtx, Vec2, Widget,
 };
-use serde::{Deserialize, Serialize};
 
 use self::{
-    data::{EditableSpace, Object},
+    background::{BackgroundImage, BackgroundPainter},
+    data::{Directivity, EditableSpace, Object},
+    history::History,
+    snapping::Snapping,
     tool::{Tool, ToolImpl},
     transform::Transform,
 };
+use crate::{
+    commands,
+    import::{self, ImportError, ImportOptions},
+    sparse_set::Id,
+};
 
-#[derive(Clone, Data, Deserialize, Serialize)]
+/// The space editor's part of the project. See [`crate::project`] for how it's saved.
+#[derive(Clone, Data)]
 pub struct SpaceEditorProjectData {
     pub space: Arc<EditableSpace>,
     pub transform: Transform,
     pub tool: Tool,
+    pub snapping: Snapping,
+    /// The objects currently selected in the editor.
+    #[data(same_fn = "PartialEq::eq")]
+    pub selection: HashSet<Id<Object>>,
+    /// Reference image painted beneath the space.
+    pub background: Option<BackgroundImage>,
 }
 
 impl SpaceEditorProjectData {
@@ -31,16 +51,28 @@ impl SpaceEditorProjectData {
         Self {
             space: Arc::new(space),
             transform: Transform {
-                pan: druid::Vec2::new(0.0, 0.0),
+                pan: Vec2::new(0.0, 0.0),
                 zoom_level: 1.0,
             },
             tool: Tool::Cursor,
+            snapping: Snapping::default(),
+            selection: HashSet::new(),
+            background: None,
         }
     }
 
     pub fn edit_space(&mut self) -> &mut EditableSpace {
         Arc::make_mut(&mut self.space)
     }
+
+    /// Imports walls from a floor plan into the space, and selects them.
+    pub fn import(&mut self, path: &Path, options: &ImportOptions) -> Result<(), ImportError> {
+        // Import into a copy, such that the space is left untouched if importing fails.
+        let mut space = EditableSpace::clone(&self.space);
+        self.selection = import::import_file(&mut space, path, options)?;
+        self.space = Arc::new(space);
+        Ok(())
+    }
 }
 
 pub struct SpaceEditor {
@@ -48,6 +80,15 @@ pub struct SpaceEditor {
     previous_mouse_pos: Point,
 
     tool: Box<dyn ToolImpl>,
+    background: BackgroundPainter,
+
+    history: History,
+    /// The last version of the space this editor is aware of. Used for telling apart changes made
+    /// by the editor itself from changes made from the outside.
+    known_space: Option<Arc<EditableSpace>>,
+    /// Places where rays escaped the space during the last render. Cleared once the space
+    /// changes, since they may no longer be accurate.
+    leaks: Vec<Point>,
 }
 
 impl SpaceEditor {
@@ -57,12 +98,43 @@ impl SpaceEditor {
             previous_mouse_pos: Point::ZERO,
 
             tool: Tool::default().get_impl(),
+            background: BackgroundPainter::new(),
+
+            history: History::new(),
+            known_space: None,
+            leaks: vec![],
         }
     }
 
     fn update_tool(&mut self, tool: Tool) {
         self.tool = tool.get_impl();
     }
+
+    fn undo(&mut self, data: &mut SpaceEditorProjectData) {
+        if let Some(space) = self.history.undo(Arc::clone(&data.space)) {
+            data.space = space;
+        }
+    }
+
+    /// Sets the background image, fitting it into the current view.
+    fn set_background(data: &mut SpaceEditorProjectData, path: &Path, viewport_size: Size) {
+        if let Some(image) = background::load(path) {
+            let center = data.transform.pan.to_point();
+            let width = viewport_size.width / data.transform.zoom();
+            data.background = Some(BackgroundImage::new(
+                path.to_owned(),
+                image.size(),
+                center,
+                width,
+            ));
+        }
+    }
+
+    fn redo(&mut self, data: &mut SpaceEditorProjectData) {
+        if let Some(space) = self.history.redo(Arc::clone(&data.space)) {
+            data.space = space;
+        }
+    }
 }
 
 impl Widget<SpaceEditorProjectData> for SpaceEditor {
@@ -75,6 +147,8 @@ impl Widget<SpaceEditorProjectData> for SpaceEditor {
     ) {
         let viewport_size = ctx.size();
         let viewport_space_event = data.transform.mouse_to_viewport_space(event, viewport_size);
+        let space_before = Arc::clone(&data.space);
+        let mut restored_from_history = false;
 
         match event {
             Event::MouseDown(mouse) if mouse.button.is_middle() => {
@@ -91,17 +165,88 @@ impl Widget<SpaceEditorProjectData> for SpaceEditor {
                 data.transform.pan_by(delta);
                 ctx.request_paint();
             }
+            // Keep the coordinate readout up to date.
+            Event::MouseMove(_) => ctx.request_paint(),
             Event::Wheel(mouse) => {
                 let delta = -mouse.wheel_delta.y.signum();
                 data.transform.zoom_level += delta;
                 ctx.request_paint();
             }
+            Event::Command(command) if command.is(commands::SET_TOOL) => {
+                data.tool = *command.get_unchecked(commands::SET_TOOL);
+                ctx.set_handled();
+            }
+            Event::MouseDown(_) => self.history.begin_gesture(),
+            Event::Command(command) if command.is(commands::UNDO) => {
+                self.undo(data);
+                restored_from_history = true;
+                ctx.request_paint();
+                ctx.set_handled();
+            }
+            Event::Command(command) if command.is(commands::REDO) => {
+                self.redo(data);
+                restored_from_history = true;
+                ctx.request_paint();
+                ctx.set_handled();
+            }
+            Event::Command(command) if command.is(commands::COPY) => {
+                clipboard::copy(data);
+                ctx.set_handled();
+            }
+            Event::Command(command) if command.is(commands::CUT) => {
+                clipboard::cut(data);
+                ctx.request_paint();
+                ctx.set_handled();
+            }
+            Event::Command(command) if command.is(commands::PASTE) => {
+                let position = data
+                    .transform
+                    .to_viewport_space(self.previous_mouse_pos, viewport_size);
+                clipboard::paste(data, position);
+                ctx.request_paint();
+                ctx.set_handled();
+            }
+            Event::Command(command) if command.is(commands::DUPLICATE) => {
+                let offset = env.get(style::DUPLICATE_OFFSET) / data.transform.zoom();
+                clipboard::duplicate(data, Vec2::new(offset, offset));
+                ctx.request_paint();
+                ctx.set_handled();
+            }
+            // Background images are opened through a button showing an open panel. Other kinds of
+            // files are left for whoever asked for them.
+            Event::Command(command) if command.is(druid::commands::OPEN_FILE) => {
+                let path = command.get_unchecked(druid::commands::OPEN_FILE).path();
+                if background::is_image(path) {
+                    Self::set_background(data, path, viewport_size);
+                    ctx.request_paint();
+                    ctx.set_handled();
+                }
+            }
+            Event::Command(command) if command.is(commands::TOGGLE_SNAPPING) => {
+                data.snapping.enabled = !data.snapping.enabled;
+                ctx.set_handled();
+            }
+            Event::Command(command) if command.is(commands::SHOW_LEAKS) => {
+                self.leaks = command.get_unchecked(commands::SHOW_LEAKS).clone();
+                ctx.request_paint();
+                ctx.set_handled();
+            }
             _ => (),
         }
 
         if !ctx.is_handled() {
             self.tool.event(ctx, &viewport_space_event, data, env);
         }
+        if !space_before.same(&data.space) {
+            if !restored_from_history {
+                self.history.record(space_before);
+            }
+            self.leaks.clear();
+        }
+        if let Event::MouseUp(_) = event {
+            self.history.end_gesture();
+        }
+        self.known_space = Some(Arc::clone(&data.space));
 
         if let Event::MouseMove(mouse) = event {
             self.previous_mouse_pos = mouse.pos;
@@ -117,18 +262,39 @@ impl Widget<SpaceEditorProjectData> for SpaceEditor {
     ) {
         if let LifeCycle::WidgetAdded = event {
             self.update_tool(data.tool);
+            self.known_space = Some(Arc::clone(&data.space));
         }
     }
 
     fn update(
         &mut self,
-        _ctx: &mut UpdateCtx,
+        ctx: &mut UpdateCtx,
         old_data: &SpaceEditorProjectData,
         data: &SpaceEditorProjectData,
         _env: &Env,
     ) {
         if data.tool != old_data.tool {
             self.update_tool(data.tool);
+            ctx.request_paint();
+        }
+        if !data.snapping.same(&old_data.snapping) || !data.background.same(&old_data.background) {
+            ctx.request_paint();
+        }
+
+        // Changes made by other widgets don't pass through the editor's events, so they have to be
+        // recorded here.
+        let is_known = self
+            .known_space
+            .as_ref()
+            .map(|known| Arc::ptr_eq(known, &data.space))
+            .unwrap_or(false);
+        if !is_known {
+            if !data.space.same(&old_data.space) {
+                self.history.record(Arc::clone(&old_data.space));
+                self.leaks.clear();
+                ctx.request_paint();
+            }
+            self.known_space = Some(Arc::clone(&data.space));
         }
     }
 
@@ -146,21 +312,37 @@ impl Widget<SpaceEditorProjectData> for SpaceEditor {
         let bounds = ctx.size().to_rect();
 
         ctx.fill(bounds, &env.get(style::BACKGROUND));
+        self.background.paint(ctx, data, env);
+        if data.snapping.enabled && data.snapping.grid {
+            snapping::paint_grid(ctx, data, env);
+        }
 
         ctx.with_save(|ctx| {
-            ctx.transform(Affine::translate(bounds.center().to_vec2()));
-            ctx.transform(Affine::scale(data.transform.zoom()));
-            ctx.transform(Affine::translate(-data.transform.pan));
+            ctx.transform(data.transform.to_screen_affine(bounds.size()));
 
             for object in &data.space.objects {
                 match object {
                     Object::Wall(wall) => {
+                        let stroke_style = StrokeStyle::default().line_cap(LineCap::Round);
                         ctx.stroke_styled(
-                            Line::new(wall.start, wall.end),
+                            wall.to_path(),
                             &env.get(style::WALL_COLOR),
                             env.get(style::WALL_THICKNESS),
-                            &StrokeStyle::default().line_cap(LineCap::Round),
+                            &stroke_style,
                         );
+                        // Walls whose sides differ point out which side is the front.
+                        if wall.back_material.is_some() {
+                            let midpoint = wall.midpoint();
+                            ctx.stroke_styled(
+                                Line::new(
+                                    midpoint,
+                                    midpoint + wall.normal() * env.get(style::FRONT_MARKER_LENGTH),
+                                ),
+                                &env.get(style::WALL_COLOR),
+                                env.get(style::WALL_THICKNESS),
+                                &stroke_style,
+                            );
+                        }
                     }
                     Object::Microphone(microphone) => {
                         let thickness = env.get(style::MICROPHONE_THICKNESS);
@@ -171,14 +353,37 @@ impl Widget<SpaceEditorProjectData> for SpaceEditor {
                             thickness,
                         );
                     }
-                    Object::Speaker(speaker) => ctx.fill(
-                        Circle::new(speaker.position, env.get(style::SPEAKER_RADIUS)),
-                        &env.get(style::SPEAKER_COLOR),
-                    ),
+                    Object::Speaker(speaker) => {
+                        if speaker.directivity != Directivity::Omnidirectional {
+                            let outline = speaker.directivity.outline(
+                                speaker.position,
+                                speaker.angle,
+                                env.get(style::DIRECTIVITY_RADIUS),
+                            );
+                            ctx.stroke(
+                                outline,
+                                &env.get(style::DIRECTIVITY_COLOR),
+                                env.get(style::DIRECTIVITY_THICKNESS),
+                            );
+                        }
+                        ctx.fill(
+                            Circle::new(speaker.position, env.get(style::SPEAKER_RADIUS)),
+                            &env.get(style::SPEAKER_COLOR),
+                        );
+                    }
                 }
             }
+
+            for &leak in &self.leaks {
+                ctx.stroke(
+                    Circle::new(leak, env.get(style::LEAK_RADIUS)),
+                    &env.get(style::LEAK_COLOR),
+                    env.get(style::LEAK_THICKNESS),
+                );
+            }
         });
 
         self.tool.paint(ctx, data, env);
+        units::paint_scale_bar(ctx, data, self.previous_mouse_pos, env);
     }
 }
@@ -0,0 +1,335 @@
+//! Snapping of edited points to the grid, existing walls, and angles.
+
+use std::f64::consts::PI;
+
+use druid::{
+    kurbo::{BezPath, Circle, Line},
+    Data, Env, Modifiers, PaintCtx, Point, Rect, RenderContext,
+};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    data::{EditableSpace, Object},
+    transform::Transform,
+    SpaceEditorProjectData,
+};
+use crate::{math::nice_step, sparse_set::Id};
+
+/// The angle step used when holding Shift.
+const FINE_ANGLE_STEP: f64 = PI / 12.0;
+/// The angle step used when holding Shift and Alt.
+const COARSE_ANGLE_STEP: f64 = PI / 4.0;
+
+#[derive(Debug, Clone, Data, Deserialize, Serialize)]
+pub struct Snapping {
+    /// Whether snapping is enabled at all.
+    pub enabled: bool,
+    /// Whether points snap to grid intersections.
+    pub grid: bool,
+    /// Whether points snap to the endpoints and midpoints of existing walls.
+    pub walls: bool,
+}
+
+impl Default for Snapping {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            grid: true,
+            walls: true,
+        }
+    }
+}
+
+/// What a point was snapped to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SnapTarget {
+    Grid,
+    Endpoint,
+    Midpoint,
+    /// The point was constrained to lie at an angle from the given origin.
+    Angle {
+        origin: Point,
+    },
+}
+
+/// The result of snapping a point.
+#[derive(Debug, Clone, Copy)]
+pub struct Snap {
+    pub position: Point,
+    pub target: Option<SnapTarget>,
+}
+
+impl Snap {
+    /// Returns a snap that leaves the point as is.
+    pub fn none(position: Point) -> Self {
+        Self {
+            position,
+            target: None,
+        }
+    }
+}
+
+/// Constrains the line between an origin and the snapped point to multiples of an angle.
+#[derive(Debug, Clone, Copy)]
+pub struct AngleConstraint {
+    pub origin: Point,
+    pub step: f64,
+}
+
+impl AngleConstraint {
+    /// Returns the angle constraint requested by the given modifiers: Shift constrains angles to
+    /// 15° steps, and Shift+Alt to 45° steps.
+    pub fn from_mods(mods: Modifiers, origin: Point) -> Option<Self> {
+        if !mods.shift() {
+            return None;
+        }
+        let step = if mods.alt() {
+            COARSE_ANGLE_STEP
+        } else {
+            FINE_ANGLE_STEP
+        };
+        Some(Self { origin, step })
+    }
+
+    fn apply(&self, point: Point, length_step: Option<f64>) -> Point {
+        let offset = point - self.origin;
+        let angle = (offset.atan2() / self.step).round() * self.step;
+        let direction = druid::Vec2::from_angle(angle);
+        let mut length = offset.dot(direction);
+        if let Some(length_step) = length_step {
+            length = (length / length_step).round() * length_step;
+        }
+        self.origin + direction * length
+    }
+}
+
+/// Returns the spacing between grid lines, such that they're never closer to each other on screen
+/// than [`style::MIN_GRID_SPACING`].
+pub fn grid_spacing(transform: &Transform, env: &Env) -> f64 {
+    nice_step(env.get(style::MIN_GRID_SPACING) / transform.zoom())
+}
+
+/// Snaps the given point according to the project's snapping settings.
+///
+/// `exclude` can be used to prevent points from snapping to the objects that are being edited.
+pub fn snap(
+    data: &SpaceEditorProjectData,
+    env: &Env,
+    point: Point,
+    angle: Option<AngleConstraint>,
+    exclude: &[Id<Object>],
+) -> Snap {
+    let snapping = &data.snapping;
+    let grid_spacing =
+        (snapping.enabled && snapping.grid).then(|| grid_spacing(&data.transform, env));
+
+    let point = match angle {
+        Some(angle) => angle.apply(point, grid_spacing),
+        None => point,
+    };
+
+    if snapping.enabled && snapping.walls {
+        let radius = env.get(style::SNAP_RADIUS) / data.transform.zoom();
+        if let Some(snap) = snap_to_walls(&data.space, point, radius, exclude) {
+            return snap;
+        }
+    }
+
+    if let Some(angle) = angle {
+        Snap {
+            position: point,
+            target: Some(SnapTarget::Angle {
+                origin: angle.origin,
+            }),
+        }
+    } else if let Some(spacing) = grid_spacing {
+        Snap {
+            position: Point::new(
+                (point.x / spacing).round() * spacing,
+                (point.y / spacing).round() * spacing,
+            ),
+            target: Some(SnapTarget::Grid),
+        }
+    } else {
+        Snap::none(point)
+    }
+}
+
+/// Snaps the point to the closest wall endpoint within `radius`, or the closest midpoint if
+/// there are no endpoints nearby.
+fn snap_to_walls(
+    space: &EditableSpace,
+    point: Point,
+    radius: f64,
+    exclude: &[Id<Object>],
+) -> Option<Snap> {
+    let walls = space
+        .objects
+        .pairs()
+        .filter(|(id, _)| !exclude.contains(id))
+        .filter_map(|(_, object)| match object {
+            Object::Wall(wall) => Some(wall),
+            _ => None,
+        });
+
+    let mut closest: Option<(f64, Snap)> = None;
+    for wall in walls {
+        let candidates = [
+            (wall.start, SnapTarget::Endpoint),
+            (wall.end, SnapTarget::Endpoint),
+            (wall.midpoint(), SnapTarget::Midpoint),
+        ];
+        for (position, target) in candidates {
+            let distance = point.distance(position);
+            if distance > radius {
+                continue;
+            }
+            let is_better = match closest {
+                None => true,
+                Some((closest_distance, closest_snap)) => {
+                    // Endpoints always take priority over midpoints.
+                    let closest_is_endpoint = closest_snap.target == Some(SnapTarget::Endpoint);
+                    let is_endpoint = target == SnapTarget::Endpoint;
+                    (is_endpoint && !closest_is_endpoint)
+                        || (is_endpoint == closest_is_endpoint && distance < closest_distance)
+                }
+            };
+            if is_better {
+                closest = Some((
+                    distance,
+                    Snap {
+                        position,
+                        target: Some(target),
+                    },
+                ));
+            }
+        }
+    }
+    closest.map(|(_, snap)| snap)
+}
+
+/// Paints the grid lines visible in the viewport.
+pub fn paint_grid(ctx: &mut PaintCtx, data: &SpaceEditorProjectData, env: &Env) {
+    let viewport_size = ctx.size();
+    let spacing = grid_spacing(&data.transform, env);
+    let top_left = data.transform.to_viewport_space(Point::ZERO, viewport_size);
+    let bottom_right = data
+        .transform
+        .to_viewport_space(viewport_size.to_vec2().to_point(), viewport_size);
+
+    let line_color = env.get(style::GRID_COLOR);
+    let axis_color = env.get(style::GRID_AXIS_COLOR);
+    let thickness = env.get(style::GRID_THICKNESS);
+
+    let first_column = (top_left.x / spacing).floor() as i64;
+    let last_column = (bottom_right.x / spacing).ceil() as i64;
+    for column in first_column..=last_column {
+        let x = column as f64 * spacing;
+        let x = data
+            .transform
+            .to_screen_space(Point::new(x, 0.0), viewport_size)
+            .x;
+        let color = if column == 0 {
+            &axis_color
+        } else {
+            &line_color
+        };
+        ctx.stroke(
+            Line::new((x, 0.0), (x, viewport_size.height)),
+            color,
+            thickness,
+        );
+    }
+
+    let first_row = (top_left.y / spacing).floor() as i64;
+    let last_row = (bottom_right.y / spacing).ceil() as i64;
+    for row in first_row..=last_row {
+        let y = row as f64 * spacing;
+        let y = data
+            .transform
+            .to_screen_space(Point::new(0.0, y), viewport_size)
+            .y;
+        let color = if row == 0 { &axis_color } else { &line_color };
+        ctx.stroke(
+            Line::new((0.0, y), (viewport_size.width, y)),
+            color,
+            thickness,
+        );
+    }
+}
+
+/// Paints an indicator showing what the point was snapped to.
+pub fn paint_indicator(ctx: &mut PaintCtx, transform: &Transform, snap: &Snap, env: &Env) {
+    let viewport_size = ctx.size();
+    let position = transform.to_screen_space(snap.position, viewport_size);
+    let color = env.get(style::INDICATOR_COLOR);
+    let size = env.get(style::INDICATOR_SIZE);
+    let thickness = env.get(style::INDICATOR_THICKNESS);
+
+    match snap.target {
+        None => (),
+        Some(SnapTarget::Grid) => {
+            ctx.stroke(
+                Line::new(position - (size, 0.0), position + (size, 0.0)),
+                &color,
+                thickness,
+            );
+            ctx.stroke(
+                Line::new(position - (0.0, size), position + (0.0, size)),
+                &color,
+                thickness,
+            );
+        }
+        Some(SnapTarget::Endpoint) => {
+            ctx.stroke(
+                Rect::from_center_size(position, (size * 2.0, size * 2.0)),
+                &color,
+                thickness,
+            );
+        }
+        Some(SnapTarget::Midpoint) => {
+            let mut triangle = BezPath::new();
+            triangle.move_to(position + (0.0, -size));
+            triangle.line_to(position + (size, size));
+            triangle.line_to(position + (-size, size));
+            triangle.close_path();
+            ctx.stroke(triangle, &color, thickness);
+        }
+        Some(SnapTarget::Angle { origin }) => {
+            let origin = transform.to_screen_space(origin, viewport_size);
+            ctx.stroke(Line::new(origin, position), &color, thickness);
+            ctx.stroke(Circle::new(position, size), &color, thickness);
+        }
+    }
+}
+
+pub mod style {
+    use druid::{Color, Env, Key};
+
+    use crate::style::color;
+
+    pub const SNAP_RADIUS: Key<f64> = style_key!("space-editor.snapping.radius");
+
+    pub const MIN_GRID_SPACING: Key<f64> = style_key!("space-editor.grid.min-spacing");
+    pub const GRID_COLOR: Key<Color> = style_key!("space-editor.grid.color");
+    pub const GRID_AXIS_COLOR: Key<Color> = style_key!("space-editor.grid.axis-color");
+    pub const GRID_THICKNESS: Key<f64> = style_key!("space-editor.grid.thickness");
+
+    pub const INDICATOR_COLOR: Key<Color> = style_key!("space-editor.snapping.indicator-color");
+    pub const INDICATOR_SIZE: Key<f64> = style_key!("space-editor.snapping.indicator-size");
+    pub const INDICATOR_THICKNESS: Key<f64> =
+        style_key!("space-editor.snapping.indicator-thickness");
+
+    pub fn configure_env(env: &mut Env) {
+        env.set(SNAP_RADIUS, 12.0);
+
+        env.set(MIN_GRID_SPACING, 24.0);
+        env.set(GRID_COLOR, color(0xE6E6EA));
+        env.set(GRID_AXIS_COLOR, color(0xC8C8D0));
+        env.set(GRID_THICKNESS, 1.0);
+
+        env.set(INDICATOR_COLOR, color(0xE0A100));
+        env.set(INDICATOR_SIZE, 6.0);
+        env.set(INDICATOR_THICKNESS, 1.5);
+    }
+}
@@ -1,12 +1,13 @@
 use druid::{Color, Env, Key};
 
-use super::tool;
+use super::{snapping, tool, units};
 use crate::style::color;
 
 pub const BACKGROUND: Key<Color> = style_key!("space-editor.background");
 
 pub const WALL_COLOR: Key<Color> = style_key!("space-editor.wall.color");
 pub const WALL_THICKNESS: Key<f64> = style_key!("space-editor.wall.thickness");
+pub const FRONT_MARKER_LENGTH: Key<f64> = style_key!("space-editor.wall.front-marker-length");
 
 pub const MICROPHONE_COLOR: Key<Color> = style_key!("space-editor.microphone.color");
 pub const MICROPHONE_THICKNESS: Key<f64> = style_key!("space-editor.microphone.thickness");
@@ -14,12 +15,23 @@ pub const MICROPHONE_RADIUS: Key<f64> = style_key!("space-editor.microphone.radi
 
 pub const SPEAKER_COLOR: Key<Color> = style_key!("space-editor.speaker.color");
 pub const SPEAKER_RADIUS: Key<f64> = style_key!("space-editor.speaker.radius");
+pub const DIRECTIVITY_COLOR: Key<Color> = style_key!("space-editor.speaker.directivity-color");
+pub const DIRECTIVITY_THICKNESS: Key<f64> =
+    style_key!("space-editor.speaker.directivity-thickness");
+pub const DIRECTIVITY_RADIUS: Key<f64> = style_key!("space-editor.speaker.directivity-radius");
+
+pub const LEAK_COLOR: Key<Color> = style_key!("space-editor.leak.color");
+pub const LEAK_RADIUS: Key<f64> = style_key!("space-editor.leak.radius");
+pub const LEAK_THICKNESS: Key<f64> = style_key!("space-editor.leak.thickness");
+
+pub const DUPLICATE_OFFSET: Key<f64> = style_key!("space-editor.duplicate-offset");
 
 pub fn configure_env(env: &mut Env) {
     env.set(BACKGROUND, color(0xF7F7F8));
 
     env.set(WALL_COLOR, color(0x071013));
     env.set(WALL_THICKNESS, 0.5);
+    env.set(FRONT_MARKER_LENGTH, 1.0);
 
     env.set(MICROPHONE_COLOR, color(0x23B5D3));
     env.set(MICROPHONE_RADIUS, 0.5);
@@ -27,6 +39,17 @@ pub fn configure_env(env: &mut Env) {
 
     env.set(SPEAKER_COLOR, color(0xEC5740));
     env.set(SPEAKER_RADIUS, 0.5);
+    env.set(DIRECTIVITY_COLOR, color(0xEC5740).with_alpha(0.5));
+    env.set(DIRECTIVITY_THICKNESS, 0.1);
+    env.set(DIRECTIVITY_RADIUS, 2.0);
+
+    env.set(LEAK_COLOR, color(0xF2A541));
+    env.set(LEAK_RADIUS, 1.0);
+    env.set(LEAK_THICKNESS, 0.25);
+
+    env.set(DUPLICATE_OFFSET, 16.0);
 
+    snapping::style::configure_env(env);
+    units::style::configure_env(env);
     tool::style::configure_env(env);
 }
@@ -1,10 +1,12 @@
 use druid::{
+    im::HashSet,
     kurbo::{Circle, Line},
     piet::{LineCap, StrokeStyle},
-    Color, Command, Env, Event, EventCtx, PaintCtx, Point, RenderContext, Vec2,
+    Affine, Color, Command, Env, Event, EventCtx, MouseEvent, PaintCtx, Point, Rect, RenderContext,
+    Vec2,
 };
 use space_editor::{
-    data::{Microphone, Speaker, Wall},
+    data::{Microphone, Speaker, WallShape},
     transform::Transform,
 };
 use tracing::info;
@@ -15,15 +17,42 @@ use crate::{
     math::PointExtHitTests,
     sparse_set::Id,
     widgets::{
-        data::{EditableSpace, Object},
+        data::Object,
+        snapping::{self, AngleConstraint, Snap},
         space_editor, SpaceEditorProjectData,
     },
 };
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 enum State {
     Idle,
-    Dragging,
+    /// Dragging a single part of an object, such as a wall's endpoint.
+    DraggingPart {
+        object: Id<Object>,
+        part: HotPart,
+        /// The mouse position at which the drag started.
+        mouse_start: Point,
+        /// The position of the dragged part's anchor when the drag started.
+        anchor_start: Point,
+    },
+    /// Moving all selected objects together.
+    Moving {
+        mouse_start: Point,
+        anchor_start: Point,
+    },
+    /// Rotating or scaling all selected objects around their centroid.
+    Transforming {
+        handle: TransformHandle,
+        pivot: Point,
+        mouse_start: Point,
+    },
+    /// Dragging out a rectangle, selecting all objects inside of it.
+    BoxSelecting {
+        start: Point,
+        /// Whether the objects should be added to the existing selection, rather than replacing
+        /// it.
+        additive: bool,
+    },
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -31,16 +60,8 @@ enum HotPart {
     EntireObject,
     WallStart,
     WallEnd,
-}
-
-impl HotPart {
-    fn should_move_wall_start(&self) -> bool {
-        matches!(self, HotPart::EntireObject | HotPart::WallStart)
-    }
-
-    fn should_move_wall_end(&self) -> bool {
-        matches!(self, HotPart::EntireObject | HotPart::WallEnd)
-    }
+    /// A control point of a curved wall, as indexed by [`Wall::control_points`].
+    WallControl(usize),
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -49,46 +70,123 @@ struct HotState {
     part: HotPart,
 }
 
+/// One of the handles around a multi-object selection.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TransformHandle {
+    Rotate,
+    /// Scales the selection along the axes on which the handle is offset from the center of the
+    /// selection (-1, 0, or 1.) Corners scale uniformly, while edges scale along a single axis,
+    /// which can be used for mirroring.
+    Scale {
+        x: f64,
+        y: f64,
+    },
+}
+
+/// The bounds and centroid of a multi-object selection, around which transform handles are
+/// placed.
+#[derive(Debug, Clone, Copy)]
+struct SelectionFrame {
+    bounds: Rect,
+    centroid: Point,
+}
+
+impl SelectionFrame {
+    /// Returns the frame around the selection, or `None` if less than two objects are selected.
+    fn new(data: &SpaceEditorProjectData) -> Option<Self> {
+        let objects: Vec<_> = data
+            .selection
+            .iter()
+            .filter_map(|&id| data.space.objects.get(id))
+            .collect();
+        if objects.len() < 2 {
+            return None;
+        }
+
+        let bounds = objects
+            .iter()
+            .map(|object| object.bounding_box())
+            .reduce(|a, b| a.union(b))?;
+        let centroid = objects
+            .iter()
+            .fold(Vec2::ZERO, |sum, object| sum + object.center().to_vec2())
+            / objects.len() as f64;
+        Some(Self {
+            bounds,
+            centroid: centroid.to_point(),
+        })
+    }
+
+    /// Returns the handles of the frame, along with their positions in viewport space.
+    fn handles(&self, transform: &Transform, env: &Env) -> Vec<(TransformHandle, Point)> {
+        let center = self.bounds.center();
+        let half_size = self.bounds.size().to_vec2() / 2.0;
+        let rotate_handle_distance = env.get(style::ROTATE_HANDLE_DISTANCE) / transform.zoom();
+
+        let mut handles = vec![(
+            TransformHandle::Rotate,
+            Point::new(center.x, self.bounds.y0 - rotate_handle_distance),
+        )];
+        let offsets = [
+            (-1.0, -1.0),
+            (0.0, -1.0),
+            (1.0, -1.0),
+            (1.0, 0.0),
+            (1.0, 1.0),
+            (0.0, 1.0),
+            (-1.0, 1.0),
+            (-1.0, 0.0),
+        ];
+        for (x, y) in offsets {
+            let position = center + Vec2::new(x * half_size.x, y * half_size.y);
+            handles.push((TransformHandle::Scale { x, y }, position));
+        }
+        handles
+    }
+}
+
 pub struct CursorTool {
     hot_state: Option<HotState>,
-    focused_state: Option<HotState>,
+    hot_handle: Option<TransformHandle>,
     state: State,
-    last_mouse_pos: Point,
+    snap: Option<Snap>,
+    mouse_pos: Point,
+    /// The selected objects, as they were before the current move or transform started.
+    originals: Vec<(Id<Object>, Object)>,
 }
 
 impl CursorTool {
     pub fn new() -> Self {
         Self {
             hot_state: None,
-            focused_state: None,
+            hot_handle: None,
             state: State::Idle,
-            last_mouse_pos: Point::ZERO,
+            snap: None,
+            mouse_pos: Point::ZERO,
+            originals: vec![],
         }
     }
 
     fn make_object_hot_at_position(
         &mut self,
         object_params: &CachedObjectParams,
-        space: &EditableSpace,
+        data: &SpaceEditorProjectData,
         position: Point,
     ) {
         self.hot_state = None;
 
-        // Prioritize the focused object, then try other objects.
-        if let Some(HotState { object: id, .. }) = self.focused_state {
-            if let Some(object) = space.objects.get(id) {
-                if self.check_object_hotness(object, id, position, object_params) {
+        // Prioritize selected objects, then try other objects.
+        for &id in &data.selection {
+            if let Some(object) = data.space.objects.get(id) {
+                if self.check_object_hotness(object, id, true, position, object_params) {
                     return;
                 }
             }
         }
 
-        for (id, object) in space.objects.pairs() {
-            let did_set_hot_state = self.check_object_hotness(object, id, position, object_params);
-
-            // The hotness of focused objects takes priority over non-focused objects.
-            if did_set_hot_state && self.focused_state == self.hot_state {
-                break;
+        for (id, object) in data.space.objects.pairs() {
+            if !data.selection.contains(&id) {
+                self.check_object_hotness(object, id, false, position, object_params);
             }
         }
     }
@@ -97,16 +195,27 @@ impl CursorTool {
         &mut self,
         object: &Object,
         object_id: Id<Object>,
+        is_selected: bool,
         position: Point,
         object_params: &CachedObjectParams,
     ) -> bool {
         match object {
             Object::Wall(wall) => {
+                // Control points are only visible (and thus interactible) on selected walls.
+                let hot_control = is_selected
+                    .then(|| {
+                        wall.control_points().into_iter().position(|point| {
+                            position.in_circle(point, object_params.handle_radius)
+                        })
+                    })
+                    .flatten();
                 let hot_part = if position.in_circle(wall.start, object_params.handle_radius) {
                     Some(HotPart::WallStart)
                 } else if position.in_circle(wall.end, object_params.handle_radius) {
                     Some(HotPart::WallEnd)
-                } else if position.near_line(wall.start, wall.end, object_params.wall_thickness) {
+                } else if let Some(index) = hot_control {
+                    Some(HotPart::WallControl(index))
+                } else if wall.is_near(position, object_params.wall_thickness) {
                     Some(HotPart::EntireObject)
                 } else {
                     None
@@ -144,23 +253,173 @@ impl CursorTool {
         false
     }
 
-    fn drag_entire_object(&mut self, object: &mut Object, part: HotPart, delta: Vec2) {
+    fn make_handle_hot_at_position(
+        &mut self,
+        object_params: &CachedObjectParams,
+        data: &SpaceEditorProjectData,
+        env: &Env,
+        position: Point,
+    ) {
+        self.hot_handle = SelectionFrame::new(data).and_then(|frame| {
+            frame
+                .handles(&data.transform, env)
+                .into_iter()
+                .find(|&(_, handle)| position.in_circle(handle, object_params.handle_radius))
+                .map(|(handle, _)| handle)
+        });
+    }
+
+    /// Returns the point of the object part which gets snapped while dragging it.
+    fn anchor(object: &Object, part: HotPart) -> Option<Point> {
+        match (object, part) {
+            (Object::Wall(wall), HotPart::EntireObject | HotPart::WallStart) => Some(wall.start),
+            (Object::Wall(wall), HotPart::WallEnd) => Some(wall.end),
+            (Object::Wall(wall), HotPart::WallControl(index)) => {
+                wall.control_points().get(index).copied()
+            }
+            (Object::Microphone(Microphone { position }), _)
+            | (Object::Speaker(Speaker { position, .. }), _) => Some(*position),
+        }
+    }
+
+    /// Returns the point from which the angle of the dragged part is measured, when constraining
+    /// angles.
+    fn angle_origin(object: &Object, part: HotPart, anchor_start: Point) -> Point {
+        match (object, part) {
+            (Object::Wall(wall), HotPart::WallStart) => wall.end,
+            (Object::Wall(wall), HotPart::WallEnd) => wall.start,
+            _ => anchor_start,
+        }
+    }
+
+    fn drag_part(&mut self, object: &mut Object, part: HotPart, delta: Vec2) {
         match object {
-            Object::Wall(Wall { start, end, .. }) => {
-                if part.should_move_wall_start() {
-                    *start += delta;
-                }
-                if part.should_move_wall_end() {
-                    *end += delta;
+            Object::Wall(wall) => match part {
+                HotPart::EntireObject => wall.translate(delta),
+                HotPart::WallStart => wall.start += delta,
+                HotPart::WallEnd => wall.end += delta,
+                HotPart::WallControl(index) => {
+                    if let Some(&point) = wall.control_points().get(index) {
+                        wall.set_control_point(index, point + delta);
+                    }
                 }
-            }
+            },
             Object::Microphone(Microphone { position })
             | Object::Speaker(Speaker { position, .. }) => *position += delta,
         }
     }
 
-    fn focused_object_is_hot(&self) -> bool {
-        self.focused_state.map(|s| s.object) == self.hot_state.map(|s| s.object)
+    /// Remembers the selected objects' current state, such that moves and transforms can be
+    /// applied to them without accumulating error.
+    fn save_originals(&mut self, data: &SpaceEditorProjectData) {
+        self.originals = data
+            .selection
+            .iter()
+            .filter_map(|&id| {
+                data.space
+                    .objects
+                    .get(id)
+                    .map(|object| (id, object.clone()))
+            })
+            .collect();
+    }
+
+    /// Resets the selected objects to their original state and applies `f` to each of them.
+    fn apply_to_originals(&self, data: &mut SpaceEditorProjectData, f: impl Fn(&mut Object)) {
+        let space = data.edit_space();
+        for (id, original) in &self.originals {
+            if let Some(object) = space.objects.get_mut(*id) {
+                *object = original.clone();
+                f(object);
+            }
+        }
+    }
+
+    fn start_drag(
+        &mut self,
+        ctx: &mut EventCtx,
+        data: &mut SpaceEditorProjectData,
+        mouse: &MouseEvent,
+    ) {
+        if let Some(handle) = self.hot_handle {
+            if let Some(frame) = SelectionFrame::new(data) {
+                self.save_originals(data);
+                self.state = State::Transforming {
+                    handle,
+                    pivot: frame.centroid,
+                    mouse_start: mouse.pos,
+                };
+            }
+        } else if let Some(HotState { object: id, part }) = self.hot_state {
+            if mouse.mods.shift() {
+                if data.selection.contains(&id) {
+                    data.selection.remove(&id);
+                } else {
+                    data.selection.insert(id);
+                }
+                ctx.request_paint();
+                return;
+            }
+
+            if !data.selection.contains(&id) {
+                data.selection = HashSet::unit(id);
+            }
+            let anchor = data
+                .space
+                .objects
+                .get(id)
+                .and_then(|object| Self::anchor(object, part));
+            if let Some(anchor_start) = anchor {
+                self.state = if part == HotPart::EntireObject {
+                    self.save_originals(data);
+                    State::Moving {
+                        mouse_start: mouse.pos,
+                        anchor_start,
+                    }
+                } else {
+                    State::DraggingPart {
+                        object: id,
+                        part,
+                        mouse_start: mouse.pos,
+                        anchor_start,
+                    }
+                };
+            }
+        } else {
+            if !mouse.mods.shift() {
+                data.selection.clear();
+            }
+            self.state = State::BoxSelecting {
+                start: mouse.pos,
+                additive: mouse.mods.shift(),
+            };
+        }
+        ctx.set_active(true);
+        ctx.request_paint();
+    }
+
+    fn finish_box_selection(
+        &self,
+        data: &mut SpaceEditorProjectData,
+        start: Point,
+        additive: bool,
+    ) {
+        let rect = Rect::from_points(start, self.mouse_pos);
+        let inside: Vec<_> = data
+            .space
+            .objects
+            .pairs()
+            .filter(|(_, object)| {
+                let bounds = object.bounding_box();
+                rect.intersect(bounds) == bounds
+            })
+            .map(|(id, _)| id)
+            .collect();
+        if additive {
+            data.selection.extend(inside);
+        } else {
+            data.selection = inside.into_iter().collect();
+        }
     }
 
     fn object_part_is_hot(&self, object_id: Id<Object>, part: HotPart) -> bool {
@@ -169,6 +428,64 @@ impl CursorTool {
             .unwrap_or(false)
     }
 
+    fn finish_drag(&mut self, ctx: &mut EventCtx) {
+        self.state = State::Idle;
+        self.snap = None;
+        self.originals.clear();
+        ctx.set_active(false);
+        ctx.request_paint();
+    }
+
+    fn paint_selection_frame(
+        &self,
+        ctx: &mut PaintCtx,
+        data: &SpaceEditorProjectData,
+        env: &Env,
+        frame: &SelectionFrame,
+    ) {
+        let viewport_size = ctx.size();
+        let primary_color = env.get(style::PRIMARY_SELECTION_COLOR);
+        let secondary_color = env.get(style::SECONDARY_SELECTION_COLOR);
+        let thickness = env.get(style::FRAME_THICKNESS);
+
+        let bounds = Rect::from_points(
+            data.transform
+                .to_screen_space(frame.bounds.origin(), viewport_size),
+            data.transform
+                .to_screen_space(Point::new(frame.bounds.x1, frame.bounds.y1), viewport_size),
+        );
+        ctx.stroke(bounds, &primary_color, thickness);
+
+        let handles = frame.handles(&data.transform, env);
+        for &(handle, position) in &handles {
+            let position = data.transform.to_screen_space(position, viewport_size);
+            if handle == TransformHandle::Rotate {
+                let top = data.transform.to_screen_space(
+                    Point::new(frame.bounds.center().x, frame.bounds.y0),
+                    viewport_size,
+                );
+                ctx.stroke(Line::new(top, position), &primary_color, thickness);
+            }
+            paint_object_handle(
+                ctx,
+                env,
+                position,
+                &primary_color,
+                &secondary_color,
+                self.hot_handle == Some(handle),
+            );
+        }
+
+        let centroid = data
+            .transform
+            .to_screen_space(frame.centroid, viewport_size);
+        ctx.stroke(
+            Circle::new(centroid, env.get(style::IDLE_HANDLE_INNER_RADIUS)),
+            &primary_color,
+            thickness,
+        );
+    }
+
     fn command(
         &mut self,
         ctx: &mut EventCtx,
@@ -176,11 +493,20 @@ impl CursorTool {
         command: &Command,
     ) {
         if command.is(commands::DELETE) {
-            if let Some(HotState { object, .. }) = self.focused_state {
-                info!(?object, "delete");
-                data.edit_space().objects.remove(object);
+            if !data.selection.is_empty() {
+                let selection = std::mem::take(&mut data.selection);
+                info!(?selection, "delete");
+                let space = data.edit_space();
+                for &id in &selection {
+                    space.objects.remove(id);
+                }
+                self.hot_state = None;
+                self.hot_handle = None;
                 ctx.request_paint();
             }
+        } else if command.is(commands::CANCEL) {
+            data.selection.clear();
+            ctx.request_paint();
         } else {
             return;
         }
@@ -196,52 +522,101 @@ impl ToolImpl for CursorTool {
         data: &mut SpaceEditorProjectData,
         env: &Env,
     ) {
+        if let Event::MouseMove(mouse) = event {
+            self.mouse_pos = mouse.pos;
+        }
+
         match (self.state, event) {
             (State::Idle, Event::MouseMove(mouse)) => {
                 let object_params =
                     CachedObjectParams::from_env_and_transform(env, &data.transform);
-                let previous_hot = self.hot_state;
-                self.make_object_hot_at_position(&object_params, &data.space, mouse.pos);
-                if self.hot_state != previous_hot {
+                let previous_hot = (self.hot_state, self.hot_handle);
+                self.make_handle_hot_at_position(&object_params, data, env, mouse.pos);
+                if self.hot_handle.is_some() {
+                    self.hot_state = None;
+                } else {
+                    self.make_object_hot_at_position(&object_params, data, mouse.pos);
+                }
+                if (self.hot_state, self.hot_handle) != previous_hot {
                     ctx.request_paint();
                 }
             }
             (State::Idle, Event::MouseDown(mouse)) => {
                 if mouse.button.is_left() {
-                    self.focused_state = self.hot_state;
-                    if self.hot_state.is_some() {
-                        self.state = State::Dragging;
-                        ctx.set_active(true);
-                        ctx.request_paint();
-                    }
+                    self.mouse_pos = mouse.pos;
+                    self.start_drag(ctx, data, mouse);
                     ctx.set_handled();
                 }
             }
 
-            (State::Dragging, Event::MouseMove(mouse)) => {
-                if let Some(HotState { object, part }) = self.focused_state {
-                    if let Some(object) = data.edit_space().objects.get_mut(object) {
-                        let delta = mouse.pos - self.last_mouse_pos;
-                        self.drag_entire_object(object, part, delta);
-                        ctx.request_paint();
+            (
+                State::DraggingPart {
+                    object: id,
+                    part,
+                    mouse_start,
+                    anchor_start,
+                },
+                Event::MouseMove(mouse),
+            ) => {
+                if let Some(object) = data.space.objects.get(id) {
+                    let target = anchor_start + (mouse.pos - mouse_start);
+                    let origin = Self::angle_origin(object, part, anchor_start);
+                    let angle = AngleConstraint::from_mods(mouse.mods, origin);
+                    let snap = snapping::snap(data, env, target, angle, &[id]);
+                    self.snap = Some(snap);
+
+                    if let Some(anchor) = Self::anchor(object, part) {
+                        let delta = snap.position - anchor;
+                        if let Some(object) = data.edit_space().objects.get_mut(id) {
+                            self.drag_part(object, part, delta);
+                        }
                     }
+                    ctx.request_paint();
                 }
             }
-
-            (_, Event::MouseUp(_)) => {
-                self.state = State::Idle;
-                ctx.set_active(false);
+            (
+                State::Moving {
+                    mouse_start,
+                    anchor_start,
+                },
+                Event::MouseMove(mouse),
+            ) => {
+                let target = anchor_start + (mouse.pos - mouse_start);
+                let angle = AngleConstraint::from_mods(mouse.mods, anchor_start);
+                let exclude: Vec<_> = self.originals.iter().map(|&(id, _)| id).collect();
+                let snap = snapping::snap(data, env, target, angle, &exclude);
+                self.snap = Some(snap);
+
+                let delta = snap.position - anchor_start;
+                self.apply_to_originals(data, |object| object.translate(delta));
+                ctx.request_paint();
+            }
+            (
+                State::Transforming {
+                    handle,
+                    pivot,
+                    mouse_start,
+                },
+                Event::MouseMove(mouse),
+            ) => {
+                let affine = transform_affine(handle, pivot, mouse_start, mouse);
+                self.apply_to_originals(data, |object| object.transform(affine));
+                ctx.request_paint();
+            }
+            (State::BoxSelecting { .. }, Event::MouseMove(_)) => {
                 ctx.request_paint();
             }
 
+            (State::BoxSelecting { start, additive }, Event::MouseUp(_)) => {
+                self.finish_box_selection(data, start, additive);
+                self.finish_drag(ctx);
+            }
+            (_, Event::MouseUp(_)) => self.finish_drag(ctx),
+
             (_, Event::Command(command)) => self.command(ctx, data, command),
 
             _ => (),
         }
-
-        if let Event::MouseMove(mouse) = event {
-            self.last_mouse_pos = mouse.pos;
-        }
     }
 
     fn paint(&mut self, ctx: &mut PaintCtx, data: &SpaceEditorProjectData, env: &Env) {
@@ -249,10 +624,7 @@ impl ToolImpl for CursorTool {
         let primary_color = env.get(style::PRIMARY_SELECTION_COLOR);
         let secondary_color = env.get(style::SECONDARY_SELECTION_COLOR);
 
-        if let Some(HotState {
-            object: object_id, ..
-        }) = self.focused_state
-        {
+        for &object_id in &data.selection {
             if let Some(object) = data.space.objects.get(object_id) {
                 let thickness = if self.object_part_is_hot(object_id, HotPart::EntireObject) {
                     env.get(style::HOT_FOCUSED_OUTLINE_THICKNESS)
@@ -260,32 +632,64 @@ impl ToolImpl for CursorTool {
                     env.get(style::FOCUSED_OUTLINE_THICKNESS)
                 };
                 paint_object_outline(ctx, env, &data.transform, viewport_size, object, thickness);
+            }
+        }
 
-                if let &Object::Wall(Wall { start, end, .. }) = object {
-                    let start = data.transform.to_screen_space(start, viewport_size);
-                    let end = data.transform.to_screen_space(end, viewport_size);
+        for &object_id in &data.selection {
+            if let Some(Object::Wall(wall)) = data.space.objects.get(object_id) {
+                let start = data.transform.to_screen_space(wall.start, viewport_size);
+                let end = data.transform.to_screen_space(wall.end, viewport_size);
+                let control_points: Vec<_> = wall
+                    .control_points()
+                    .into_iter()
+                    .map(|point| data.transform.to_screen_space(point, viewport_size))
+                    .collect();
+
+                // Bézier control points are connected to the endpoints they belong to.
+                let control_lines = match *control_points.as_slice() {
+                    [control] if matches!(wall.shape, WallShape::QuadraticBezier { .. }) => {
+                        vec![Line::new(start, control), Line::new(control, end)]
+                    }
+                    [control_start, control_end] => {
+                        vec![Line::new(start, control_start), Line::new(end, control_end)]
+                    }
+                    _ => vec![],
+                };
+                for line in control_lines {
+                    ctx.stroke(line, &primary_color, env.get(style::CONTROL_LINE_THICKNESS));
+                }
+                for (index, &control) in control_points.iter().enumerate() {
                     paint_object_handle(
                         ctx,
                         env,
-                        start,
-                        &primary_color,
+                        control,
                         &secondary_color,
-                        self.object_part_is_hot(object_id, HotPart::WallStart),
-                    );
-                    paint_object_handle(
-                        ctx,
-                        env,
-                        end,
                         &primary_color,
-                        &secondary_color,
-                        self.object_part_is_hot(object_id, HotPart::WallEnd),
+                        self.object_part_is_hot(object_id, HotPart::WallControl(index)),
                     );
                 }
+
+                paint_object_handle(
+                    ctx,
+                    env,
+                    start,
+                    &primary_color,
+                    &secondary_color,
+                    self.object_part_is_hot(object_id, HotPart::WallStart),
+                );
+                paint_object_handle(
+                    ctx,
+                    env,
+                    end,
+                    &primary_color,
+                    &secondary_color,
+                    self.object_part_is_hot(object_id, HotPart::WallEnd),
+                );
             }
         }
 
         if let Some(HotState { object, .. }) = self.hot_state {
-            if !self.focused_object_is_hot() {
+            if !data.selection.contains(&object) {
                 if let Some(object) = data.space.objects.get(object) {
                     let thickness = env.get(style::HOT_OUTLINE_THICKNESS);
                     paint_object_outline(
@@ -299,9 +703,73 @@ impl ToolImpl for CursorTool {
                 }
             }
         }
+
+        if let Some(frame) = SelectionFrame::new(data) {
+            self.paint_selection_frame(ctx, data, env, &frame);
+        }
+
+        if let State::BoxSelecting { start, .. } = self.state {
+            let start = data.transform.to_screen_space(start, viewport_size);
+            let end = data
+                .transform
+                .to_screen_space(self.mouse_pos, viewport_size);
+            let rect = Rect::from_points(start, end);
+            ctx.fill(rect, &env.get(style::BOX_SELECTION_FILL_COLOR));
+            ctx.stroke(rect, &primary_color, env.get(style::FRAME_THICKNESS));
+        }
+
+        if let Some(snap) = &self.snap {
+            snapping::paint_indicator(ctx, &data.transform, snap, env);
+        }
     }
 }
 
+/// Returns the transform applied to the selection when dragging the given handle from
+/// `mouse_start` to the mouse's current position.
+fn transform_affine(
+    handle: TransformHandle,
+    pivot: Point,
+    mouse_start: Point,
+    mouse: &MouseEvent,
+) -> Affine {
+    let start = mouse_start - pivot;
+    let current = mouse.pos - pivot;
+    let ratio = |current: f64, start: f64| {
+        if start.abs() > 1e-9 {
+            current / start
+        } else {
+            1.0
+        }
+    };
+
+    let local = match handle {
+        TransformHandle::Rotate => {
+            let mut angle = current.atan2() - start.atan2();
+            if let Some(constraint) = AngleConstraint::from_mods(mouse.mods, pivot) {
+                angle = (angle / constraint.step).round() * constraint.step;
+            }
+            Affine::rotate(angle)
+        }
+        TransformHandle::Scale { x, y } if x != 0.0 && y != 0.0 => {
+            Affine::scale(ratio(current.dot(start), start.hypot2()))
+        }
+        TransformHandle::Scale { x, y } => {
+            let scale_x = if x != 0.0 {
+                ratio(current.x, start.x)
+            } else {
+                1.0
+            };
+            let scale_y = if y != 0.0 {
+                ratio(current.y, start.y)
+            } else {
+                1.0
+            };
+            Affine::scale_non_uniform(scale_x, scale_y)
+        }
+    };
+    Affine::translate(pivot.to_vec2()) * local * Affine::translate(-pivot.to_vec2())
+}
+
 struct CachedObjectParams {
     microphone_radius: f64,
     speaker_radius: f64,
@@ -355,14 +823,8 @@ fn paint_object_outline(
     let stroke_style = StrokeStyle::default().line_cap(LineCap::Round);
     match object {
         Object::Wall(wall) => {
-            let start = transform.to_screen_space(wall.start, viewport_size);
-            let end = transform.to_screen_space(wall.end, viewport_size);
-            ctx.stroke_styled(
-                Line::new(start, end),
-                &primary_color,
-                thickness,
-                &stroke_style,
-            );
+            let path = transform.to_screen_affine(viewport_size) * wall.to_path();
+            ctx.stroke_styled(path, &primary_color, thickness, &stroke_style);
         }
         Object::Microphone(microphone) => {
             let position = transform.to_screen_space(microphone.position, viewport_size);
@@ -402,6 +864,13 @@ pub mod style {
         style_key!("tool.cursor.selection.focused-thickness");
     pub const HOT_FOCUSED_OUTLINE_THICKNESS: Key<f64> =
         style_key!("tool.cursor.selection.hot-focused-thickness");
+    pub const CONTROL_LINE_THICKNESS: Key<f64> =
+        style_key!("tool.cursor.selection.control-line-thickness");
+    pub const FRAME_THICKNESS: Key<f64> = style_key!("tool.cursor.selection.frame-thickness");
+    pub const BOX_SELECTION_FILL_COLOR: Key<Color> =
+        style_key!("tool.cursor.selection.box-fill-color");
+    pub const ROTATE_HANDLE_DISTANCE: Key<f64> =
+        style_key!("tool.cursor.selection.rotate-handle-distance");
 
     pub const MICROPHONE_RADIUS: Key<f64> = style_key!("tool.cursor.microphone.radius");
     pub const SPEAKER_RADIUS: Key<f64> = style_key!("tool.cursor.speaker.radius");
@@ -420,6 +889,13 @@ pub mod style {
         env.set(HOT_OUTLINE_THICKNESS, 2.0);
         env.set(FOCUSED_OUTLINE_THICKNESS, 4.0);
         env.set(HOT_FOCUSED_OUTLINE_THICKNESS, 6.0);
+        env.set(CONTROL_LINE_THICKNESS, 1.0);
+        env.set(FRAME_THICKNESS, 1.0);
+        env.set(
+            BOX_SELECTION_FILL_COLOR,
+            env.get(PRIMARY_SELECTION_COLOR).with_alpha(0.15),
+        );
+        env.set(ROTATE_HANDLE_DISTANCE, 24.0);
         env.set(
             MICROPHONE_RADIUS,
             env.get(space_editor::style::MICROPHONE_RADIUS) * 1.4,
@@ -0,0 +1,167 @@
+use druid::{kurbo::Line, Cursor, Env, Event, EventCtx, PaintCtx, Point, RenderContext};
+
+use super::{wall, ToolImpl};
+use crate::{
+    commands,
+    widgets::{
+        data::{MaterialIndex, Object, Wall, WallShape},
+        snapping::{self, AngleConstraint, Snap},
+        SpaceEditorProjectData,
+    },
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    /// A circular arc, bent by moving its middle point.
+    Arc,
+    /// A cubic Bézier curve, bent symmetrically by moving its middle point.
+    Bezier,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum State {
+    PlaceStart,
+    PlaceEnd { start: Point },
+    Bend { start: Point, end: Point },
+}
+
+/// Tool for placing curved walls. The endpoints are placed by dragging, after which the curve is
+/// bent by moving the mouse and confirmed with a click.
+pub struct CurveTool {
+    kind: Kind,
+    state: State,
+    snap: Snap,
+}
+
+impl CurveTool {
+    pub fn arc() -> Self {
+        Self {
+            kind: Kind::Arc,
+            state: State::PlaceStart,
+            snap: Snap::none(Point::ZERO),
+        }
+    }
+
+    pub fn bezier() -> Self {
+        Self {
+            kind: Kind::Bezier,
+            ..Self::arc()
+        }
+    }
+
+    /// Returns the wall spanning from `start` to `end`, whose middle point passes through `bend`.
+    fn wall(&self, start: Point, end: Point, bend: Point) -> Wall {
+        let mut wall = Wall {
+            start,
+            end,
+            shape: WallShape::Line,
+            material: MaterialIndex::default(),
+            back_material: None,
+        };
+        if start == end {
+            return wall;
+        }
+
+        let offset = bend - start.midpoint(end);
+        wall.shape = match self.kind {
+            Kind::Arc => WallShape::Arc {
+                sagitta: offset.dot(wall.normal()),
+            },
+            Kind::Bezier => {
+                // A cubic curve's midpoint lies 3/4 of the way from its chord's midpoint to the
+                // midpoint of its control points.
+                let chord = end - start;
+                let offset = offset / 0.75;
+                WallShape::CubicBezier {
+                    control_start: start + chord / 3.0 + offset,
+                    control_end: start + chord * (2.0 / 3.0) + offset,
+                }
+            }
+        };
+        wall
+    }
+}
+
+impl ToolImpl for CurveTool {
+    fn event(
+        &mut self,
+        ctx: &mut EventCtx,
+        event: &Event,
+        data: &mut SpaceEditorProjectData,
+        env: &Env,
+    ) {
+        ctx.set_cursor(&Cursor::Crosshair);
+
+        if let Event::MouseDown(mouse) | Event::MouseUp(mouse) | Event::MouseMove(mouse) = event {
+            // The bend is not snapped, as that would make it impossible to shape the curve freely.
+            self.snap = match self.state {
+                State::PlaceStart => snapping::snap(data, env, mouse.pos, None, &[]),
+                State::PlaceEnd { start } => {
+                    let angle = AngleConstraint::from_mods(mouse.mods, start);
+                    snapping::snap(data, env, mouse.pos, angle, &[])
+                }
+                State::Bend { .. } => Snap::none(mouse.pos),
+            };
+        }
+        let position = self.snap.position;
+
+        match (self.state, event) {
+            (State::PlaceStart, Event::MouseDown(mouse)) if mouse.button.is_left() => {
+                self.state = State::PlaceEnd { start: position };
+                ctx.set_active(true);
+            }
+            (State::PlaceEnd { start }, Event::MouseUp(_)) => {
+                self.state = if start == position {
+                    State::PlaceStart
+                } else {
+                    State::Bend {
+                        start,
+                        end: position,
+                    }
+                };
+                ctx.set_active(false);
+                ctx.request_paint();
+            }
+            (State::Bend { start, end }, Event::MouseDown(mouse)) => {
+                if mouse.button.is_left() {
+                    let wall = self.wall(start, end, position);
+                    data.edit_space().objects.insert(Object::Wall(wall));
+                }
+                self.state = State::PlaceStart;
+                ctx.request_paint();
+                ctx.set_handled();
+            }
+            (State::Bend { .. }, Event::Command(command)) if command.is(commands::CANCEL) => {
+                self.state = State::PlaceStart;
+                ctx.request_paint();
+                ctx.set_handled();
+            }
+            (_, Event::MouseMove(_)) => {
+                ctx.request_paint();
+            }
+            _ => (),
+        }
+    }
+
+    fn paint(&mut self, ctx: &mut PaintCtx, data: &SpaceEditorProjectData, env: &Env) {
+        let viewport_size = ctx.size();
+        let primary_color = env.get(wall::style::PRIMARY_COLOR);
+        let line_thickness = env.get(wall::style::LINE_THICKNESS);
+        match self.state {
+            State::PlaceStart => (),
+            State::PlaceEnd { start } => {
+                let start = data.transform.to_screen_space(start, viewport_size);
+                let end = data
+                    .transform
+                    .to_screen_space(self.snap.position, viewport_size);
+                ctx.stroke(Line::new(start, end), &primary_color, line_thickness);
+            }
+            State::Bend { start, end } => {
+                let wall = self.wall(start, end, self.snap.position);
+                let path = data.transform.to_screen_affine(viewport_size) * wall.to_path();
+                ctx.stroke(path, &primary_color, line_thickness);
+            }
+        }
+        snapping::paint_indicator(ctx, &data.transform, &self.snap, env);
+    }
+}
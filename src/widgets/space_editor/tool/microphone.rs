@@ -0,0 +1,81 @@
+use druid::{kurbo::Circle, Cursor, Env, Event, EventCtx, PaintCtx, RenderContext};
+
+use super::ToolImpl;
+use crate::widgets::{
+    data::{Microphone, Object},
+    snapping::{self, Snap},
+    space_editor, SpaceEditorProjectData,
+};
+
+pub struct MicrophoneTool {
+    snap: Option<Snap>,
+}
+
+impl MicrophoneTool {
+    pub fn new() -> Self {
+        Self { snap: None }
+    }
+}
+
+impl ToolImpl for MicrophoneTool {
+    fn event(
+        &mut self,
+        ctx: &mut EventCtx,
+        event: &Event,
+        data: &mut SpaceEditorProjectData,
+        env: &Env,
+    ) {
+        ctx.set_cursor(&Cursor::Crosshair);
+
+        if let Event::MouseDown(mouse) | Event::MouseMove(mouse) = event {
+            self.snap = Some(snapping::snap(data, env, mouse.pos, None, &[]));
+        }
+
+        match event {
+            Event::MouseDown(mouse) if mouse.button.is_left() => {
+                data.edit_space()
+                    .objects
+                    .insert(Object::Microphone(Microphone {
+                        position: self.snap.map_or(mouse.pos, |snap| snap.position),
+                    }));
+                ctx.request_paint();
+                ctx.set_handled();
+            }
+            Event::MouseMove(_) => {
+                ctx.request_paint();
+            }
+            _ => (),
+        }
+    }
+
+    fn paint(&mut self, ctx: &mut PaintCtx, data: &SpaceEditorProjectData, env: &Env) {
+        if let Some(snap) = &self.snap {
+            let zoom = data.transform.zoom();
+            let position = data.transform.to_screen_space(snap.position, ctx.size());
+            let thickness = env.get(space_editor::style::MICROPHONE_THICKNESS) * zoom;
+            let radius = env.get(space_editor::style::MICROPHONE_RADIUS) * zoom - thickness * 0.5;
+            ctx.stroke(
+                Circle::new(position, radius),
+                &env.get(style::PREVIEW_COLOR),
+                thickness,
+            );
+            snapping::paint_indicator(ctx, &data.transform, snap, env);
+        }
+    }
+}
+
+pub mod style {
+    use druid::{Color, Env, Key};
+
+    use crate::widgets::space_editor;
+
+    pub const PREVIEW_COLOR: Key<Color> = style_key!("tool.microphone.preview-color");
+
+    pub fn configure_env(env: &mut Env) {
+        env.set(
+            PREVIEW_COLOR,
+            env.get(space_editor::style::MICROPHONE_COLOR)
+                .with_alpha(0.5),
+        );
+    }
+}
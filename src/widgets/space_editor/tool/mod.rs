@@ -1,10 +1,18 @@
 mod cursor;
+mod curve;
+mod microphone;
+mod ruler;
+mod shape;
+mod speaker;
 mod wall;
 
 use druid::{Data, Env, Event, EventCtx, PaintCtx};
 use serde::{Deserialize, Serialize};
 
-use self::{cursor::CursorTool, wall::WallTool};
+use self::{
+    cursor::CursorTool, curve::CurveTool, microphone::MicrophoneTool, ruler::RulerTool,
+    shape::ShapeTool, speaker::SpeakerTool, wall::WallTool,
+};
 use super::SpaceEditorProjectData;
 
 pub trait ToolImpl {
@@ -24,6 +32,15 @@ pub enum Tool {
     #[default]
     Cursor,
     Wall,
+    Polyline,
+    Rectangle,
+    Polygon,
+    Arc,
+    Bezier,
+    Microphone,
+    Speaker,
+    Ruler,
+    Calibrate,
 }
 
 impl Tool {
@@ -31,6 +48,15 @@ impl Tool {
         match self {
             Tool::Cursor => Box::new(CursorTool::new()),
             Tool::Wall => Box::new(WallTool::new()),
+            Tool::Polyline => Box::new(WallTool::polyline()),
+            Tool::Rectangle => Box::new(ShapeTool::rectangle()),
+            Tool::Polygon => Box::new(ShapeTool::polygon()),
+            Tool::Arc => Box::new(CurveTool::arc()),
+            Tool::Bezier => Box::new(CurveTool::bezier()),
+            Tool::Microphone => Box::new(MicrophoneTool::new()),
+            Tool::Speaker => Box::new(SpeakerTool::new()),
+            Tool::Ruler => Box::new(RulerTool::new()),
+            Tool::Calibrate => Box::new(RulerTool::calibrate()),
         }
     }
 }
@@ -43,5 +69,8 @@ pub mod style {
     pub fn configure_env(env: &mut Env) {
         cursor::style::configure_env(env);
         wall::style::configure_env(env);
+        microphone::style::configure_env(env);
+        speaker::style::configure_env(env);
+        ruler::style::configure_env(env);
     }
 }
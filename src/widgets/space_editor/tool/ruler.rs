@@ -0,0 +1,178 @@
+use druid::{
+    kurbo::{Circle, Line},
+    Cursor, Env, Event, EventCtx, PaintCtx, Point, RenderContext,
+};
+
+use super::ToolImpl;
+use crate::{
+    commands,
+    widgets::{
+        snapping::{self, AngleConstraint, Snap},
+        units, SpaceEditorProjectData,
+    },
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    /// Measures the distance between two points, along with the time it takes sound to travel
+    /// it.
+    Measure,
+    /// Scales the background image such that the distance between two points becomes the
+    /// image's calibration length.
+    Calibrate,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum State {
+    Idle,
+    Measuring {
+        start: Point,
+    },
+    /// The measurement is kept on screen until the next one is started.
+    Measured {
+        start: Point,
+        end: Point,
+    },
+}
+
+/// Tool for measuring distances.
+///
+/// The ruler does not modify the space in any way.
+pub struct RulerTool {
+    mode: Mode,
+    state: State,
+    snap: Snap,
+}
+
+impl RulerTool {
+    pub fn new() -> Self {
+        Self {
+            mode: Mode::Measure,
+            state: State::Idle,
+            snap: Snap::none(Point::ZERO),
+        }
+    }
+
+    pub fn calibrate() -> Self {
+        Self {
+            mode: Mode::Calibrate,
+            ..Self::new()
+        }
+    }
+}
+
+impl ToolImpl for RulerTool {
+    fn event(
+        &mut self,
+        ctx: &mut EventCtx,
+        event: &Event,
+        data: &mut SpaceEditorProjectData,
+        env: &Env,
+    ) {
+        ctx.set_cursor(&Cursor::Crosshair);
+
+        if let Event::MouseDown(mouse) | Event::MouseUp(mouse) | Event::MouseMove(mouse) = event {
+            let angle = match self.state {
+                State::Measuring { start } => AngleConstraint::from_mods(mouse.mods, start),
+                _ => None,
+            };
+            self.snap = snapping::snap(data, env, mouse.pos, angle, &[]);
+        }
+        let position = self.snap.position;
+
+        match (self.state, event) {
+            (_, Event::MouseDown(mouse)) if mouse.button.is_left() => {
+                self.state = State::Measuring { start: position };
+                ctx.set_active(true);
+                ctx.request_paint();
+            }
+            (State::Measuring { start }, Event::MouseUp(_)) => {
+                self.state = match self.mode {
+                    Mode::Measure => State::Measured {
+                        start,
+                        end: position,
+                    },
+                    Mode::Calibrate => {
+                        if let Some(background) = &mut data.background {
+                            background.calibrate(start, position);
+                        }
+                        State::Idle
+                    }
+                };
+                ctx.set_active(false);
+                ctx.request_paint();
+            }
+            (State::Measured { .. }, Event::Command(command)) if command.is(commands::CANCEL) => {
+                self.state = State::Idle;
+                ctx.request_paint();
+                ctx.set_handled();
+            }
+            (_, Event::MouseMove(_)) => {
+                ctx.request_paint();
+            }
+            _ => (),
+        }
+    }
+
+    fn paint(&mut self, ctx: &mut PaintCtx, data: &SpaceEditorProjectData, env: &Env) {
+        let (start, end) = match self.state {
+            State::Idle => {
+                snapping::paint_indicator(ctx, &data.transform, &self.snap, env);
+                return;
+            }
+            State::Measuring { start } => (start, self.snap.position),
+            State::Measured { start, end } => (start, end),
+        };
+
+        let viewport_size = ctx.size();
+        let screen_start = data.transform.to_screen_space(start, viewport_size);
+        let screen_end = data.transform.to_screen_space(end, viewport_size);
+        let color = env.get(style::COLOR);
+        ctx.stroke(
+            Line::new(screen_start, screen_end),
+            &color,
+            env.get(style::LINE_THICKNESS),
+        );
+        for point in [screen_start, screen_end] {
+            ctx.fill(Circle::new(point, env.get(style::END_RADIUS)), &color);
+        }
+
+        let unit = data.space.unit;
+        let length = start.distance(end);
+        let text = match (self.mode, &data.background) {
+            (Mode::Measure, _) => format!(
+                "{}  ·  {:.2} ms",
+                unit.format(length),
+                unit.delay_ms(length)
+            ),
+            (Mode::Calibrate, Some(background)) => format!(
+                "{} → {}",
+                unit.format(length),
+                unit.format(background.calibration_length)
+            ),
+            (Mode::Calibrate, None) => "No background image".into(),
+        };
+        let center = screen_start.midpoint(screen_end);
+        units::paint_label(ctx, text, center, env);
+
+        if let State::Measuring { .. } = self.state {
+            snapping::paint_indicator(ctx, &data.transform, &self.snap, env);
+        }
+    }
+}
+
+pub mod style {
+    use druid::{Color, Env, Key};
+
+    use crate::style::color;
+
+    pub const COLOR: Key<Color> = style_key!("tool.ruler.color");
+    pub const LINE_THICKNESS: Key<f64> = style_key!("tool.ruler.line-thickness");
+    pub const END_RADIUS: Key<f64> = style_key!("tool.ruler.end-radius");
+
+    pub fn configure_env(env: &mut Env) {
+        env.set(COLOR, color(0xE0A100));
+        env.set(LINE_THICKNESS, 2.0);
+        env.set(END_RADIUS, 4.0);
+    }
+}
@@ -0,0 +1,164 @@
+use druid::{kurbo::Line, Cursor, Env, Event, EventCtx, PaintCtx, Point, Rect, RenderContext};
+use fizzerb_model::walls;
+
+use super::{wall, ToolImpl};
+use crate::{
+    commands,
+    math::DruidExtToGlam,
+    widgets::{
+        data::{MaterialIndex, Object, Wall},
+        snapping::{self, AngleConstraint, Snap},
+        SpaceEditorProjectData,
+    },
+};
+
+const MIN_POLYGON_SIDES: usize = 3;
+const MAX_POLYGON_SIDES: usize = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Shape {
+    /// An axis-aligned rectangle spanned between two opposite corners.
+    Rectangle,
+    /// A regular polygon spanned between its center and one of its vertices.
+    Polygon { sides: usize },
+}
+
+#[derive(Debug, Clone, Copy)]
+enum State {
+    Idle,
+    Dragging { start: Point },
+}
+
+/// Tool for placing whole rooms at once.
+pub struct ShapeTool {
+    shape: Shape,
+    state: State,
+    snap: Snap,
+}
+
+impl ShapeTool {
+    pub fn rectangle() -> Self {
+        Self {
+            shape: Shape::Rectangle,
+            state: State::Idle,
+            snap: Snap::none(Point::ZERO),
+        }
+    }
+
+    pub fn polygon() -> Self {
+        Self {
+            shape: Shape::Polygon { sides: 6 },
+            ..Self::rectangle()
+        }
+    }
+
+    /// Returns the walls making up the shape spanned between `start` and `end`.
+    fn walls(&self, start: Point, end: Point) -> Vec<Wall> {
+        let material = MaterialIndex::default().to_model();
+        let walls: Vec<_> = match self.shape {
+            Shape::Rectangle => {
+                let rect = Rect::from_points(start, end);
+                if rect.size().is_empty() {
+                    return vec![];
+                }
+                let position = rect.origin().to_glam();
+                let size = rect.size().to_vec2().to_glam();
+                walls::make_box(position, size, material).collect()
+            }
+            Shape::Polygon { sides } => {
+                let radius = start.distance(end);
+                if radius == 0.0 {
+                    return vec![];
+                }
+                let angle = (end - start).atan2();
+                walls::make_regular_polygon(
+                    start.to_glam(),
+                    radius as f32,
+                    sides,
+                    angle as f32,
+                    material,
+                )
+                .collect()
+            }
+        };
+        walls.iter().map(Wall::from_model).collect()
+    }
+
+    fn change_sides(&mut self, delta: isize) {
+        if let Shape::Polygon { sides } = &mut self.shape {
+            *sides = (*sides as isize + delta)
+                .clamp(MIN_POLYGON_SIDES as isize, MAX_POLYGON_SIDES as isize)
+                as usize;
+        }
+    }
+}
+
+impl ToolImpl for ShapeTool {
+    fn event(
+        &mut self,
+        ctx: &mut EventCtx,
+        event: &Event,
+        data: &mut SpaceEditorProjectData,
+        env: &Env,
+    ) {
+        ctx.set_cursor(&Cursor::Crosshair);
+
+        if let Event::MouseDown(mouse) | Event::MouseUp(mouse) | Event::MouseMove(mouse) = event {
+            let angle = match self.state {
+                State::Idle => None,
+                State::Dragging { start } => AngleConstraint::from_mods(mouse.mods, start),
+            };
+            self.snap = snapping::snap(data, env, mouse.pos, angle, &[]);
+        }
+        let position = self.snap.position;
+
+        match (self.state, event) {
+            (State::Idle, Event::MouseDown(mouse)) if mouse.button.is_left() => {
+                self.state = State::Dragging { start: position };
+                ctx.set_active(true);
+            }
+            (State::Dragging { start }, Event::MouseUp(_)) => {
+                let walls = self.walls(start, position);
+                if !walls.is_empty() {
+                    let space = data.edit_space();
+                    for wall in walls {
+                        space.objects.insert(Object::Wall(wall));
+                    }
+                }
+                self.state = State::Idle;
+                ctx.set_active(false);
+                ctx.request_paint();
+            }
+            (_, Event::MouseMove(_)) => {
+                ctx.request_paint();
+            }
+            (_, Event::Command(command)) if command.is(commands::INCREASE_SIDES) => {
+                self.change_sides(1);
+                ctx.request_paint();
+                ctx.set_handled();
+            }
+            (_, Event::Command(command)) if command.is(commands::DECREASE_SIDES) => {
+                self.change_sides(-1);
+                ctx.request_paint();
+                ctx.set_handled();
+            }
+            _ => (),
+        }
+    }
+
+    fn paint(&mut self, ctx: &mut PaintCtx, data: &SpaceEditorProjectData, env: &Env) {
+        if let State::Dragging { start } = self.state {
+            let viewport_size = ctx.size();
+            for wall in self.walls(start, self.snap.position) {
+                let start = data.transform.to_screen_space(wall.start, viewport_size);
+                let end = data.transform.to_screen_space(wall.end, viewport_size);
+                ctx.stroke(
+                    Line::new(start, end),
+                    &env.get(wall::style::PRIMARY_COLOR),
+                    env.get(wall::style::LINE_THICKNESS),
+                );
+            }
+        }
+        snapping::paint_indicator(ctx, &data.transform, &self.snap, env);
+    }
+}
@@ -0,0 +1,80 @@
+use druid::{kurbo::Circle, Cursor, Env, Event, EventCtx, PaintCtx, RenderContext};
+
+use super::ToolImpl;
+use crate::widgets::{
+    data::{Object, Speaker},
+    snapping::{self, Snap},
+    space_editor, SpaceEditorProjectData,
+};
+
+/// The power newly placed speakers start out with.
+const DEFAULT_POWER: f32 = 10.0;
+
+pub struct SpeakerTool {
+    snap: Option<Snap>,
+}
+
+impl SpeakerTool {
+    pub fn new() -> Self {
+        Self { snap: None }
+    }
+}
+
+impl ToolImpl for SpeakerTool {
+    fn event(
+        &mut self,
+        ctx: &mut EventCtx,
+        event: &Event,
+        data: &mut SpaceEditorProjectData,
+        env: &Env,
+    ) {
+        ctx.set_cursor(&Cursor::Crosshair);
+
+        if let Event::MouseDown(mouse) | Event::MouseMove(mouse) = event {
+            self.snap = Some(snapping::snap(data, env, mouse.pos, None, &[]));
+        }
+
+        match event {
+            Event::MouseDown(mouse) if mouse.button.is_left() => {
+                data.edit_space().objects.insert(Object::Speaker(Speaker {
+                    position: self.snap.map_or(mouse.pos, |snap| snap.position),
+                    power: DEFAULT_POWER,
+                    ..Speaker::default()
+                }));
+                ctx.request_paint();
+                ctx.set_handled();
+            }
+            Event::MouseMove(_) => {
+                ctx.request_paint();
+            }
+            _ => (),
+        }
+    }
+
+    fn paint(&mut self, ctx: &mut PaintCtx, data: &SpaceEditorProjectData, env: &Env) {
+        if let Some(snap) = &self.snap {
+            let position = data.transform.to_screen_space(snap.position, ctx.size());
+            let radius = env.get(space_editor::style::SPEAKER_RADIUS) * data.transform.zoom();
+            ctx.fill(
+                Circle::new(position, radius),
+                &env.get(style::PREVIEW_COLOR),
+            );
+            snapping::paint_indicator(ctx, &data.transform, snap, env);
+        }
+    }
+}
+
+pub mod style {
+    use druid::{Color, Env, Key};
+
+    use crate::widgets::space_editor;
+
+    pub const PREVIEW_COLOR: Key<Color> = style_key!("tool.speaker.preview-color");
+
+    pub fn configure_env(env: &mut Env) {
+        env.set(
+            PREVIEW_COLOR,
+            env.get(space_editor::style::SPEAKER_COLOR).with_alpha(0.5),
+        );
+    }
+}
@@ -4,27 +4,106 @@ use druid::{
 };
 
 use super::ToolImpl;
-use crate::widgets::{
-    data::{MaterialIndex, Object, Wall},
-    SpaceEditorProjectData,
+use crate::{
+    commands,
+    math::PointExtHitTests,
+    widgets::{
+        data::{MaterialIndex, Object, Wall, WallShape},
+        snapping::{self, AngleConstraint, Snap},
+        units, SpaceEditorProjectData,
+    },
 };
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    /// Each click-drag places a single wall.
+    Segment,
+    /// Each click places a wall continuing from the end of the previous one.
+    Polyline,
+}
+
 #[derive(Debug, Clone, Copy)]
 enum State {
     PlaceStart,
-    PlaceEnd { start: Point },
+    PlaceEnd {
+        start: Point,
+    },
+    Chain {
+        first: Point,
+        last: Point,
+        walls: usize,
+    },
 }
 
 pub struct WallTool {
+    mode: Mode,
     state: State,
-    mouse_pos: Point,
+    snap: Snap,
 }
 
 impl WallTool {
     pub fn new() -> Self {
         Self {
+            mode: Mode::Segment,
             state: State::PlaceStart,
-            mouse_pos: Point::ZERO,
+            snap: Snap::none(Point::ZERO),
+        }
+    }
+
+    pub fn polyline() -> Self {
+        Self {
+            mode: Mode::Polyline,
+            ..Self::new()
+        }
+    }
+
+    fn place_wall(data: &mut SpaceEditorProjectData, start: Point, end: Point) {
+        data.edit_space().objects.insert(Object::Wall(Wall {
+            start,
+            end,
+            shape: WallShape::Line,
+            material: MaterialIndex::default(),
+            back_material: None,
+        }));
+    }
+
+    /// Returns the point from which the angle of the wall being placed is measured.
+    fn angle_origin(&self) -> Option<Point> {
+        match self.state {
+            State::PlaceStart => None,
+            State::PlaceEnd { start } => Some(start),
+            State::Chain { last, .. } => Some(last),
+        }
+    }
+
+    /// Returns whether the given point lies on the first vertex of the chain being drawn, meaning
+    /// that placing a vertex there would close the loop.
+    fn closes_chain(&self, point: Point, data: &SpaceEditorProjectData, env: &Env) -> bool {
+        match self.state {
+            State::Chain { first, walls, .. } if walls >= 2 => {
+                let radius = env.get(style::VERTEX_OUTER_RADIUS) / data.transform.zoom();
+                point.in_circle(first, radius)
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns the start and end point of the wall that would be placed by the next click.
+    fn wall_being_placed(
+        &self,
+        data: &SpaceEditorProjectData,
+        env: &Env,
+    ) -> Option<(Point, Point)> {
+        match self.state {
+            State::PlaceStart => None,
+            State::PlaceEnd { start } => Some((start, self.snap.position)),
+            State::Chain { first, last, .. } => {
+                if self.closes_chain(self.snap.position, data, env) {
+                    Some((last, first))
+                } else {
+                    Some((last, self.snap.position))
+                }
+            }
         }
     }
 }
@@ -35,28 +114,62 @@ impl ToolImpl for WallTool {
         ctx: &mut EventCtx,
         event: &Event,
         data: &mut SpaceEditorProjectData,
-        _env: &Env,
+        env: &Env,
     ) {
         ctx.set_cursor(&Cursor::Crosshair);
 
+        if let Event::MouseDown(mouse) | Event::MouseUp(mouse) | Event::MouseMove(mouse) = event {
+            let angle = self
+                .angle_origin()
+                .and_then(|origin| AngleConstraint::from_mods(mouse.mods, origin));
+            self.snap = snapping::snap(data, env, mouse.pos, angle, &[]);
+        }
+        let position = self.snap.position;
+
         match (&self.state, event) {
-            (State::PlaceStart, Event::MouseDown(mouse)) => {
-                self.state = State::PlaceEnd { start: mouse.pos };
+            (State::PlaceStart, Event::MouseDown(mouse)) if mouse.button.is_left() => {
+                self.state = match self.mode {
+                    Mode::Segment => State::PlaceEnd { start: position },
+                    Mode::Polyline => State::Chain {
+                        first: position,
+                        last: position,
+                        walls: 0,
+                    },
+                };
                 ctx.set_active(true);
             }
-            (&State::PlaceEnd { start }, Event::MouseUp(mouse)) => {
-                let end = mouse.pos;
-                data.edit_space().objects.insert(Object::Wall(Wall {
-                    start,
-                    end,
-                    material: MaterialIndex::default(),
-                }));
+            (&State::PlaceEnd { start }, Event::MouseUp(_)) => {
+                // A click without a drag doesn't make a wall.
+                if position != start {
+                    Self::place_wall(data, start, position);
+                }
                 ctx.request_paint();
                 ctx.set_active(true);
                 self.state = State::PlaceStart;
             }
-            (_, Event::MouseMove(mouse)) => {
-                self.mouse_pos = mouse.pos;
+            (&State::Chain { first, last, walls }, Event::MouseDown(mouse)) => {
+                if mouse.button.is_right() {
+                    self.state = State::PlaceStart;
+                } else if self.closes_chain(position, data, env) {
+                    Self::place_wall(data, last, first);
+                    self.state = State::PlaceStart;
+                } else if position != last {
+                    Self::place_wall(data, last, position);
+                    self.state = State::Chain {
+                        first,
+                        last: position,
+                        walls: walls + 1,
+                    };
+                }
+                ctx.request_paint();
+                ctx.set_handled();
+            }
+            (State::Chain { .. }, Event::Command(command)) if command.is(commands::CANCEL) => {
+                self.state = State::PlaceStart;
+                ctx.request_paint();
+                ctx.set_handled();
+            }
+            (_, Event::MouseMove(_)) => {
                 ctx.request_paint();
             }
             _ => (),
@@ -67,25 +180,51 @@ impl ToolImpl for WallTool {
         let viewport_size = ctx.size();
         let end = data
             .transform
-            .to_screen_space(self.mouse_pos, viewport_size);
-        match &self.state {
+            .to_screen_space(self.snap.position, viewport_size);
+        match self.state {
             State::PlaceStart => {
                 paint_vertex(ctx, end, env);
             }
-            &State::PlaceEnd { start } => {
+            State::PlaceEnd { start } => {
                 let start = data.transform.to_screen_space(start, viewport_size);
-                ctx.stroke(
-                    Line::new(start, end),
-                    &env.get(style::PRIMARY_COLOR),
-                    env.get(style::LINE_THICKNESS),
-                );
+                paint_line(ctx, start, end, env);
                 paint_vertex(ctx, start, env);
                 paint_vertex(ctx, end, env);
             }
+            State::Chain { first, last, .. } => {
+                let closes_chain = self.closes_chain(self.snap.position, data, env);
+                let first = data.transform.to_screen_space(first, viewport_size);
+                let last = data.transform.to_screen_space(last, viewport_size);
+                let end = if closes_chain { first } else { end };
+                paint_line(ctx, last, end, env);
+                paint_vertex(ctx, first, env);
+                paint_vertex(ctx, last, env);
+                if closes_chain {
+                    ctx.stroke(
+                        Circle::new(first, env.get(style::VERTEX_OUTER_RADIUS)),
+                        &env.get(style::PRIMARY_COLOR),
+                        env.get(style::LINE_THICKNESS),
+                    );
+                } else {
+                    paint_vertex(ctx, end, env);
+                }
+            }
+        }
+        if let Some((start, end)) = self.wall_being_placed(data, env) {
+            units::paint_length_label(ctx, data, start, end, env);
         }
+        snapping::paint_indicator(ctx, &data.transform, &self.snap, env);
     }
 }
 
+fn paint_line(ctx: &mut PaintCtx, start: Point, end: Point, env: &Env) {
+    ctx.stroke(
+        Line::new(start, end),
+        &env.get(style::PRIMARY_COLOR),
+        env.get(style::LINE_THICKNESS),
+    );
+}
+
 fn paint_vertex(ctx: &mut PaintCtx, vertex: Point, env: &Env) {
     ctx.fill(
         Circle::new(vertex, env.get(style::VERTEX_OUTER_RADIUS)),
@@ -1,4 +1,4 @@
-use druid::{Data, Event, MouseEvent, Point, Size, Vec2};
+use druid::{Affine, Data, Event, MouseEvent, Point, Size, Vec2};
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Data, Deserialize, Serialize)]
@@ -7,6 +7,15 @@ pub struct Transform {
     pub zoom_level: f64,
 }
 
+impl Default for Transform {
+    fn default() -> Self {
+        Self {
+            pan: Vec2::new(0.0, 0.0),
+            zoom_level: 24.0,
+        }
+    }
+}
+
 impl Transform {
     /// Pans the viewport transform by the given amount.
     pub fn pan_by(&mut self, delta: Vec2) {
@@ -34,6 +43,16 @@ impl Transform {
         vec.to_point()
     }
 
+    /// Returns the affine transform converting from viewport space to screen space.
+    ///
+    /// This is the same transformation as [`Transform::to_screen_space`], but applicable to
+    /// shapes.
+    pub fn to_screen_affine(&self, viewport_size: Size) -> Affine {
+        Affine::translate(viewport_size.to_vec2() / 2.0)
+            * Affine::scale(self.zoom())
+            * Affine::translate(-self.pan)
+    }
+
     /// Converts the screen space mouse position in the given event to a viewport space position,
     /// and returns a new event with the altered position.
     ///
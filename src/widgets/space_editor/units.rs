@@ -0,0 +1,197 @@
+//! Physical units of length, and painting of measurements in the editor.
+
+use druid::{kurbo::Line, Data, Env, PaintCtx, Point, Rect, RenderContext, Size, TextLayout, Vec2};
+use fizzerb_tracer::SPEED_OF_SOUND_IN_AIR;
+use serde::{Deserialize, Serialize};
+
+use super::SpaceEditorProjectData;
+use crate::math::nice_step;
+
+/// The unit of length that coordinates in a space are expressed in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Data, Deserialize, Serialize)]
+pub enum Unit {
+    #[default]
+    Metres,
+    Centimetres,
+    Feet,
+}
+
+impl Unit {
+    pub const ALL: [Unit; 3] = [Unit::Metres, Unit::Centimetres, Unit::Feet];
+
+    /// Returns how many metres a single unit is.
+    pub fn to_metres(self) -> f64 {
+        match self {
+            Unit::Metres => 1.0,
+            Unit::Centimetres => 0.01,
+            Unit::Feet => 0.3048,
+        }
+    }
+
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Unit::Metres => "m",
+            Unit::Centimetres => "cm",
+            Unit::Feet => "ft",
+        }
+    }
+
+    /// Returns the unit following this one in [`Unit::ALL`], wrapping around at the end.
+    pub fn next(self) -> Unit {
+        let index = Unit::ALL.iter().position(|&unit| unit == self).unwrap_or(0);
+        Unit::ALL[(index + 1) % Unit::ALL.len()]
+    }
+
+    /// Formats a length expressed in this unit for display.
+    pub fn format(self, length: f64) -> String {
+        let precision = match self {
+            Unit::Metres | Unit::Feet => 2,
+            Unit::Centimetres => 0,
+        };
+        format!("{:.*} {}", precision, length, self.symbol())
+    }
+
+    /// Returns the time it takes sound to travel the given length in air, in milliseconds.
+    pub fn delay_ms(self, length: f64) -> f64 {
+        length * self.to_metres() / SPEED_OF_SOUND_IN_AIR as f64 * 1000.0
+    }
+}
+
+fn label_layout(ctx: &mut PaintCtx, text: String, env: &Env) -> TextLayout<String> {
+    let mut layout = TextLayout::from_text(text);
+    layout.set_font(style::LABEL_FONT);
+    layout.set_text_color(style::LABEL_TEXT_COLOR);
+    layout.rebuild_if_needed(ctx.text(), env);
+    layout
+}
+
+/// Returns the size of a label's background, including padding.
+fn label_size(layout: &TextLayout<String>, env: &Env) -> Size {
+    let padding = env.get(style::LABEL_PADDING);
+    layout.size() + Size::new(padding * 2.0, padding)
+}
+
+fn paint_label_layout(ctx: &mut PaintCtx, layout: &TextLayout<String>, rect: Rect, env: &Env) {
+    let background = rect.to_rounded_rect(env.get(style::LABEL_CORNER_RADIUS));
+    ctx.fill(background, &env.get(style::LABEL_BACKGROUND));
+    layout.draw(ctx, rect.center() - layout.size().to_vec2() / 2.0);
+}
+
+/// Paints a text label centered at the given screen space position.
+pub fn paint_label(ctx: &mut PaintCtx, text: String, center: Point, env: &Env) {
+    let layout = label_layout(ctx, text, env);
+    let rect = Rect::from_center_size(center, label_size(&layout, env));
+    paint_label_layout(ctx, &layout, rect, env);
+}
+
+/// Paints a label showing the length of a line between two viewport space points, next to the
+/// line's midpoint.
+pub fn paint_length_label(
+    ctx: &mut PaintCtx,
+    data: &SpaceEditorProjectData,
+    start: Point,
+    end: Point,
+    env: &Env,
+) {
+    let length = start.distance(end);
+    if length <= 0.0 {
+        return;
+    }
+    let viewport_size = ctx.size();
+    let midpoint = data
+        .transform
+        .to_screen_space(start.midpoint(end), viewport_size);
+    // Push the label off to the side of the line, so that it doesn't obscure it.
+    let direction = (end - start) / length;
+    let offset = Vec2::new(-direction.y, direction.x) * env.get(style::LABEL_OFFSET);
+    paint_label(ctx, data.space.unit.format(length), midpoint + offset, env);
+}
+
+/// Paints a scale bar and the viewport space coordinates of the mouse in the bottom left corner
+/// of the editor.
+pub fn paint_scale_bar(ctx: &mut PaintCtx, data: &SpaceEditorProjectData, mouse: Point, env: &Env) {
+    let viewport_size = ctx.size();
+    let zoom = data.transform.zoom();
+    let unit = data.space.unit;
+    let margin = env.get(style::SCALE_BAR_MARGIN);
+    let color = env.get(style::SCALE_BAR_COLOR);
+    let thickness = env.get(style::SCALE_BAR_THICKNESS);
+    let tick_size = env.get(style::SCALE_BAR_TICK_SIZE);
+
+    let length = nice_step(env.get(style::MIN_SCALE_BAR_WIDTH) / zoom);
+    let width = length * zoom;
+    let left = margin;
+    let right = margin + width;
+    let y = viewport_size.height - margin;
+    ctx.stroke(Line::new((left, y), (right, y)), &color, thickness);
+    ctx.stroke(
+        Line::new((left, y - tick_size), (left, y)),
+        &color,
+        thickness,
+    );
+    ctx.stroke(
+        Line::new((right, y - tick_size), (right, y)),
+        &color,
+        thickness,
+    );
+
+    let label_y = y - tick_size - env.get(style::LABEL_OFFSET);
+    paint_label(
+        ctx,
+        unit.format(length),
+        Point::new((left + right) / 2.0, label_y),
+        env,
+    );
+
+    let position = data.transform.to_viewport_space(mouse, viewport_size);
+    let readout = format!("{}, {}", unit.format(position.x), unit.format(position.y));
+    let layout = label_layout(ctx, readout, env);
+    let size = label_size(&layout, env);
+    let rect = Rect::from_origin_size(
+        Point::new(right + margin, label_y - size.height / 2.0),
+        size,
+    );
+    paint_label_layout(ctx, &layout, rect, env);
+}
+
+pub mod style {
+    use druid::{Color, Env, FontDescriptor, FontWeight, Key};
+
+    use crate::style::color;
+
+    pub const LABEL_FONT: Key<FontDescriptor> = style_key!("space-editor.label.font");
+    pub const LABEL_TEXT_COLOR: Key<Color> = style_key!("space-editor.label.text-color");
+    pub const LABEL_BACKGROUND: Key<Color> = style_key!("space-editor.label.background");
+    pub const LABEL_PADDING: Key<f64> = style_key!("space-editor.label.padding");
+    pub const LABEL_CORNER_RADIUS: Key<f64> = style_key!("space-editor.label.corner-radius");
+    pub const LABEL_OFFSET: Key<f64> = style_key!("space-editor.label.offset");
+
+    pub const MIN_SCALE_BAR_WIDTH: Key<f64> = style_key!("space-editor.scale-bar.min-width");
+    pub const SCALE_BAR_MARGIN: Key<f64> = style_key!("space-editor.scale-bar.margin");
+    pub const SCALE_BAR_COLOR: Key<Color> = style_key!("space-editor.scale-bar.color");
+    pub const SCALE_BAR_THICKNESS: Key<f64> = style_key!("space-editor.scale-bar.thickness");
+    pub const SCALE_BAR_TICK_SIZE: Key<f64> = style_key!("space-editor.scale-bar.tick-size");
+
+    pub fn configure_env(env: &mut Env) {
+        let font = env.get(crate::style::TEXT);
+        env.set(
+            LABEL_FONT,
+            FontDescriptor {
+                size: 11.0,
+                weight: FontWeight::MEDIUM,
+                ..font
+            },
+        );
+        env.set(LABEL_TEXT_COLOR, color(0xFFFFFF));
+        env.set(LABEL_BACKGROUND, color(0x071013).with_alpha(0.75));
+        env.set(LABEL_PADDING, 6.0);
+        env.set(LABEL_CORNER_RADIUS, 4.0);
+        env.set(LABEL_OFFSET, 16.0);
+
+        env.set(MIN_SCALE_BAR_WIDTH, 80.0);
+        env.set(SCALE_BAR_MARGIN, 16.0);
+        env.set(SCALE_BAR_COLOR, color(0x071013));
+        env.set(SCALE_BAR_THICKNESS, 1.5);
+        env.set(SCALE_BAR_TICK_SIZE, 6.0);
+    }
+}